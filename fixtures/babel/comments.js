@@ -0,0 +1,11 @@
+/*! license */
+// leading
+let a = 1; // trailing
+
+function f(/* params */) {
+  // only
+}
+call(a, /* after comma */);
+let o = { a /* after a */, b: [/* empty */] };
+x = a / 2 /* divide */;
+/* end */
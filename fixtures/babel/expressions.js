@@ -0,0 +1,10 @@
+a = (b | c) && !d;
+x += typeof y === "string" ? -1 : delete z[0] in w;
+new Foo;
+f(a, b)(c).d++;
+(--i, this.j = [1, , 3]), k;
+(a + b) * ((c - d));
+o = {a, b: [c], d() {}};
+(a).b = (x);
+(function () {});
+[(c)] = ("s");
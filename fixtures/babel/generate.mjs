@@ -0,0 +1,17 @@
+// Writes what @babel/parser makes of every fixture to `<name>.babel.json`,
+// the reference `test_babel_reference` compares against:
+//
+//     npm install @babel/parser && node fixtures/babel/generate.mjs
+import { parse } from "@babel/parser";
+import { readdirSync, readFileSync, writeFileSync } from "node:fs";
+
+const dir = new URL(".", import.meta.url);
+for (const name of readdirSync(dir)) {
+  if (!name.endsWith(".js")) {
+    continue;
+  }
+  const source = readFileSync(new URL(name, dir), "utf8");
+  const file = parse(source, { sourceType: "script" });
+  const out = new URL(name.replace(/\.js$/, ".babel.json"), dir);
+  writeFileSync(out, JSON.stringify(file, null, 2));
+}
@@ -0,0 +1,7 @@
+0x1_0; 1e21; .5; 10n;
+'it\'s' + "\x41";
+null; true; false; undefined;
+/[/]+/giu.test(`multi
+line`);
+let π = 3, café = { 1: π, "k": 2, if: 3 };
+x = `a${b}\u0041${c + `n${d}`}\n\`z`;
@@ -0,0 +1,5 @@
+[a.b, {c = 1, d: e[0]}] = f;
+for (let k in o) {}
+for (a.b in o);
+g = ({x, y: [z = 2]}, w = 1) => {};
+while (true) if (a) continue; else break
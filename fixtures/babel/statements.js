@@ -0,0 +1,10 @@
+function f(a, {b, c: [d = 1]}) {
+  return a ? `t` : /x/g;
+}
+var o = {k: 1, 'm'() {}, n}, p;
+for (;;) break;
+do x++; while (y)
+label: try { throw new E(1) } catch (e) {} finally {}
+switch (a) { case 1: b; default: }
+x = (a, b) => a.b[c](d);
+with (o) a = b;
@@ -0,0 +1,17 @@
+'use strict';
+"\x41";
+
+function f(a, b) {
+  "use strict";
+  'another';
+  return a;
+}
+
+const g = () => {
+  "use strict";
+};
+
+("not a directive");
+{
+  "not a directive either";
+}
@@ -0,0 +1,5 @@
+function greet(name) {
+    return "Hello, " + name;
+}
+greet("world");
+//# sourceMappingURL=greet.js.map
@@ -0,0 +1,965 @@
+//! Serializes the AST in the shape `@babel/parser` produces: a `File` wrapper,
+//! `StringLiteral`/`NumericLiteral`, `ObjectProperty`/`ObjectMethod` and
+//! `extra` for raw values and parentheses. Offsets and columns count chars.
+//!
+//! The AST does not keep the exact position of trailing commas, so
+//! `extra.trailingComma` is never written.
+
+use crate::json::{Json, array, optional, string};
+use crate::lex::{Loc, Position};
+use crate::node::{
+    ArrowFunctionBody, AttachedComments, BlockStatement, CatchClause, Comment, CommentKind,
+    Declaration, Directive, Expression, Extra, ForInLeft, ForInit, Function, Identifier,
+    MemberExpression, ModuleItem, NumericLiteral, ObjectMember, ObjectPatternProperty, Pattern,
+    Program, PropertyKey, Statement, StringLiteral, SwitchCase, VariableDeclaration,
+    VariableDeclarator, VariableKind,
+};
+
+pub fn to_json(program: &Program) -> String {
+    file_to_babel(program).to_string_pretty()
+}
+
+pub fn file_to_babel(program: &Program) -> Json {
+    node(
+        "File",
+        &program.loc,
+        vec![
+            ("errors", Json::Array(vec![])),
+            ("program", program_to_babel(program)),
+            (
+                "comments",
+                Json::Array(program.comments.iter().map(comment_to_babel).collect()),
+            ),
+        ],
+    )
+}
+
+pub fn program_to_babel(program: &Program) -> Json {
+    let mut json = node(
+        "Program",
+        &program.loc,
+        vec![
+            ("sourceType", string(program.source_type.name())),
+            ("interpreter", Json::Null),
+            (
+                "body",
+                Json::Array(
+                    program
+                        .body
+                        .iter()
+                        .map(|item| match item {
+                            ModuleItem::Statement(statement) => statement_to_babel(statement),
+                        })
+                        .collect(),
+                ),
+            ),
+            ("directives", array(&program.directives, directive_to_babel)),
+        ],
+    );
+    attach_comments(&mut json, &program.attached_comments, None);
+    json
+}
+
+pub fn statement_to_babel(statement: &Statement) -> Json {
+    match statement {
+        Statement::EmptyStatement { loc } => node("EmptyStatement", loc, vec![]),
+        Statement::BlockStatement(block) => block_to_babel(block),
+        Statement::ExpressionStatement { expression, loc } => node(
+            "ExpressionStatement",
+            loc,
+            vec![("expression", expression_to_babel(expression))],
+        ),
+        Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+            function_to_babel("FunctionDeclaration", function)
+        }
+        Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+            variable_declaration_to_babel(declaration)
+        }
+        Statement::IfStatement {
+            test,
+            consequent,
+            alternate,
+            loc,
+        } => node(
+            "IfStatement",
+            loc,
+            vec![
+                ("test", expression_to_babel(test)),
+                ("consequent", statement_to_babel(consequent)),
+                (
+                    "alternate",
+                    optional(alternate.as_deref(), statement_to_babel),
+                ),
+            ],
+        ),
+        Statement::ForStatement {
+            init,
+            test,
+            update,
+            body,
+            loc,
+        } => node(
+            "ForStatement",
+            loc,
+            vec![
+                (
+                    "init",
+                    match init {
+                        Some(ForInit::VariableDeclaration(declaration)) => {
+                            variable_declaration_to_babel(declaration)
+                        }
+                        Some(ForInit::Expression(expression)) => expression_to_babel(expression),
+                        None => Json::Null,
+                    },
+                ),
+                ("test", optional(test.as_deref(), expression_to_babel)),
+                ("update", optional(update.as_deref(), expression_to_babel)),
+                ("body", statement_to_babel(body)),
+            ],
+        ),
+        Statement::ForInStatement {
+            left,
+            right,
+            body,
+            loc,
+        } => node(
+            "ForInStatement",
+            loc,
+            vec![
+                (
+                    "left",
+                    match left {
+                        ForInLeft::VariableDeclaration(declaration) => {
+                            variable_declaration_to_babel(declaration)
+                        }
+                        ForInLeft::Pattern(pattern) => pattern_to_babel(pattern, false),
+                    },
+                ),
+                ("right", expression_to_babel(right)),
+                ("body", statement_to_babel(body)),
+            ],
+        ),
+        Statement::WhileStatement { test, body, loc } => node(
+            "WhileStatement",
+            loc,
+            vec![
+                ("test", expression_to_babel(test)),
+                ("body", statement_to_babel(body)),
+            ],
+        ),
+        Statement::DoWhileStatement { body, test, loc } => node(
+            "DoWhileStatement",
+            loc,
+            vec![
+                ("body", statement_to_babel(body)),
+                ("test", expression_to_babel(test)),
+            ],
+        ),
+        Statement::WithStatement { object, body, loc } => node(
+            "WithStatement",
+            loc,
+            vec![
+                ("object", expression_to_babel(object)),
+                ("body", statement_to_babel(body)),
+            ],
+        ),
+        Statement::TryStatement {
+            block,
+            handler,
+            finalizer,
+            loc,
+        } => node(
+            "TryStatement",
+            loc,
+            vec![
+                ("block", block_to_babel(block)),
+                ("handler", optional(handler.as_ref(), catch_clause_to_babel)),
+                ("finalizer", optional(finalizer.as_ref(), block_to_babel)),
+            ],
+        ),
+        Statement::ReturnStatement { argument, loc } => node(
+            "ReturnStatement",
+            loc,
+            vec![(
+                "argument",
+                optional(argument.as_deref(), expression_to_babel),
+            )],
+        ),
+        Statement::SwitchStatement {
+            discriminant,
+            cases,
+            loc,
+        } => node(
+            "SwitchStatement",
+            loc,
+            vec![
+                ("discriminant", expression_to_babel(discriminant)),
+                ("cases", array(cases, switch_case_to_babel)),
+            ],
+        ),
+        Statement::LabeledStatement { label, body, loc } => node(
+            "LabeledStatement",
+            loc,
+            vec![
+                ("label", identifier_to_babel(label)),
+                ("body", statement_to_babel(body)),
+            ],
+        ),
+        Statement::BreakStatement { label, loc } => node(
+            "BreakStatement",
+            loc,
+            vec![("label", optional(label.as_ref(), identifier_to_babel))],
+        ),
+        Statement::ContinueStatement { label, loc } => node(
+            "ContinueStatement",
+            loc,
+            vec![("label", optional(label.as_ref(), identifier_to_babel))],
+        ),
+        Statement::ThrowStatement { argument, loc } => node(
+            "ThrowStatement",
+            loc,
+            vec![("argument", expression_to_babel(argument))],
+        ),
+        Statement::Invalid { loc } => node("Invalid", loc, vec![]),
+    }
+}
+
+pub fn expression_to_babel(expression: &Expression) -> Json {
+    let json = match expression {
+        Expression::Identifier(identifier) => identifier_to_babel(identifier),
+        Expression::ThisExpression { loc, .. } => node("ThisExpression", loc, vec![]),
+        Expression::NullLiteral { loc, .. } => node("NullLiteral", loc, vec![]),
+        Expression::BooleanLiteral { value, loc, .. } => {
+            node("BooleanLiteral", loc, vec![("value", Json::Bool(*value))])
+        }
+        Expression::NumericLiteral(literal) => numeric_literal_to_babel(literal),
+        Expression::StringLiteral(literal) => string_literal_to_babel(literal),
+        Expression::BigIntLiteral {
+            value, raw, loc, ..
+        } => node(
+            "BigIntLiteral",
+            loc,
+            vec![
+                ("extra", raw_extra(string(value), raw)),
+                ("value", string(value)),
+            ],
+        ),
+        Expression::RegExpLiteral {
+            pattern,
+            flags,
+            loc,
+            ..
+        } => node(
+            "RegExpLiteral",
+            loc,
+            vec![
+                (
+                    "extra",
+                    Json::Object(vec![(
+                        "raw".to_string(),
+                        string(&format!("/{pattern}/{flags}")),
+                    )]),
+                ),
+                ("pattern", string(pattern)),
+                ("flags", string(flags)),
+            ],
+        ),
+        Expression::TemplateLiteral {
+            expressions,
+            quasis,
+            loc,
+            ..
+        } => node(
+            "TemplateLiteral",
+            loc,
+            vec![
+                ("expressions", array(expressions, expression_to_babel)),
+                (
+                    "quasis",
+                    Json::Array(
+                        quasis
+                            .iter()
+                            .enumerate()
+                            .map(|(i, quasi)| {
+                                node(
+                                    "TemplateElement",
+                                    &quasi.loc,
+                                    vec![
+                                        (
+                                            "value",
+                                            Json::Object(vec![
+                                                ("raw".to_string(), string(&quasi.value)),
+                                                ("cooked".to_string(), string(&quasi.cooked)),
+                                            ]),
+                                        ),
+                                        ("tail", Json::Bool(i == quasis.len() - 1)),
+                                    ],
+                                )
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        Expression::ArrayExpression { elements, loc, .. } => node(
+            "ArrayExpression",
+            loc,
+            vec![(
+                "elements",
+                Json::Array(
+                    elements
+                        .iter()
+                        .map(|element| optional(element.as_ref(), expression_to_babel))
+                        .collect(),
+                ),
+            )],
+        ),
+        Expression::ObjectExpression {
+            properties, loc, ..
+        } => node(
+            "ObjectExpression",
+            loc,
+            vec![("properties", array(properties, object_member_to_babel))],
+        ),
+        Expression::FunctionExpression(function) => {
+            function_to_babel("FunctionExpression", function)
+        }
+        Expression::ArrowFunctionExpression {
+            params, body, loc, ..
+        } => node(
+            "ArrowFunctionExpression",
+            loc,
+            vec![
+                ("id", Json::Null),
+                ("generator", Json::Bool(false)),
+                ("async", Json::Bool(false)),
+                (
+                    "params",
+                    Json::Array(
+                        params
+                            .iter()
+                            .map(|param| pattern_to_babel(param, false))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "body",
+                    match body {
+                        ArrowFunctionBody::BlockStatement(block) => block_to_babel(block),
+                        ArrowFunctionBody::Expression(expression) => {
+                            expression_to_babel(expression)
+                        }
+                    },
+                ),
+            ],
+        ),
+        Expression::SequenceExpression {
+            expressions, loc, ..
+        } => node(
+            "SequenceExpression",
+            loc,
+            vec![("expressions", array(expressions, expression_to_babel))],
+        ),
+        Expression::AssignmentExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node(
+            "AssignmentExpression",
+            loc,
+            vec![
+                ("operator", string(operator)),
+                ("left", pattern_to_babel(left, false)),
+                ("right", expression_to_babel(right)),
+            ],
+        ),
+        Expression::BinaryExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node("BinaryExpression", loc, binary(left, operator, right)),
+        Expression::LogicalExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node("LogicalExpression", loc, binary(left, operator, right)),
+        Expression::UnaryExpression {
+            operator,
+            prefix,
+            argument,
+            loc,
+            ..
+        } => unary("UnaryExpression", operator, *prefix, argument, loc),
+        Expression::UpdateExpression {
+            operator,
+            prefix,
+            argument,
+            loc,
+            ..
+        } => unary("UpdateExpression", operator, *prefix, argument, loc),
+        Expression::AwaitExpression { argument, loc, .. } => node(
+            "AwaitExpression",
+            loc,
+            vec![("argument", expression_to_babel(argument))],
+        ),
+        Expression::MemberExpression(member) => member_to_babel(member),
+        Expression::ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+            loc,
+            ..
+        } => node(
+            "ConditionalExpression",
+            loc,
+            vec![
+                ("test", expression_to_babel(test)),
+                ("consequent", expression_to_babel(consequent)),
+                ("alternate", expression_to_babel(alternate)),
+            ],
+        ),
+        Expression::CallExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => node(
+            "CallExpression",
+            loc,
+            vec![
+                ("callee", expression_to_babel(callee)),
+                ("arguments", array(arguments, expression_to_babel)),
+            ],
+        ),
+        Expression::NewExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => node(
+            "NewExpression",
+            loc,
+            vec![
+                ("callee", expression_to_babel(callee)),
+                ("arguments", array(arguments, expression_to_babel)),
+            ],
+        ),
+        Expression::ParenthesizedExpression { expression, loc } => node(
+            "ParenthesizedExpression",
+            loc,
+            vec![("expression", expression_to_babel(expression))],
+        ),
+    };
+    with_extra(json, expression.extra())
+}
+
+/// Babel orders the fields of an object pattern property differently when it
+/// parses a binding (params, declarations, catch) than when it converts an
+/// object literal into an assignment target, so `binding` picks the layout.
+pub fn pattern_to_babel(pattern: &Pattern, binding: bool) -> Json {
+    match pattern {
+        Pattern::Identifier(identifier) => {
+            with_extra(identifier_to_babel(identifier), &identifier.extra)
+        }
+        Pattern::MemberExpression(member) => with_extra(member_to_babel(member), &member.extra),
+        Pattern::ObjectPattern {
+            properties, loc, ..
+        } => node(
+            "ObjectPattern",
+            loc,
+            vec![(
+                "properties",
+                Json::Array(
+                    properties
+                        .iter()
+                        .map(|property| object_pattern_property_to_babel(property, binding))
+                        .collect(),
+                ),
+            )],
+        ),
+        Pattern::ArrayPattern { elements, loc, .. } => node(
+            "ArrayPattern",
+            loc,
+            vec![(
+                "elements",
+                Json::Array(
+                    elements
+                        .iter()
+                        .map(|element| {
+                            optional(element.as_ref(), |element| {
+                                pattern_to_babel(element, binding)
+                            })
+                        })
+                        .collect(),
+                ),
+            )],
+        ),
+        Pattern::AssignmentPattern { left, right, loc } => node(
+            "AssignmentPattern",
+            loc,
+            vec![
+                ("left", pattern_to_babel(left, binding)),
+                ("right", expression_to_babel(right)),
+            ],
+        ),
+    }
+}
+
+fn binding_to_babel(pattern: &Pattern) -> Json {
+    pattern_to_babel(pattern, true)
+}
+
+fn object_pattern_property_to_babel(property: &ObjectPatternProperty, binding: bool) -> Json {
+    let key = property_key_to_babel(&property.key);
+    let value = pattern_to_babel(&property.value, binding);
+    let mut fields = if binding {
+        vec![
+            ("key", key),
+            ("computed", Json::Bool(false)),
+            ("method", Json::Bool(false)),
+        ]
+    } else {
+        vec![
+            ("method", Json::Bool(false)),
+            ("key", key),
+            ("computed", Json::Bool(false)),
+        ]
+    };
+    fields.push(("shorthand", Json::Bool(property.shorthand)));
+    fields.push(("value", value));
+    if property.shorthand {
+        fields.push(("extra", shorthand_extra()));
+    }
+    node("ObjectProperty", &property.loc, fields)
+}
+
+fn block_to_babel(block: &BlockStatement) -> Json {
+    node(
+        "BlockStatement",
+        &block.loc,
+        vec![
+            ("body", array(&block.body, statement_to_babel)),
+            ("directives", array(&block.directives, directive_to_babel)),
+        ],
+    )
+}
+
+/// The value of a `DirectiveLiteral` is the text between the quotes, as
+/// written.
+fn directive_to_babel(directive: &Directive) -> Json {
+    let literal = &directive.value;
+    let value = directive.raw_value();
+    node(
+        "Directive",
+        &directive.loc,
+        vec![(
+            "value",
+            node(
+                "DirectiveLiteral",
+                &literal.loc,
+                vec![
+                    ("extra", raw_extra(string(value), &literal.raw)),
+                    ("value", string(value)),
+                ],
+            ),
+        )],
+    )
+}
+
+fn catch_clause_to_babel(clause: &CatchClause) -> Json {
+    node(
+        "CatchClause",
+        &clause.loc,
+        vec![
+            ("param", optional(clause.param.as_ref(), binding_to_babel)),
+            ("body", block_to_babel(&clause.body)),
+        ],
+    )
+}
+
+fn switch_case_to_babel(case: &SwitchCase) -> Json {
+    node(
+        "SwitchCase",
+        &case.loc,
+        vec![
+            ("consequent", array(&case.consequent, statement_to_babel)),
+            ("test", optional(case.test.as_deref(), expression_to_babel)),
+        ],
+    )
+}
+
+fn variable_declaration_to_babel(declaration: &VariableDeclaration) -> Json {
+    let kind = match declaration.kind {
+        VariableKind::Var => "var",
+        VariableKind::Let => "let",
+        VariableKind::Const => "const",
+    };
+    node(
+        "VariableDeclaration",
+        &declaration.loc,
+        vec![
+            (
+                "declarations",
+                array(&declaration.declarations, variable_declarator_to_babel),
+            ),
+            ("kind", string(kind)),
+        ],
+    )
+}
+
+fn variable_declarator_to_babel(declarator: &VariableDeclarator) -> Json {
+    node(
+        "VariableDeclarator",
+        &declarator.loc,
+        vec![
+            ("id", binding_to_babel(&declarator.id)),
+            (
+                "init",
+                optional(declarator.init.as_deref(), expression_to_babel),
+            ),
+        ],
+    )
+}
+
+fn function_to_babel(kind: &str, function: &Function) -> Json {
+    node(
+        kind,
+        &function.loc,
+        vec![
+            ("id", optional(function.id.as_ref(), identifier_to_babel)),
+            ("generator", Json::Bool(false)),
+            ("async", Json::Bool(false)),
+            ("params", array(&function.params, binding_to_babel)),
+            ("body", block_to_babel(&function.body)),
+        ],
+    )
+}
+
+fn object_member_to_babel(member: &ObjectMember) -> Json {
+    match member {
+        ObjectMember::ObjectProperty {
+            key,
+            value,
+            shorthand,
+            loc,
+        } => {
+            let mut fields = vec![
+                ("method", Json::Bool(false)),
+                ("key", property_key_to_babel(key)),
+                ("computed", Json::Bool(false)),
+                ("shorthand", Json::Bool(*shorthand)),
+                ("value", expression_to_babel(value)),
+            ];
+            if *shorthand {
+                fields.push(("extra", shorthand_extra()));
+            }
+            node("ObjectProperty", loc, fields)
+        }
+        ObjectMember::ObjectMethod { key, function, loc } => node(
+            "ObjectMethod",
+            loc,
+            vec![
+                ("method", Json::Bool(true)),
+                ("key", property_key_to_babel(key)),
+                ("computed", Json::Bool(false)),
+                ("kind", string("method")),
+                ("id", Json::Null),
+                ("generator", Json::Bool(false)),
+                ("async", Json::Bool(false)),
+                ("params", array(&function.params, binding_to_babel)),
+                ("body", block_to_babel(&function.body)),
+            ],
+        ),
+    }
+}
+
+fn property_key_to_babel(key: &PropertyKey) -> Json {
+    match key {
+        PropertyKey::Identifier(identifier) => identifier_to_babel(identifier),
+        PropertyKey::StringLiteral(literal) => string_literal_to_babel(literal),
+        PropertyKey::NumericLiteral(literal) => numeric_literal_to_babel(literal),
+    }
+}
+
+fn member_to_babel(member: &MemberExpression) -> Json {
+    node(
+        "MemberExpression",
+        &member.loc,
+        vec![
+            ("object", expression_to_babel(&member.object)),
+            ("computed", Json::Bool(member.computed)),
+            ("property", expression_to_babel(&member.property)),
+        ],
+    )
+}
+
+fn identifier_to_babel(identifier: &Identifier) -> Json {
+    node_at(
+        "Identifier",
+        &identifier.loc,
+        location(&identifier.loc, Some(&identifier.name)),
+        vec![("name", string(&identifier.name))],
+    )
+}
+
+fn numeric_literal_to_babel(literal: &NumericLiteral) -> Json {
+    node(
+        "NumericLiteral",
+        &literal.loc,
+        vec![
+            (
+                "extra",
+                raw_extra(Json::Number(literal.value), &literal.raw),
+            ),
+            ("value", Json::Number(literal.value)),
+        ],
+    )
+}
+
+fn string_literal_to_babel(literal: &StringLiteral) -> Json {
+    node(
+        "StringLiteral",
+        &literal.loc,
+        vec![
+            ("extra", raw_extra(string(&literal.value), &literal.raw)),
+            ("value", string(&literal.value)),
+        ],
+    )
+}
+
+fn raw_extra(value: Json, raw: &str) -> Json {
+    Json::Object(vec![
+        ("rawValue".to_string(), value),
+        ("raw".to_string(), string(raw)),
+    ])
+}
+
+fn shorthand_extra() -> Json {
+    Json::Object(vec![("shorthand".to_string(), Json::Bool(true))])
+}
+
+/// Adds `extra.parenthesized` to `node` when it was written in parens,
+/// next to any raw value already in its `extra`.
+fn with_extra(mut node: Json, extra: &Extra) -> Json {
+    let (Extra::Parenthesized { paren_start }, Json::Object(members)) = (extra, &mut node) else {
+        return node;
+    };
+    let parens = [
+        ("parenthesized".to_string(), Json::Bool(true)),
+        ("parenStart".to_string(), Json::Number(*paren_start as f64)),
+    ];
+    match members.iter_mut().find(|(key, _)| key == "extra") {
+        Some((_, Json::Object(extra))) => extra.extend(parens),
+        _ => members.push(("extra".to_string(), Json::Object(parens.to_vec()))),
+    }
+    node
+}
+
+fn binary(left: &Expression, operator: &str, right: &Expression) -> Vec<(&'static str, Json)> {
+    vec![
+        ("left", expression_to_babel(left)),
+        ("operator", string(operator)),
+        ("right", expression_to_babel(right)),
+    ]
+}
+
+fn unary(kind: &str, operator: &str, prefix: bool, argument: &Expression, loc: &Loc) -> Json {
+    node(
+        kind,
+        loc,
+        vec![
+            ("operator", string(operator)),
+            ("prefix", Json::Bool(prefix)),
+            ("argument", expression_to_babel(argument)),
+        ],
+    )
+}
+
+fn node(kind: &str, loc: &Loc, fields: Vec<(&str, Json)>) -> Json {
+    node_at(kind, loc, location(loc, None), fields)
+}
+
+fn node_at(kind: &str, loc: &Loc, location: Json, fields: Vec<(&str, Json)>) -> Json {
+    let mut members = vec![
+        ("type".to_string(), string(kind)),
+        ("start".to_string(), offset(&loc.start)),
+        ("end".to_string(), offset(&loc.end)),
+        ("loc".to_string(), location),
+    ];
+    members.extend(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value)),
+    );
+    Json::Object(members)
+}
+
+/// Adds the `attached` comments to the nodes of `json` they belong to, after
+/// their other fields. `parent` is the span of the closest node around.
+fn attach_comments(json: &mut Json, attached: &AttachedComments, parent: Option<(usize, usize)>) {
+    match json {
+        Json::Array(items) => {
+            for item in items {
+                attach_comments(item, attached, parent);
+            }
+        }
+        Json::Object(members) => {
+            let span = span(members);
+            let mut innermost = true;
+            for (_, value) in members.iter_mut() {
+                innermost &= span.is_none_or(|span| !child_spans(value).contains(&span));
+                attach_comments(value, attached, span.or(parent));
+            }
+            let Some(comments) = span.and_then(|span| attached.get(&span)) else {
+                return;
+            };
+            let outermost = span != parent;
+            for (key, list, attach) in [
+                ("leadingComments", &comments.leading_comments, outermost),
+                ("innerComments", &comments.inner_comments, innermost),
+                ("trailingComments", &comments.trailing_comments, outermost),
+            ] {
+                if attach && !list.is_empty() {
+                    let list = list.iter().map(comment_to_babel).collect();
+                    members.push((key.to_string(), Json::Array(list)));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The `(start, end)` of a node; `None` for other objects, like `loc`.
+fn span(members: &[(String, Json)]) -> Option<(usize, usize)> {
+    let get = |key: &str| {
+        members
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    };
+    match (get("type"), get("start"), get("end")) {
+        (Some(_), Some(Json::Number(start)), Some(Json::Number(end))) => {
+            Some((*start as usize, *end as usize))
+        }
+        _ => None,
+    }
+}
+
+/// The spans of the nodes directly in `json`, itself or its items.
+fn child_spans(json: &Json) -> Vec<(usize, usize)> {
+    match json {
+        Json::Object(members) => span(members).into_iter().collect(),
+        Json::Array(items) => items.iter().flat_map(child_spans).collect(),
+        _ => vec![],
+    }
+}
+
+fn comment_to_babel(comment: &Comment) -> Json {
+    let kind = match comment.kind {
+        CommentKind::Line => "CommentLine",
+        CommentKind::Block => "CommentBlock",
+    };
+    Json::Object(vec![
+        ("type".to_string(), string(kind)),
+        ("value".to_string(), string(&comment.value)),
+        ("start".to_string(), offset(&comment.loc.start)),
+        ("end".to_string(), offset(&comment.loc.end)),
+        ("loc".to_string(), location(&comment.loc, None)),
+    ])
+}
+
+/// Babel also records the name on the location of every identifier.
+fn location(loc: &Loc, identifier_name: Option<&str>) -> Json {
+    let mut members = vec![
+        ("start".to_string(), position(&loc.start)),
+        ("end".to_string(), position(&loc.end)),
+    ];
+    if let Some(name) = identifier_name {
+        members.push(("identifierName".to_string(), string(name)));
+    }
+    Json::Object(members)
+}
+
+fn position(position: &Position) -> Json {
+    Json::Object(vec![
+        ("line".to_string(), Json::Number(position.line as f64)),
+        (
+            "column".to_string(),
+            Json::Number(position.column.saturating_sub(1) as f64),
+        ),
+        ("index".to_string(), offset(position)),
+    ])
+}
+
+fn offset(position: &Position) -> Json {
+    Json::Number(position.index as f64)
+}
+
+#[cfg(test)]
+mod test_babel {
+    use super::*;
+    use crate::fixtures;
+    use std::fs;
+
+    /// Fields `@babel/parser` writes that the AST does not model.
+    const UNMODELLED: [&str; 2] = ["identifierName", "trailingComma"];
+
+    /// Sorts object members and drops [`UNMODELLED`] fields, and the
+    /// `extra` objects left empty by that.
+    fn normalize(json: Json) -> Json {
+        match json {
+            Json::Object(members) => {
+                let mut members: Vec<(String, Json)> = members
+                    .into_iter()
+                    .filter(|(key, _)| !UNMODELLED.contains(&key.as_str()))
+                    .map(|(key, value)| (key, normalize(value)))
+                    .filter(|(key, value)| {
+                        !(key == "extra"
+                            && matches!(value, Json::Object(members) if members.is_empty()))
+                    })
+                    .collect();
+                members.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Json::Object(members)
+            }
+            Json::Array(items) => Json::Array(items.into_iter().map(normalize).collect()),
+            json => json,
+        }
+    }
+
+    #[test]
+    fn test_fixtures() {
+        fixtures::check_snapshots("babel", to_json);
+    }
+
+    /// Compares against the output of `@babel/parser` itself: the
+    /// `*.babel.json` files `fixtures/babel/generate.mjs` writes, which every
+    /// fixture must have. Writing them takes Node and `@babel/parser`, so the
+    /// test only runs with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "needs the fixtures/babel/*.babel.json that generate.mjs writes"]
+    fn test_babel_reference() {
+        for (path, source) in fixtures::sources("babel") {
+            let reference_path = path.with_extension("babel.json");
+            let reference = fs::read_to_string(&reference_path).unwrap_or_else(|err| {
+                panic!(
+                    "{}: {err}; run fixtures/babel/generate.mjs",
+                    reference_path.display()
+                )
+            });
+            let expected = normalize(Json::parse(&reference).unwrap());
+            let actual = normalize(file_to_babel(&fixtures::parse(&path, &source)));
+            assert_eq!(
+                actual.to_string_pretty(),
+                expected.to_string_pretty(),
+                "{}",
+                path.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,1578 @@
+//! Prints the AST back as JavaScript. Parentheses are added wherever the
+//! operator levels of [`get_level`] require them, and kept wherever the
+//! source had them (`Extra::Parenthesized`). With `minify` set the output
+//! drops every optional space, parenthesis, semicolon and brace instead.
+//! [`print_with_source_map`] also maps the output back to the source.
+
+use crate::express::get_level;
+use crate::lex::{Loc, Position};
+use crate::node::{
+    ArrowFunctionBody, BlockStatement, CatchClause, Declaration, Directive, Expression, ForInLeft,
+    ForInit, Function, Identifier, MemberExpression, ModuleItem, NumericLiteral, ObjectMember,
+    Pattern, Program, PropertyKey, Statement, StringLiteral, SwitchCase, VariableDeclaration,
+    VariableKind,
+};
+use crate::sourcemap::{Mapping, SourceMap};
+use crate::token::Token;
+
+/// Level of primary expressions, which never need parentheses.
+const PRIMARY: u8 = 18;
+/// Level of member, call and `new` expressions.
+const MEMBER: u8 = 17;
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// The text of one level of indentation.
+    pub indent: String,
+    /// Print everything on one line with as few characters as possible.
+    pub minify: bool,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            indent: "  ".to_string(),
+            minify: false,
+        }
+    }
+}
+
+impl CodegenOptions {
+    pub fn minified() -> Self {
+        CodegenOptions {
+            minify: true,
+            ..CodegenOptions::default()
+        }
+    }
+}
+
+pub fn print(program: &Program) -> String {
+    print_with(program, &CodegenOptions::default())
+}
+
+pub fn minify(program: &Program) -> String {
+    print_with(program, &CodegenOptions::minified())
+}
+
+pub fn print_with(program: &Program, options: &CodegenOptions) -> String {
+    let mut printer = Printer::new(options);
+    printer.program(program);
+    printer.out
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMapOptions {
+    /// How the map names the original file in `sources`.
+    pub source: String,
+    /// The generated file, written as `file`.
+    pub file: Option<String>,
+    /// Embed the original text as `sourcesContent`.
+    pub sources_content: bool,
+}
+
+/// Prints `program`, which was parsed from `source`, together with a map from
+/// every printed node back to its `Loc`. Identifiers whose name differs from
+/// the source text get that text as their `names` entry.
+pub fn print_with_source_map(
+    program: &Program,
+    options: &CodegenOptions,
+    source: &str,
+    map_options: &SourceMapOptions,
+) -> (String, SourceMap) {
+    let mut map = SourceMap {
+        file: map_options.file.clone(),
+        ..SourceMap::default()
+    };
+    let content = map_options.sources_content.then_some(source);
+    map.add_source(&map_options.source, content);
+    let mut printer = Printer::new(options);
+    printer.mapper = Some(Mapper::new(map, source));
+    printer.program(program);
+    let map = printer.mapper.take().unwrap().map;
+    (printer.out, map)
+}
+
+/// Collects mappings while printing. A node marks its start, and the mapping
+/// is made at the next token written, after any space or `;` before it.
+struct Mapper {
+    map: SourceMap,
+    source: Vec<char>,
+    /// UTF-16 column of each char of the source, plus one for the end.
+    columns: Vec<u32>,
+    /// Output bytes already counted into `line` and `column`.
+    scanned: usize,
+    line: u32,
+    column: u32,
+    /// The start of the last marked node and, for identifiers, its name.
+    pending: Option<(Position, Option<String>)>,
+}
+
+impl Mapper {
+    fn new(map: SourceMap, source: &str) -> Mapper {
+        let source: Vec<char> = source.chars().collect();
+        let mut columns = Vec::with_capacity(source.len() + 1);
+        let mut column = 0;
+        for (i, c) in source.iter().enumerate() {
+            columns.push(column);
+            column += c.len_utf16() as u32;
+            let crlf = *c == '\r' && source.get(i + 1) == Some(&'\n');
+            if matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}') && !crlf {
+                column = 0;
+            }
+        }
+        columns.push(column);
+        Mapper {
+            map,
+            source,
+            columns,
+            scanned: 0,
+            line: 0,
+            column: 0,
+            pending: None,
+        }
+    }
+
+    fn mark(&mut self, loc: &Loc, name: Option<&str>) {
+        // the original text, when the identifier was renamed since parsing
+        let name = name.and_then(|name| {
+            let original: String = self
+                .source
+                .get(loc.start.index..loc.end.index)?
+                .iter()
+                .collect();
+            (original != name).then_some(original)
+        });
+        self.pending = Some((loc.start.clone(), name));
+    }
+
+    fn flush(&mut self, out: &str) {
+        let Some((position, name)) = self.pending.take() else {
+            return;
+        };
+        for c in out[self.scanned..].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 0;
+            } else {
+                self.column += c.len_utf16() as u32;
+            }
+        }
+        self.scanned = out.len();
+        let original_column = match self.columns.get(position.index) {
+            Some(column) => *column,
+            None => position.column.saturating_sub(1) as u32,
+        };
+        let name = name.map(|name| self.map.add_name(&name));
+        self.map.add_mapping(Mapping {
+            generated_line: self.line,
+            generated_column: self.column,
+            source: 0,
+            original_line: position.line.saturating_sub(1) as u32,
+            original_column,
+            name,
+        });
+    }
+}
+
+struct Printer<'a> {
+    out: String,
+    options: &'a CodegenOptions,
+    depth: usize,
+    /// Output offset where an expression statement starts; a `{` or
+    /// `function` there would be read as a block or declaration.
+    statement_start: Option<usize>,
+    /// Output offset where a concise arrow body starts; a `{` there would be
+    /// read as a block body.
+    arrow_body_start: Option<usize>,
+    /// Output offset right after a regular expression, where an identifier
+    /// character would be read as one more flag.
+    regex_end: Option<usize>,
+    /// Output offset where the object of a member expression or the callee
+    /// of a call starts; a `new` there needs its `()` even without arguments.
+    callee_start: Option<usize>,
+    /// The next statement opens a program or function body, where a string
+    /// alone would be read as a directive.
+    prologue: bool,
+    /// A minified statement ended; its `;` is written before the next token
+    /// unless that token is a `}`.
+    semicolon: bool,
+    mapper: Option<Mapper>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(options: &'a CodegenOptions) -> Printer<'a> {
+        Printer {
+            out: String::new(),
+            options,
+            depth: 0,
+            statement_start: None,
+            arrow_body_start: None,
+            regex_end: None,
+            callee_start: None,
+            prologue: false,
+            semicolon: false,
+            mapper: None,
+        }
+    }
+
+    fn program(&mut self, program: &Program) {
+        for directive in &program.directives {
+            self.directive(directive);
+            if !self.options.minify {
+                self.out.push('\n');
+            }
+        }
+        for (i, item) in program.body.iter().enumerate() {
+            self.prologue = i == 0;
+            match item {
+                ModuleItem::Statement(statement) => self.statement(statement),
+            }
+            if !self.options.minify {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    /// Maps the next token written to the start of `loc`.
+    fn mark(&mut self, loc: &Loc, name: Option<&str>) {
+        if let Some(mapper) = &mut self.mapper {
+            mapper.mark(loc, name);
+        }
+    }
+
+    fn newline(&mut self) {
+        if self.options.minify {
+            return;
+        }
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str(&self.options.indent);
+        }
+    }
+
+    /// A space that only the pretty output needs.
+    fn space(&mut self) {
+        if !self.options.minify {
+            self.out.push(' ');
+        }
+    }
+
+    /// Writes a token, separated from the previous one by a space when the
+    /// two would otherwise run together.
+    fn word(&mut self, s: &str) {
+        if self.semicolon {
+            self.semicolon = false;
+            if !s.starts_with('}') {
+                self.out.push(';');
+            }
+        }
+        if let (Some(last), Some(first)) = (self.out.chars().last(), s.chars().next()) {
+            let after_regex = self.regex_end == Some(self.out.len()) && is_identifier_char(first);
+            if after_regex || fuses(last, first) {
+                self.out.push(' ');
+            }
+        }
+        if let Some(mapper) = &mut self.mapper {
+            mapper.flush(&self.out);
+        }
+        self.out.push_str(s);
+    }
+
+    /// Ends a statement. Minified output only writes the `;` once it knows
+    /// the next token, since none is needed before a `}`.
+    fn semicolon(&mut self) {
+        if self.options.minify {
+            self.semicolon = true;
+        } else {
+            self.word(";");
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        let prologue = std::mem::take(&mut self.prologue);
+        self.mark(statement.loc(), None);
+        match statement {
+            Statement::EmptyStatement { .. } => self.word(";"),
+            Statement::BlockStatement(block) => self.block(block),
+            // Unless `expression` keeps parentheses from the source already.
+            Statement::ExpressionStatement { expression, .. }
+                if prologue
+                    && matches!(**expression, Expression::StringLiteral(_))
+                    && (self.options.minify || !expression.is_parenthesized()) =>
+            {
+                self.word("(");
+                self.expression(expression, 0);
+                self.word(")");
+                self.semicolon();
+            }
+            Statement::ExpressionStatement { expression, .. } => {
+                self.statement_start = Some(self.out.len());
+                self.expression(expression, 0);
+                self.semicolon();
+            }
+            Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+                self.function(function)
+            }
+            Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+                self.variable_declaration(declaration);
+                self.semicolon();
+            }
+            Statement::IfStatement {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.word("if");
+                self.space();
+                self.word("(");
+                self.expression(test, 0);
+                self.word(")");
+                let consequent = self.unwrap(consequent);
+                match alternate {
+                    Some(alternate) => {
+                        // `else` would bind to an inner `if` that has none
+                        if has_dangling_if(consequent) {
+                            self.space();
+                            self.word("{");
+                            self.depth += 1;
+                            self.newline();
+                            self.statement(consequent);
+                            self.depth -= 1;
+                            self.newline();
+                            self.word("}");
+                            self.space();
+                        } else {
+                            self.body(consequent);
+                            self.separate_else(consequent);
+                        }
+                        self.word("else");
+                        self.body(alternate);
+                    }
+                    None => self.body(consequent),
+                }
+            }
+            Statement::ForStatement {
+                init,
+                test,
+                update,
+                body,
+                ..
+            } => {
+                self.word("for");
+                self.space();
+                self.word("(");
+                match init {
+                    Some(ForInit::VariableDeclaration(declaration)) => {
+                        self.variable_declaration(declaration)
+                    }
+                    Some(ForInit::Expression(expression)) => {
+                        // an `in` here would turn the loop into a for-in
+                        let level = if contains_in(expression) { PRIMARY } else { 0 };
+                        self.expression(expression, level);
+                    }
+                    None => {}
+                }
+                self.word(";");
+                if let Some(test) = test {
+                    self.space();
+                    self.expression(test, 0);
+                }
+                self.word(";");
+                if let Some(update) = update {
+                    self.space();
+                    self.expression(update, 0);
+                }
+                self.word(")");
+                self.body(body);
+            }
+            Statement::ForInStatement {
+                left, right, body, ..
+            } => {
+                self.word("for");
+                self.space();
+                self.word("(");
+                match left {
+                    ForInLeft::VariableDeclaration(declaration) => {
+                        self.variable_declaration(declaration)
+                    }
+                    ForInLeft::Pattern(pattern) => self.pattern(pattern),
+                }
+                self.operator("in");
+                self.expression(right, 0);
+                self.word(")");
+                self.body(body);
+            }
+            Statement::WhileStatement { test, body, .. } => {
+                self.word("while");
+                self.space();
+                self.word("(");
+                self.expression(test, 0);
+                self.word(")");
+                self.body(body);
+            }
+            Statement::DoWhileStatement { body, test, .. } => {
+                self.word("do");
+                self.body(body);
+                self.separate_else(self.unwrap(body));
+                self.word("while");
+                self.space();
+                self.word("(");
+                self.expression(test, 0);
+                self.word(")");
+                self.semicolon();
+            }
+            Statement::TryStatement {
+                block,
+                handler,
+                finalizer,
+                ..
+            } => {
+                self.word("try");
+                self.space();
+                self.block(block);
+                if let Some(handler) = handler {
+                    self.space();
+                    self.catch_clause(handler);
+                }
+                if let Some(finalizer) = finalizer {
+                    self.space();
+                    self.word("finally");
+                    self.space();
+                    self.block(finalizer);
+                }
+            }
+            Statement::ReturnStatement { argument, .. } => {
+                self.word("return");
+                if let Some(argument) = argument {
+                    self.space();
+                    self.expression(argument, 0);
+                }
+                self.semicolon();
+            }
+            Statement::SwitchStatement {
+                discriminant,
+                cases,
+                ..
+            } => {
+                self.word("switch");
+                self.space();
+                self.word("(");
+                self.expression(discriminant, 0);
+                self.word(")");
+                self.space();
+                self.word("{");
+                self.depth += 1;
+                for case in cases {
+                    self.newline();
+                    self.switch_case(case);
+                }
+                self.depth -= 1;
+                self.newline();
+                self.word("}");
+            }
+            Statement::LabeledStatement { label, body, .. } => {
+                self.identifier(label);
+                self.word(":");
+                self.space();
+                self.statement(body);
+            }
+            Statement::BreakStatement { label, .. } => self.jump("break", label.as_ref()),
+            Statement::ContinueStatement { label, .. } => self.jump("continue", label.as_ref()),
+            Statement::ThrowStatement { argument, .. } => {
+                self.word("throw");
+                self.space();
+                self.expression(argument, 0);
+                self.semicolon();
+            }
+            // There is no source to print it from.
+            Statement::WithStatement { object, body, .. } => {
+                self.word("with");
+                self.space();
+                self.word("(");
+                self.expression(object, 0);
+                self.word(")");
+                self.body(body);
+            }
+            Statement::Invalid { .. } => {}
+        }
+    }
+
+    /// Written as it was: `"use\x20strict"` is no `"use strict"`.
+    fn directive(&mut self, directive: &Directive) {
+        self.mark(&directive.loc, None);
+        self.word(&directive.value.raw);
+        self.semicolon();
+    }
+
+    /// The body of `if`, `for`, `while` and `do`: blocks stay on the line of
+    /// the keyword, other statements follow it after a space.
+    fn body(&mut self, statement: &Statement) {
+        self.space();
+        self.statement(self.unwrap(statement));
+    }
+
+    /// In minified output a block holding one statement is printed as that
+    /// statement alone, unless it declares something scoped to the block.
+    fn unwrap<'s>(&self, statement: &'s Statement) -> &'s Statement {
+        match statement {
+            Statement::BlockStatement(block) if self.options.minify && block.body.len() == 1 => {
+                match &block.body[0] {
+                    Statement::Declaration(Declaration::FunctionDeclaration(_)) => statement,
+                    Statement::Declaration(Declaration::VariableDeclaration(declaration))
+                        if !matches!(declaration.kind, VariableKind::Var) =>
+                    {
+                        statement
+                    }
+                    inner => inner,
+                }
+            }
+            _ => statement,
+        }
+    }
+
+    /// Whitespace before the `else` of an `if` or the `while` of a `do`.
+    fn separate_else(&mut self, body: &Statement) {
+        if matches!(body, Statement::BlockStatement(_)) {
+            self.space();
+        } else {
+            self.newline();
+        }
+    }
+
+    fn jump(&mut self, keyword: &str, label: Option<&Identifier>) {
+        self.word(keyword);
+        if let Some(label) = label {
+            self.space();
+            self.identifier(label);
+        }
+        self.semicolon();
+    }
+
+    fn block(&mut self, block: &BlockStatement) {
+        self.statement_list_in_braces(block, false);
+    }
+
+    fn function_body(&mut self, body: &BlockStatement) {
+        self.statement_list_in_braces(body, true);
+    }
+
+    fn statement_list_in_braces(&mut self, block: &BlockStatement, prologue: bool) {
+        let BlockStatement {
+            directives, body, ..
+        } = block;
+        if directives.is_empty() && body.is_empty() {
+            self.word("{");
+            self.word("}");
+            return;
+        }
+        self.word("{");
+        self.depth += 1;
+        for directive in directives {
+            self.newline();
+            self.directive(directive);
+        }
+        for (i, statement) in body.iter().enumerate() {
+            self.newline();
+            self.prologue = prologue && i == 0;
+            self.statement(statement);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.word("}");
+    }
+
+    fn catch_clause(&mut self, clause: &CatchClause) {
+        self.word("catch");
+        self.space();
+        if let Some(param) = &clause.param {
+            self.word("(");
+            self.pattern(param);
+            self.word(")");
+            self.space();
+        }
+        self.block(&clause.body);
+    }
+
+    fn switch_case(&mut self, case: &SwitchCase) {
+        match &case.test {
+            Some(test) => {
+                self.word("case");
+                self.space();
+                self.expression(test, 0);
+                self.word(":");
+            }
+            None => {
+                self.word("default");
+                self.word(":");
+            }
+        }
+        self.depth += 1;
+        for statement in &case.consequent {
+            self.newline();
+            self.statement(statement);
+        }
+        self.depth -= 1;
+    }
+
+    fn variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        self.word(match declaration.kind {
+            VariableKind::Var => "var",
+            VariableKind::Let => "let",
+            VariableKind::Const => "const",
+        });
+        self.space();
+        for (i, declarator) in declaration.declarations.iter().enumerate() {
+            if i > 0 {
+                self.comma();
+            }
+            self.pattern(&declarator.id);
+            if let Some(init) = &declarator.init {
+                self.operator("=");
+                self.expression(init, 2);
+            }
+        }
+    }
+
+    fn function(&mut self, function: &Function) {
+        self.word("function");
+        self.space();
+        if let Some(id) = &function.id {
+            self.identifier(id);
+        }
+        self.params(&function.params, function.trailing_comma);
+        self.space();
+        self.function_body(&function.body);
+    }
+
+    fn params(&mut self, params: &[Pattern], trailing_comma: bool) {
+        self.word("(");
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 {
+                self.comma();
+            }
+            self.pattern(param);
+        }
+        if trailing_comma {
+            self.word(",");
+        }
+        self.word(")");
+    }
+
+    fn comma(&mut self) {
+        self.word(",");
+        self.space();
+    }
+
+    /// A binary operator, with spaces around it in pretty output.
+    fn operator(&mut self, operator: &str) {
+        self.space();
+        self.word(operator);
+        self.space();
+    }
+
+    /// Prints `expression` so that it parses back as an operand of at least
+    /// `level`, wrapping it in parentheses when it binds looser than that.
+    fn expression(&mut self, expression: &Expression, level: u8) {
+        let parenthesized = !self.options.minify && expression.is_parenthesized();
+        if parenthesized || precedence(expression) < level || self.needs_parens(expression) {
+            self.word("(");
+            self.expression_without_parens(expression);
+            self.word(")");
+        } else {
+            self.expression_without_parens(expression);
+        }
+    }
+
+    /// Whether `expression` would be misread at the start of a statement or
+    /// an arrow body.
+    fn needs_parens(&self, expression: &Expression) -> bool {
+        let at = Some(self.out.len());
+        match expression {
+            Expression::ObjectExpression { .. } => {
+                at == self.statement_start || at == self.arrow_body_start
+            }
+            Expression::FunctionExpression(_) => at == self.statement_start,
+            Expression::AssignmentExpression { left, .. } => {
+                matches!(**left, Pattern::ObjectPattern { .. })
+                    && (at == self.statement_start || at == self.arrow_body_start)
+            }
+            _ => false,
+        }
+    }
+
+    fn expression_without_parens(&mut self, expression: &Expression) {
+        self.mark(expression.loc(), None);
+        match expression {
+            Expression::Identifier(identifier) => self.identifier(identifier),
+            Expression::ThisExpression { .. } => self.word("this"),
+            Expression::NullLiteral { .. } => self.word("null"),
+            Expression::BooleanLiteral { value, .. } => {
+                self.word(if *value { "true" } else { "false" })
+            }
+            Expression::NumericLiteral(literal) => self.word(&self.number(literal)),
+            Expression::BigIntLiteral { raw, .. } => self.word(raw),
+            Expression::StringLiteral(literal) => self.string(literal),
+            Expression::RegExpLiteral { pattern, flags, .. } => {
+                self.word("/");
+                self.out.push_str(pattern);
+                self.out.push('/');
+                self.out.push_str(flags);
+                self.regex_end = Some(self.out.len());
+            }
+            Expression::TemplateLiteral {
+                expressions,
+                quasis,
+                ..
+            } => {
+                self.word("`");
+                for (i, quasi) in quasis.iter().enumerate() {
+                    self.out.push_str(&quasi.value);
+                    if let Some(expression) = expressions.get(i) {
+                        self.out.push_str("${");
+                        self.expression(expression, 0);
+                        self.word("}");
+                    }
+                }
+                self.out.push('`');
+            }
+            Expression::ArrayExpression {
+                elements,
+                trailing_comma,
+                ..
+            } => {
+                self.word("[");
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.comma();
+                    }
+                    if let Some(element) = element {
+                        self.expression(element, 2);
+                    }
+                }
+                // a trailing hole needs its own comma
+                if *trailing_comma || matches!(elements.last(), Some(None)) {
+                    self.word(",");
+                }
+                self.word("]");
+            }
+            Expression::ObjectExpression {
+                properties,
+                trailing_comma,
+                ..
+            } => {
+                self.word("{");
+                for (i, property) in properties.iter().enumerate() {
+                    if i > 0 {
+                        self.word(",");
+                    }
+                    self.space();
+                    self.object_member(property);
+                }
+                if *trailing_comma {
+                    self.word(",");
+                }
+                if !properties.is_empty() {
+                    self.space();
+                }
+                self.word("}");
+            }
+            Expression::FunctionExpression(function) => self.function(function),
+            Expression::ArrowFunctionExpression {
+                params,
+                body,
+                trailing_comma,
+                ..
+            } => {
+                match params.as_slice() {
+                    [Pattern::Identifier(param)] if self.options.minify && !trailing_comma => {
+                        self.identifier(param)
+                    }
+                    _ => self.params(params, *trailing_comma),
+                }
+                self.operator("=>");
+                match body {
+                    ArrowFunctionBody::BlockStatement(block) => self.function_body(block),
+                    ArrowFunctionBody::Expression(expression) => {
+                        self.arrow_body_start = Some(self.out.len());
+                        self.expression(expression, 2);
+                    }
+                }
+            }
+            Expression::SequenceExpression { expressions, .. } => {
+                for (i, expression) in expressions.iter().enumerate() {
+                    if i > 0 {
+                        self.comma();
+                    }
+                    self.expression(expression, 2);
+                }
+            }
+            Expression::AssignmentExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.pattern(left);
+                self.operator(operator);
+                self.expression(right, 2);
+            }
+            Expression::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            }
+            | Expression::LogicalExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let level = operator_level(operator);
+                let (left_level, right_level) = if operator == "**" {
+                    (level + 1, level)
+                } else {
+                    (level, level + 1)
+                };
+                self.operand(left, left_level, operator);
+                self.operator(operator);
+                self.operand(right, right_level, operator);
+            }
+            Expression::UnaryExpression {
+                operator, argument, ..
+            } => {
+                self.word(operator);
+                if operator.chars().all(char::is_alphabetic) {
+                    self.space();
+                }
+                self.expression(argument, operator_level("!"));
+            }
+            Expression::UpdateExpression {
+                operator,
+                prefix,
+                argument,
+                ..
+            } => {
+                if *prefix {
+                    self.word(operator);
+                    self.expression(argument, MEMBER);
+                } else {
+                    self.expression(argument, MEMBER);
+                    self.word(operator);
+                }
+            }
+            Expression::AwaitExpression { argument, .. } => {
+                self.word("await");
+                self.space();
+                self.expression(argument, operator_level("!"));
+            }
+            Expression::MemberExpression(member) => self.member(member),
+            Expression::ConditionalExpression {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.expression(test, 3);
+                self.operator("?");
+                self.expression(consequent, 2);
+                self.operator(":");
+                self.expression(alternate, 2);
+            }
+            Expression::CallExpression {
+                callee,
+                arguments,
+                trailing_comma,
+                ..
+            } => {
+                self.callee_start = Some(self.out.len());
+                self.expression(callee, MEMBER);
+                self.arguments(arguments, *trailing_comma);
+            }
+            Expression::NewExpression {
+                callee,
+                arguments,
+                trailing_comma,
+                ..
+            } => {
+                let needs_arguments = !self.options.minify
+                    || !arguments.is_empty()
+                    || *trailing_comma
+                    || self.callee_start == Some(self.out.len());
+                self.word("new");
+                self.space();
+                // a call or member in the callee would take the arguments
+                self.expression(callee, PRIMARY);
+                if needs_arguments {
+                    self.arguments(arguments, *trailing_comma);
+                }
+            }
+            Expression::ParenthesizedExpression { expression, .. } => {
+                self.word("(");
+                self.expression(expression, 0);
+                self.word(")");
+            }
+        }
+    }
+
+    /// `??` cannot be mixed with `||` or `&&` without parentheses.
+    fn operand(&mut self, operand: &Expression, level: u8, operator: &str) {
+        let mixes_nullish = matches!(
+            operand,
+            Expression::LogicalExpression { operator: inner, .. }
+                if (operator == "??") != (inner == "??")
+        ) && matches!(operator, "??" | "||" | "&&");
+        self.expression(operand, if mixes_nullish { PRIMARY } else { level });
+    }
+
+    fn arguments(&mut self, arguments: &[Expression], trailing_comma: bool) {
+        self.word("(");
+        for (i, argument) in arguments.iter().enumerate() {
+            if i > 0 {
+                self.comma();
+            }
+            self.expression(argument, 2);
+        }
+        if trailing_comma {
+            self.word(",");
+        }
+        self.word(")");
+    }
+
+    fn member(&mut self, member: &MemberExpression) {
+        // `1.a` would read the dot as a decimal point
+        let level = match &*member.object {
+            Expression::NumericLiteral(literal)
+                if !member.computed && !self.number(literal).contains('.') =>
+            {
+                PRIMARY + 1
+            }
+            _ => MEMBER,
+        };
+        self.callee_start = Some(self.out.len());
+        self.expression(&member.object, level);
+        if member.computed {
+            self.word("[");
+            self.expression(&member.property, 0);
+            self.word("]");
+        } else {
+            self.word(".");
+            self.expression(&member.property, PRIMARY);
+        }
+    }
+
+    fn object_member(&mut self, member: &ObjectMember) {
+        match member {
+            ObjectMember::ObjectProperty {
+                key,
+                value,
+                shorthand,
+                ..
+            } => {
+                if *shorthand {
+                    // `{a}` or the cover grammar's `{a = 1}`
+                    self.expression(value, 2);
+                } else {
+                    self.property_key(key);
+                    self.word(":");
+                    self.space();
+                    self.expression(value, 2);
+                }
+            }
+            ObjectMember::ObjectMethod { key, function, .. } => {
+                self.property_key(key);
+                self.params(&function.params, function.trailing_comma);
+                self.space();
+                self.function_body(&function.body);
+            }
+        }
+    }
+
+    fn property_key(&mut self, key: &PropertyKey) {
+        self.mark(key.loc(), None);
+        match key {
+            PropertyKey::Identifier(identifier) => self.identifier(identifier),
+            PropertyKey::StringLiteral(literal) => self.string(literal),
+            PropertyKey::NumericLiteral(literal) => self.word(&self.number(literal)),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        self.mark(pattern.loc(), None);
+        match pattern {
+            Pattern::Identifier(identifier) => self.identifier(identifier),
+            Pattern::MemberExpression(member) => self.member(member),
+            Pattern::ObjectPattern {
+                properties,
+                trailing_comma,
+                ..
+            } => {
+                self.word("{");
+                for (i, property) in properties.iter().enumerate() {
+                    if i > 0 {
+                        self.word(",");
+                    }
+                    self.space();
+                    if !property.shorthand {
+                        self.property_key(&property.key);
+                        self.word(":");
+                        self.space();
+                    }
+                    self.pattern(&property.value);
+                }
+                if *trailing_comma {
+                    self.word(",");
+                }
+                if !properties.is_empty() {
+                    self.space();
+                }
+                self.word("}");
+            }
+            Pattern::ArrayPattern {
+                elements,
+                trailing_comma,
+                ..
+            } => {
+                self.word("[");
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.comma();
+                    }
+                    if let Some(element) = element {
+                        self.pattern(element);
+                    }
+                }
+                // a trailing hole needs its own comma
+                if *trailing_comma || matches!(elements.last(), Some(None)) {
+                    self.word(",");
+                }
+                self.word("]");
+            }
+            Pattern::AssignmentPattern { left, right, .. } => {
+                self.pattern(left);
+                self.operator("=");
+                self.expression(right, 2);
+            }
+        }
+    }
+
+    fn identifier(&mut self, identifier: &Identifier) {
+        self.mark(&identifier.loc, Some(&identifier.name));
+        self.word(&identifier.name);
+    }
+
+    /// Prints a string as written unless minifying. The lexer reads a lone
+    /// surrogate escape such as `\uD800` as U+FFFD, so a value holding one
+    /// is requoted from `raw` rather than from the value.
+    fn string(&mut self, literal: &StringLiteral) {
+        if literal.raw.is_empty() {
+            self.word(&quote(&literal.value));
+        } else if !self.options.minify {
+            self.word(&literal.raw);
+        } else if literal.value.contains('\u{FFFD}') {
+            self.word(&requote(&literal.raw, &literal.value));
+        } else {
+            self.word(&quote(&literal.value));
+        }
+    }
+
+    fn number(&self, literal: &NumericLiteral) -> String {
+        if self.options.minify {
+            shortest_number(literal.value, &literal.raw)
+        } else {
+            literal.raw.clone()
+        }
+    }
+}
+
+/// Quotes a string value, preferring double quotes unless single quotes need
+/// fewer escapes.
+pub fn quote(value: &str) -> String {
+    let quote = preferred_quote(value);
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push(quote);
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{b}' => out.push_str("\\v"),
+            '\u{c}' => out.push_str("\\f"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            // `\0` followed by a digit would read as a legacy octal escape
+            '\0' if !chars.peek().is_some_and(char::is_ascii_digit) => out.push_str("\\0"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\x{:02x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+fn preferred_quote(value: &str) -> char {
+    let double = value.matches('"').count();
+    let single = value.matches('\'').count();
+    if single < double { '\'' } else { '"' }
+}
+
+/// Quotes like [`quote`] but from the source text `raw` of a string whose
+/// value is `value`. Escapes are kept as written, but for quotes.
+fn requote(raw: &str, value: &str) -> String {
+    let quote = preferred_quote(value);
+    let mut out = String::with_capacity(raw.len());
+    out.push(quote);
+    let mut chars = raw[1..raw.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c @ ('\'' | '"')) if c != quote => out.push(c),
+                Some(c) => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                None => {}
+            },
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+/// The shortest spelling of a number, e.g. `1e3` for `1000` and `.5` for
+/// `0.5`. Falls back to `raw` when that is shorter.
+fn shortest_number(value: f64, raw: &str) -> String {
+    if !value.is_finite() {
+        return raw.to_string();
+    }
+    let decimal = value.to_string();
+    let decimal = match decimal.strip_prefix("0.") {
+        Some(fraction) => format!(".{fraction}"),
+        None => decimal,
+    };
+    [decimal, format!("{value:e}"), raw.to_string()]
+        .into_iter()
+        .min_by_key(String::len)
+        .unwrap()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '\\' || !c.is_ascii()
+}
+
+/// Whether `last` followed directly by `first` would lex differently than
+/// with a space between them: `a in`, `a+ +b`, `a/ /b/`.
+fn fuses(last: char, first: char) -> bool {
+    (is_identifier_char(last) && is_identifier_char(first))
+        || (last == first && matches!(last, '+' | '-' | '/'))
+}
+
+fn operator_level(operator: &str) -> u8 {
+    let token = match operator {
+        "in" => Token::In,
+        "instanceof" => Token::Instanceof,
+        "typeof" => Token::Typeof,
+        "void" => Token::Void,
+        "delete" => Token::Delete,
+        operator => Token::Control(operator.to_string()),
+    };
+    get_level(&token).unwrap_or(PRIMARY)
+}
+
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::SequenceExpression { .. } => 1,
+        Expression::AssignmentExpression { .. }
+        | Expression::ConditionalExpression { .. }
+        | Expression::ArrowFunctionExpression { .. } => 2,
+        Expression::BinaryExpression { operator, .. }
+        | Expression::LogicalExpression { operator, .. } => operator_level(operator),
+        Expression::UnaryExpression { .. } | Expression::AwaitExpression { .. } => {
+            operator_level("!")
+        }
+        Expression::UpdateExpression { .. } => operator_level("++"),
+        Expression::MemberExpression(_)
+        | Expression::CallExpression { .. }
+        | Expression::NewExpression { .. } => MEMBER,
+        _ => PRIMARY,
+    }
+}
+
+/// Whether an `if` without `else` would be the last thing printed for
+/// `statement`, so that a following `else` would attach to it.
+fn has_dangling_if(statement: &Statement) -> bool {
+    match statement {
+        Statement::IfStatement {
+            alternate: None, ..
+        } => true,
+        Statement::IfStatement {
+            alternate: Some(body),
+            ..
+        }
+        | Statement::ForStatement { body, .. }
+        | Statement::ForInStatement { body, .. }
+        | Statement::WhileStatement { body, .. }
+        | Statement::LabeledStatement { body, .. } => has_dangling_if(body),
+        _ => false,
+    }
+}
+
+/// Whether `expression` has an `in` operator outside of parentheses.
+fn contains_in(expression: &Expression) -> bool {
+    match expression {
+        expression if expression.is_parenthesized() => false,
+        Expression::BinaryExpression {
+            left,
+            operator,
+            right,
+            ..
+        } => operator == "in" || contains_in(left) || contains_in(right),
+        Expression::LogicalExpression { left, right, .. } => {
+            contains_in(left) || contains_in(right)
+        }
+        Expression::SequenceExpression { expressions, .. } => expressions.iter().any(contains_in),
+        Expression::AssignmentExpression { right, .. } => contains_in(right),
+        Expression::ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+            ..
+        } => contains_in(test) || contains_in(consequent) || contains_in(alternate),
+        Expression::UnaryExpression { argument, .. } => contains_in(argument),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test_codegen {
+    use super::*;
+    use crate::estree::program_to_estree;
+    use crate::json::Json;
+    use crate::parser::Parser;
+    use std::fs;
+    use std::path::Path;
+
+    /// The ESTree JSON of `source` without spans and raw texts. Parentheses
+    /// are ignored as well, since the printer adds them where the tree needs
+    /// them.
+    fn shape(source: &str) -> Result<Json, String> {
+        fn strip(json: Json) -> Json {
+            match json {
+                Json::Object(members) => Json::Object(
+                    members
+                        .into_iter()
+                        .filter(|(key, _)| {
+                            !matches!(
+                                key.as_str(),
+                                "start"
+                                    | "end"
+                                    | "loc"
+                                    | "range"
+                                    | "raw"
+                                    | "parenthesized"
+                                    | "parenStart"
+                            )
+                        })
+                        .map(|(key, value)| (key, strip(value)))
+                        .filter(|(key, value)| {
+                            !(key == "extra"
+                                && matches!(value, Json::Object(members) if members.is_empty()))
+                        })
+                        .collect(),
+                ),
+                Json::Array(items) => Json::Array(items.into_iter().map(strip).collect()),
+                json => json,
+            }
+        }
+        let program = Parser::new(source.to_string())?.parse()?;
+        Ok(strip(program_to_estree(&program)))
+    }
+
+    /// Replaces blocks of a single statement with that statement, which is
+    /// how the minified output prints them.
+    fn unblock(json: Json) -> Json {
+        match json {
+            Json::Object(members) => {
+                let is_block = members.iter().any(|(key, value)| {
+                    key == "type" && *value == Json::String("BlockStatement".to_string())
+                });
+                let mut members: Vec<(String, Json)> = members
+                    .into_iter()
+                    .map(|(key, value)| (key, unblock(value)))
+                    .collect();
+                match members.iter_mut().find(|(key, _)| key == "body") {
+                    Some((_, Json::Array(body))) if is_block && body.len() == 1 => body.remove(0),
+                    _ => Json::Object(members),
+                }
+            }
+            Json::Array(items) => Json::Array(items.into_iter().map(unblock).collect()),
+            json => json,
+        }
+    }
+
+    fn round_trip_with(source: &str, options: &CodegenOptions) -> Result<String, String> {
+        let program = Parser::new(source.to_string())?.parse()?;
+        let printed = print_with(&program, options);
+        let mut reparsed = shape(&printed).map_err(|err| format!("{err} in {printed:?}"))?;
+        let mut expected = shape(source)?;
+        if options.minify {
+            reparsed = unblock(reparsed);
+            expected = unblock(expected);
+        }
+        assert_eq!(reparsed, expected, "{source:?} printed as {printed:?}");
+        Ok(printed)
+    }
+
+    fn round_trip(source: &str) -> Result<String, String> {
+        round_trip_with(source, &CodegenOptions::default())
+    }
+
+    fn round_trip_minified(source: &str) -> Result<String, String> {
+        round_trip_with(source, &CodegenOptions::minified())
+    }
+
+    #[test]
+    fn test_round_trip_fixtures() -> Result<(), String> {
+        for dir in ["fixtures/estree", "fixtures/babel"] {
+            let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|ext| ext == "js") {
+                    let source = fs::read_to_string(&path).unwrap();
+                    round_trip(&source)?;
+                    round_trip_minified(&source)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_print() -> Result<(), String> {
+        assert_eq!(
+            round_trip("if(a){b()}else if(c)d;else{}\nfunction f(x,y){return x*y}")?,
+            "if (a) {\n  b();\n} else if (c) d;\nelse {}\nfunction f(x, y) {\n  return x * y;\n}\n"
+        );
+        assert_eq!(
+            round_trip("switch(a){case 1:b;break;default:}")?,
+            "switch (a) {\n  case 1:\n    b;\n    break;\n  default:\n}\n"
+        );
+        assert_eq!(
+            round_trip("'use strict';('a');function f(){\"b\";('c');{'d'}}")?,
+            "'use strict';\n('a');\nfunction f() {\n  \"b\";\n  ('c');\n  {\n    'd';\n  }\n}\n"
+        );
+        let program = Parser::new("{a}".to_string())?.parse()?;
+        let options = CodegenOptions {
+            indent: "\t".to_string(),
+            ..CodegenOptions::default()
+        };
+        assert_eq!(print_with(&program, &options), "{\n\ta;\n}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parentheses() -> Result<(), String> {
+        for (source, printed) in [
+            ("(a + b) * c - (d - e)", "(a + b) * c - (d - e);\n"),
+            ("a - b - c", "a - b - c;\n"),
+            ("x = [a,,b,,]", "x = [a, , b, ,];\n"),
+            ("x = (a, b), c", "x = (a, b), c;\n"),
+            ("(a = b) ? c : d", "(a = b) ? c : d;\n"),
+            (
+                "-(-a); - --b; typeof typeof c",
+                "-(-a);\n- --b;\ntypeof typeof c;\n",
+            ),
+            ("(function () {})()", "(function () {})();\n"),
+            ("({}).a = 1", "({}).a = 1;\n"),
+            ("({a} = b)", "({ a } = b);\n"),
+            ("f = () => ({})", "f = () => ({});\n"),
+            ("(1).a; 1.5.a; (1)[0]", "(1).a;\n1.5.a;\n(1)[0];\n"),
+            (
+                "new (a.b)(); new a; new (f())",
+                "new (a.b)();\nnew a();\nnew (f())();\n",
+            ),
+        ] {
+            assert_eq!(round_trip(source)?, printed, "{source}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_minify() -> Result<(), String> {
+        for (source, printed) in [
+            (
+                "if (a) {\n  b();\n} else {\n  c = d;\n}",
+                "if(a)b();else c=d",
+            ),
+            (
+                "{ a; b }\nfunction f(x, y) { return x * y; }",
+                "{a;b}function f(x,y){return x*y}",
+            ),
+            ("x = a + +b - -c; y = a++ + b", "x=a+ +b- -c;y=a++ +b"),
+            (
+                "x = typeof a in b, y = 'a' in /b/g",
+                "x=typeof a in b,y=\"a\"in/b/g",
+            ),
+            ("x = /b/ in c", "x=/b/ in c"),
+            ("x = (a * b) + (c * d) + (e + f)", "x=a*b+c*d+(e+f)"),
+            (
+                "f = (a) => ({}); g = (a, b) => { return a }",
+                "f=a=>({});g=(a,b)=>{return a}",
+            ),
+            (
+                "x = [1000, 0.5, 0x10, 1.50, 1e21]",
+                "x=[1e3,.5,16,1.5,1e21]",
+            ),
+            ("x = (1000).a + (1.5).b", "x=(1e3).a+1.5.b"),
+            (
+                "for (;;) { let a; } while (a) { b; }",
+                "for(;;){let a}while(a)b",
+            ),
+            ("if (a) { if (b) c; } else d", "if(a){if(b)c}else d"),
+            ("do { a++ } while (b); c", "do a++;while(b);c"),
+            (
+                "new A(); new A().b; new (A()); new A()()",
+                "new A;new A().b;new(A());new A()()",
+            ),
+            ("x = 'it\\'s'; y = \"\\\"\"", "x=\"it's\";y='\"'"),
+            (
+                "x = '\\uD800\\x41\\'\"'; y = '\\ud83d\\ude00'",
+                "x=\"\\uD800\\x41'\\\"\";y=\"\u{1F600}\"",
+            ),
+        ] {
+            assert_eq!(round_trip_minified(source)?, printed, "{source}");
+        }
+        Ok(())
+    }
+
+    /// Checks that every mapping lands on the same token in both texts.
+    fn check_mappings(source: &str, options: &CodegenOptions) -> Result<SourceMap, String> {
+        fn char_at(text: &str, line: u32, column: u32) -> char {
+            let line: Vec<u16> = text
+                .lines()
+                .nth(line as usize)
+                .unwrap()
+                .encode_utf16()
+                .collect();
+            char::decode_utf16(line[column as usize..].iter().copied())
+                .next()
+                .unwrap()
+                .unwrap()
+        }
+        let program = Parser::new(source.to_string())?.parse()?;
+        let map_options = SourceMapOptions {
+            source: "input.js".to_string(),
+            ..SourceMapOptions::default()
+        };
+        let (printed, map) = print_with_source_map(&program, options, source, &map_options);
+        assert_eq!(printed, print_with(&program, options));
+        for mapping in &map.mappings {
+            let generated = char_at(&printed, mapping.generated_line, mapping.generated_column);
+            let original = char_at(source, mapping.original_line, mapping.original_column);
+            // quotes are normalized and minified numbers respelled
+            let same = generated == original
+                || (generated == '"' && original == '\'')
+                || (original.is_ascii_digit() && (generated.is_ascii_digit() || generated == '.'));
+            assert!(
+                same,
+                "{mapping:?} maps {generated:?} to {original:?} in {printed:?}"
+            );
+        }
+        Ok(map)
+    }
+
+    #[test]
+    fn test_source_map_fixtures() -> Result<(), String> {
+        for dir in ["fixtures/estree", "fixtures/babel"] {
+            let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|ext| ext == "js") {
+                    let source = fs::read_to_string(&path).unwrap();
+                    check_mappings(&source, &CodegenOptions::default())?;
+                    check_mappings(&source, &CodegenOptions::minified())?;
+                }
+            }
+        }
+        check_mappings(
+            "π = '😀';\r\n  x = [π, '€', y]",
+            &CodegenOptions::minified(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_map() -> Result<(), String> {
+        let source = "if (a)\n  b = c;";
+        let mut program = Parser::new(source.to_string())?.parse()?;
+        let map_options = SourceMapOptions {
+            source: "in.js".to_string(),
+            file: Some("out.js".to_string()),
+            sources_content: true,
+        };
+        let (printed, map) =
+            print_with_source_map(&program, &CodegenOptions::minified(), source, &map_options);
+        assert_eq!(printed, "if(a)b=c");
+        assert_eq!(
+            map.to_json().to_string_compact(),
+            r#"{"version":3,"file":"out.js","sources":["in.js"],"sourcesContent":["if (a)\n  b = c;"],"names":[],"mappings":"AAAA,GAAI,EACF,EAAI"}"#
+        );
+
+        // a renamed identifier keeps its original name in `names`
+        if let ModuleItem::Statement(Statement::IfStatement { test, .. }) = &mut program.body[0]
+            && let Expression::Identifier(identifier) = &mut **test
+        {
+            identifier.name = "renamed".to_string();
+        }
+        let (printed, map) =
+            print_with_source_map(&program, &CodegenOptions::default(), source, &map_options);
+        assert_eq!(printed, "if (renamed) b = c;\n");
+        assert_eq!(map.names, vec!["a".to_string()]);
+        assert_eq!(map.mappings[1].name, Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_number() {
+        assert_eq!(shortest_number(1000.0, "1000"), "1e3");
+        assert_eq!(shortest_number(0.5, "0.5"), ".5");
+        assert_eq!(shortest_number(0.0001, "0.0001"), "1e-4");
+        assert_eq!(shortest_number(123.0, "123"), "123");
+        assert_eq!(shortest_number(255.0, "0xff"), "255");
+        assert_eq!(shortest_number(f64::INFINITY, "1e999"), "1e999");
+    }
+
+    #[test]
+    fn test_raw_strings() -> Result<(), String> {
+        assert_eq!(
+            round_trip("x = 'a\\x41\\u{42}'; y = {'\\uD800': \"\\\"\"}")?,
+            "x = 'a\\x41\\u{42}';\ny = { '\\uD800': \"\\\"\" };\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("it's"), "\"it's\"");
+        assert_eq!(quote("say \"hi\""), "'say \"hi\"'");
+        assert_eq!(
+            quote("a\\b\n\u{0}1\u{1}\u{2028}"),
+            "\"a\\\\b\\n\\x001\\x01\\u2028\""
+        );
+    }
+}
@@ -0,0 +1,617 @@
+//! Attaches `Program.comments` to nodes with `@babel/parser`'s heuristics,
+//! recording them in `Program.attached_comments`.
+//! Comments separated only by whitespace form a group, which goes to the
+//! innermost node containing it: the child ending right before the group gets
+//! it as trailing comments, the child starting right after it as leading
+//! comments, and with neither the node itself keeps it as inner comments.
+
+use crate::lex::Loc;
+use crate::node::{
+    ArrowFunctionBody, AttachedComments, BlockStatement, CatchClause, Comment, Declaration,
+    Directive, Expression, ForInLeft, ForInit, Function, Identifier, MemberExpression, ModuleItem,
+    NumericLiteral, ObjectMember, ObjectPatternProperty, Pattern, Program, PropertyKey, Statement,
+    StringLiteral, SwitchCase, TemplateElement, VariableDeclaration, VariableDeclarator,
+};
+use crate::unicode::{is_line_terminator, is_whitespace};
+use std::ops::Range;
+
+pub fn attach(program: &Program, source: &str) -> AttachedComments {
+    let chars: Vec<char> = source.chars().collect();
+    let is_space = |index: usize| {
+        chars
+            .get(index)
+            .is_some_and(|&c| is_whitespace(c) || is_line_terminator(c))
+    };
+    let comments = &program.comments;
+    let mut attached = AttachedComments::new();
+    let mut i = 0;
+    while i < comments.len() {
+        let mut j = i + 1;
+        while j < comments.len()
+            && (comments[j - 1].loc.end.index..comments[j].loc.start.index).all(is_space)
+        {
+            j += 1;
+        }
+        let mut start = comments[i].loc.start.index;
+        while start > 0 && is_space(start - 1) {
+            start -= 1;
+        }
+        let mut end = comments[j - 1].loc.end.index;
+        while is_space(end) {
+            end += 1;
+        }
+        let group = Group {
+            start,
+            end,
+            after_comma: start > 0 && chars.get(start - 1) == Some(&','),
+            comments: &comments[i..j],
+        };
+        place(Node::Program(program), &group, &mut attached);
+        i = j;
+    }
+    attached
+}
+
+/// A run of comments and the whitespace around them.
+struct Group<'a> {
+    start: usize,
+    end: usize,
+    after_comma: bool,
+    comments: &'a [Comment],
+}
+
+enum Position {
+    Leading,
+    Trailing,
+    Inner,
+}
+
+fn place(node: Node, group: &Group, attached: &mut AttachedComments) {
+    let (loc, mut children, list) = node.split();
+    if let Some(index) = children.iter().position(|child| {
+        let loc = child.loc();
+        loc.start.index <= group.start && group.end <= loc.end.index
+    }) {
+        return place(children.swap_remove(index), group, attached);
+    }
+    let before = children
+        .iter()
+        .position(|child| child.loc().end.index == group.start);
+    let after = children
+        .iter()
+        .position(|child| child.loc().start.index == group.end);
+    if before.is_some() || after.is_some() {
+        for (index, child) in children.into_iter().enumerate() {
+            if Some(index) == before {
+                add(attached, child.split().0, Position::Trailing, group);
+            } else if Some(index) == after {
+                add(attached, child.split().0, Position::Leading, group);
+            }
+        }
+        return;
+    }
+    // `f(a, /* b */)`: Babel gives the comment to the last element.
+    if group.after_comma
+        && let Some(last) = list.last()
+        && children[last].loc().start.index < group.start
+    {
+        add(
+            attached,
+            children.swap_remove(last).split().0,
+            Position::Trailing,
+            group,
+        );
+        return;
+    }
+    add(attached, loc, Position::Inner, group);
+}
+
+fn add(attached: &mut AttachedComments, loc: &Loc, position: Position, group: &Group) {
+    let comments = attached
+        .entry((loc.start.index, loc.end.index))
+        .or_default();
+    let list = match position {
+        Position::Leading => &mut comments.leading_comments,
+        Position::Trailing => &mut comments.trailing_comments,
+        Position::Inner => &mut comments.inner_comments,
+    };
+    list.extend_from_slice(group.comments);
+}
+
+/// Every kind of node a comment can be attached to. Object methods are
+/// flattened into their function, as Babel's `ObjectMethod` is.
+enum Node<'a> {
+    Program(&'a Program),
+    Statement(&'a Statement),
+    Declaration(&'a VariableDeclaration),
+    Declarator(&'a VariableDeclarator),
+    Function(&'a Function),
+    Block(&'a BlockStatement),
+    Directive(&'a Directive),
+    Catch(&'a CatchClause),
+    Case(&'a SwitchCase),
+    Expression(&'a Expression),
+    Template(&'a TemplateElement),
+    Member(&'a ObjectMember),
+    MemberExpression(&'a MemberExpression),
+    Pattern(&'a Pattern),
+    PatternProperty(&'a ObjectPatternProperty),
+    Key(&'a PropertyKey),
+    Identifier(&'a Identifier),
+}
+
+type Parts<'a> = (&'a Loc, Vec<Node<'a>>, Range<usize>);
+
+impl<'a> Node<'a> {
+    fn loc(&self) -> &Loc {
+        match self {
+            Node::Program(Program { loc, .. })
+            | Node::Declaration(VariableDeclaration { loc, .. })
+            | Node::Declarator(VariableDeclarator { loc, .. })
+            | Node::Function(Function { loc, .. })
+            | Node::Block(BlockStatement { loc, .. })
+            | Node::Directive(Directive { loc, .. })
+            | Node::Catch(CatchClause { loc, .. })
+            | Node::Case(SwitchCase { loc, .. })
+            | Node::Template(TemplateElement { loc, .. })
+            | Node::Member(
+                ObjectMember::ObjectProperty { loc, .. } | ObjectMember::ObjectMethod { loc, .. },
+            )
+            | Node::MemberExpression(MemberExpression { loc, .. })
+            | Node::PatternProperty(ObjectPatternProperty { loc, .. })
+            | Node::Identifier(Identifier { loc, .. }) => loc,
+            Node::Statement(statement) => statement.loc(),
+            Node::Expression(expression) => expression.loc(),
+            Node::Pattern(pattern) => pattern.loc(),
+            Node::Key(key) => key.loc(),
+        }
+    }
+
+    /// The node's loc, its children in source order and the range of those
+    /// children that form a comma-separated list.
+    fn split(self) -> Parts<'a> {
+        match self {
+            Node::Program(Program {
+                directives,
+                body,
+                loc,
+                ..
+            }) => (
+                loc,
+                directives
+                    .iter()
+                    .map(Node::Directive)
+                    .chain(
+                        body.iter()
+                            .map(|ModuleItem::Statement(statement)| Node::Statement(statement)),
+                    )
+                    .collect(),
+                0..0,
+            ),
+            Node::Statement(statement) => split_statement(statement),
+            Node::Declaration(VariableDeclaration {
+                declarations, loc, ..
+            }) => (
+                loc,
+                declarations.iter().map(Node::Declarator).collect(),
+                0..0,
+            ),
+            Node::Declarator(VariableDeclarator { id, init, loc }) => {
+                let mut children = vec![Node::Pattern(id)];
+                children.extend(init.as_deref().map(Node::Expression));
+                (loc, children, 0..0)
+            }
+            Node::Function(Function {
+                id,
+                params,
+                body,
+                loc,
+                ..
+            }) => {
+                let mut children: Vec<Node> = id.iter().map(Node::Identifier).collect();
+                let start = children.len();
+                children.extend(params.iter().map(Node::Pattern));
+                let list = start..children.len();
+                children.push(Node::Block(body));
+                (loc, children, list)
+            }
+            Node::Block(BlockStatement {
+                directives,
+                body,
+                loc,
+            }) => (
+                loc,
+                directives
+                    .iter()
+                    .map(Node::Directive)
+                    .chain(body.iter().map(Node::Statement))
+                    .collect(),
+                0..0,
+            ),
+            Node::Catch(CatchClause { param, body, loc }) => {
+                let mut children: Vec<Node> = param.iter().map(Node::Pattern).collect();
+                children.push(Node::Block(body));
+                (loc, children, 0..0)
+            }
+            Node::Case(SwitchCase {
+                test,
+                consequent,
+                loc,
+            }) => {
+                let mut children: Vec<Node> =
+                    test.as_deref().map(Node::Expression).into_iter().collect();
+                children.extend(consequent.iter().map(Node::Statement));
+                (loc, children, 0..0)
+            }
+            Node::Expression(expression) => split_expression(expression),
+            Node::Member(ObjectMember::ObjectProperty {
+                key, value, loc, ..
+            }) => (loc, vec![Node::Key(key), Node::Expression(value)], 0..0),
+            Node::Member(ObjectMember::ObjectMethod { key, function, loc }) => {
+                let (_, function_children, list) = Node::Function(function).split();
+                let mut children = vec![Node::Key(key)];
+                children.extend(function_children);
+                (loc, children, list.start + 1..list.end + 1)
+            }
+            Node::MemberExpression(MemberExpression {
+                object,
+                property,
+                loc,
+                ..
+            }) => (
+                loc,
+                vec![Node::Expression(object), Node::Expression(property)],
+                0..0,
+            ),
+            Node::Pattern(pattern) => split_pattern(pattern),
+            Node::PatternProperty(ObjectPatternProperty {
+                key, value, loc, ..
+            }) => (loc, vec![Node::Key(key), Node::Pattern(value)], 0..0),
+            Node::Key(
+                PropertyKey::Identifier(Identifier { loc, .. })
+                | PropertyKey::StringLiteral(StringLiteral { loc, .. })
+                | PropertyKey::NumericLiteral(NumericLiteral { loc, .. }),
+            )
+            | Node::Template(TemplateElement { loc, .. })
+            | Node::Directive(Directive { loc, .. })
+            | Node::Identifier(Identifier { loc, .. }) => (loc, vec![], 0..0),
+        }
+    }
+}
+
+fn split_statement(statement: &Statement) -> Parts<'_> {
+    match statement {
+        Statement::BlockStatement(block) => Node::Block(block).split(),
+        Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+            Node::Function(function).split()
+        }
+        Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+            Node::Declaration(declaration).split()
+        }
+        Statement::EmptyStatement { loc } | Statement::Invalid { loc } => (loc, vec![], 0..0),
+        Statement::ExpressionStatement { expression, loc }
+        | Statement::ThrowStatement {
+            argument: expression,
+            loc,
+        } => (loc, vec![Node::Expression(expression)], 0..0),
+        Statement::ReturnStatement { argument, loc } => (
+            loc,
+            argument
+                .as_deref()
+                .map(Node::Expression)
+                .into_iter()
+                .collect(),
+            0..0,
+        ),
+        Statement::IfStatement {
+            test,
+            consequent,
+            alternate,
+            loc,
+        } => {
+            let mut children = vec![Node::Expression(test), Node::Statement(consequent)];
+            children.extend(alternate.as_deref().map(Node::Statement));
+            (loc, children, 0..0)
+        }
+        Statement::ForStatement {
+            init,
+            test,
+            update,
+            body,
+            loc,
+        } => {
+            let mut children: Vec<Node> = init
+                .iter()
+                .map(|init| match init {
+                    ForInit::VariableDeclaration(declaration) => Node::Declaration(declaration),
+                    ForInit::Expression(expression) => Node::Expression(expression),
+                })
+                .collect();
+            children.extend(test.as_deref().map(Node::Expression));
+            children.extend(update.as_deref().map(Node::Expression));
+            children.push(Node::Statement(body));
+            (loc, children, 0..0)
+        }
+        Statement::ForInStatement {
+            left,
+            right,
+            body,
+            loc,
+        } => {
+            let left = match left {
+                ForInLeft::VariableDeclaration(declaration) => Node::Declaration(declaration),
+                ForInLeft::Pattern(pattern) => Node::Pattern(pattern),
+            };
+            (
+                loc,
+                vec![left, Node::Expression(right), Node::Statement(body)],
+                0..0,
+            )
+        }
+        Statement::WhileStatement { test, body, loc } => (
+            loc,
+            vec![Node::Expression(test), Node::Statement(body)],
+            0..0,
+        ),
+        Statement::DoWhileStatement { body, test, loc } => (
+            loc,
+            vec![Node::Statement(body), Node::Expression(test)],
+            0..0,
+        ),
+        Statement::WithStatement { object, body, loc } => (
+            loc,
+            vec![Node::Expression(object), Node::Statement(body)],
+            0..0,
+        ),
+        Statement::TryStatement {
+            block,
+            handler,
+            finalizer,
+            loc,
+        } => {
+            let mut children = vec![Node::Block(block)];
+            children.extend(handler.as_ref().map(Node::Catch));
+            children.extend(finalizer.as_ref().map(Node::Block));
+            (loc, children, 0..0)
+        }
+        Statement::SwitchStatement {
+            discriminant,
+            cases,
+            loc,
+        } => {
+            let mut children = vec![Node::Expression(discriminant)];
+            children.extend(cases.iter().map(Node::Case));
+            (loc, children, 0..0)
+        }
+        Statement::LabeledStatement { label, body, loc } => (
+            loc,
+            vec![Node::Identifier(label), Node::Statement(body)],
+            0..0,
+        ),
+        Statement::BreakStatement { label, loc } | Statement::ContinueStatement { label, loc } => {
+            (loc, label.iter().map(Node::Identifier).collect(), 0..0)
+        }
+    }
+}
+
+fn split_expression(expression: &Expression) -> Parts<'_> {
+    match expression {
+        Expression::Identifier(identifier) => Node::Identifier(identifier).split(),
+        Expression::FunctionExpression(function) => Node::Function(function).split(),
+        Expression::MemberExpression(member) => Node::MemberExpression(member).split(),
+        Expression::ThisExpression { loc, .. }
+        | Expression::NullLiteral { loc, .. }
+        | Expression::BooleanLiteral { loc, .. }
+        | Expression::NumericLiteral(NumericLiteral { loc, .. })
+        | Expression::BigIntLiteral { loc, .. }
+        | Expression::StringLiteral(StringLiteral { loc, .. })
+        | Expression::RegExpLiteral { loc, .. } => (loc, vec![], 0..0),
+        Expression::TemplateLiteral {
+            expressions,
+            quasis,
+            loc,
+            ..
+        } => {
+            let mut children: Vec<Node> = quasis.iter().map(Node::Template).collect();
+            children.extend(expressions.iter().map(Node::Expression));
+            (loc, children, 0..0)
+        }
+        Expression::ArrayExpression { elements, loc, .. } => {
+            let children: Vec<Node> = elements.iter().flatten().map(Node::Expression).collect();
+            let list = 0..children.len();
+            (loc, children, list)
+        }
+        Expression::ObjectExpression {
+            properties, loc, ..
+        } => {
+            let children: Vec<Node> = properties.iter().map(Node::Member).collect();
+            let list = 0..children.len();
+            (loc, children, list)
+        }
+        Expression::ArrowFunctionExpression {
+            params, body, loc, ..
+        } => {
+            let mut children: Vec<Node> = params.iter().map(Node::Pattern).collect();
+            let list = 0..children.len();
+            children.push(match body {
+                ArrowFunctionBody::BlockStatement(block) => Node::Block(block),
+                ArrowFunctionBody::Expression(expression) => Node::Expression(expression),
+            });
+            (loc, children, list)
+        }
+        Expression::SequenceExpression {
+            expressions, loc, ..
+        } => (
+            loc,
+            expressions.iter().map(Node::Expression).collect(),
+            0..0,
+        ),
+        Expression::AssignmentExpression {
+            left, right, loc, ..
+        } => (
+            loc,
+            vec![Node::Pattern(left), Node::Expression(right)],
+            0..0,
+        ),
+        Expression::BinaryExpression {
+            left, right, loc, ..
+        }
+        | Expression::LogicalExpression {
+            left, right, loc, ..
+        } => (
+            loc,
+            vec![Node::Expression(left), Node::Expression(right)],
+            0..0,
+        ),
+        Expression::UnaryExpression { argument, loc, .. }
+        | Expression::UpdateExpression { argument, loc, .. }
+        | Expression::AwaitExpression { argument, loc, .. } => {
+            (loc, vec![Node::Expression(argument)], 0..0)
+        }
+        Expression::ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+            loc,
+            ..
+        } => (
+            loc,
+            vec![
+                Node::Expression(test),
+                Node::Expression(consequent),
+                Node::Expression(alternate),
+            ],
+            0..0,
+        ),
+        // Babel only moves comments after a trailing comma for calls, not `new`.
+        Expression::CallExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => {
+            let mut children = vec![Node::Expression(callee)];
+            children.extend(arguments.iter().map(Node::Expression));
+            let list = 1..children.len();
+            (loc, children, list)
+        }
+        Expression::NewExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => {
+            let mut children = vec![Node::Expression(callee)];
+            children.extend(arguments.iter().map(Node::Expression));
+            (loc, children, 0..0)
+        }
+        Expression::ParenthesizedExpression { expression, loc } => {
+            (loc, vec![Node::Expression(expression)], 0..0)
+        }
+    }
+}
+
+fn split_pattern(pattern: &Pattern) -> Parts<'_> {
+    match pattern {
+        Pattern::Identifier(identifier) => Node::Identifier(identifier).split(),
+        Pattern::MemberExpression(member) => Node::MemberExpression(member).split(),
+        Pattern::ObjectPattern {
+            properties, loc, ..
+        } => {
+            let children: Vec<Node> = properties.iter().map(Node::PatternProperty).collect();
+            let list = 0..children.len();
+            (loc, children, list)
+        }
+        Pattern::ArrayPattern { elements, loc, .. } => {
+            let children: Vec<Node> = elements.iter().flatten().map(Node::Pattern).collect();
+            let list = 0..children.len();
+            (loc, children, list)
+        }
+        Pattern::AssignmentPattern { left, right, loc } => (
+            loc,
+            vec![Node::Pattern(left), Node::Expression(right)],
+            0..0,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test_comments {
+    use crate::lex::Loc;
+    use crate::node::{
+        Comment, CommentKind, Expression, ModuleItem, NodeComments, Program, Statement,
+    };
+    use crate::parser::Parser;
+
+    fn values(comments: &[Comment]) -> Vec<&str> {
+        comments
+            .iter()
+            .map(|comment| comment.value.as_str())
+            .collect()
+    }
+
+    fn comments_at(program: &Program, loc: &Loc) -> NodeComments {
+        program
+            .attached_comments
+            .get(&(loc.start.index, loc.end.index))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn statement_comments(source: &str, index: usize) -> Result<NodeComments, String> {
+        let program = Parser::new(source.to_string())?.parse()?;
+        let ModuleItem::Statement(statement) = &program.body[index];
+        Ok(comments_at(&program, statement.loc()))
+    }
+
+    #[test]
+    fn test_collect() -> Result<(), String> {
+        let program =
+            Parser::new("// a\nx /* b */ = /c/ /* d */; (e /* f */) => e".to_string())?.parse()?;
+        assert_eq!(values(&program.comments), [" a", " b ", " d ", " f "]);
+        assert_eq!(program.comments[0].kind, CommentKind::Line);
+        assert_eq!(program.comments[1].kind, CommentKind::Block);
+        let loc = &program.comments[1].loc;
+        assert_eq!((loc.start.index, loc.end.index), (7, 14));
+        assert_eq!((loc.start.line, loc.start.column), (2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_leading_and_trailing() -> Result<(), String> {
+        let source = "a; // one\n/* two */\nb;";
+        let first = statement_comments(source, 0)?;
+        assert_eq!(values(&first.trailing_comments), [" one", " two "]);
+        let second = statement_comments(source, 1)?;
+        assert_eq!(values(&second.leading_comments), [" one", " two "]);
+        assert!(second.trailing_comments.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inner() -> Result<(), String> {
+        let inner = statement_comments("{ /* a */ }", 0)?;
+        assert_eq!(values(&inner.inner_comments), [" a "]);
+        let program = Parser::new("f(a, /* b */)".to_string())?.parse()?;
+        let ModuleItem::Statement(Statement::ExpressionStatement { expression, .. }) =
+            &program.body[0]
+        else {
+            panic!("expected an expression statement");
+        };
+        let Expression::CallExpression { arguments, .. } = expression.as_ref() else {
+            panic!("expected a call");
+        };
+        let comments = comments_at(&program, arguments[0].loc());
+        assert_eq!(values(&comments.trailing_comments), [" b "]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nodes_compare_without_comments() -> Result<(), String> {
+        let first = Parser::new("f(a /* 1 */)".to_string())?.parse()?;
+        let second = Parser::new("f(a /* 2 */)".to_string())?.parse()?;
+        assert_eq!(first.body, second.body);
+        assert_ne!(first.attached_comments, second.attached_comments);
+        Ok(())
+    }
+}
@@ -0,0 +1,1511 @@
+//! A lossless concrete syntax tree. Green nodes are immutable and only know
+//! their kind, width and children, so edits share every untouched subtree;
+//! red nodes ([`SyntaxNode`]) add offsets and parents on the way down.
+//! Tokens carry the whitespace, newlines and comments around them as
+//! trivia: trailing trivia runs up to and including the end of the line,
+//! everything after is leading trivia of the next token. Offsets are bytes.
+//!
+//! The tree is built by parsing the source, then nesting the tokens of a
+//! second, trivia-keeping lex under the spans of the AST nodes. Tokens no
+//! child node covers, like keywords and parentheses, belong to the parent.
+
+use crate::lex::{Lex, Loc};
+use crate::node::{
+    ArrowFunctionBody, BlockStatement, CatchClause, Declaration, Directive, Expression, ForInLeft,
+    ForInit, Function, ModuleItem, ObjectMember, Pattern, Program, PropertyKey, Statement,
+    VariableDeclaration,
+};
+use crate::parser::Parser;
+use crate::token::{Token, Trivia, TriviaKind};
+use std::ops::Range;
+use std::rc::Rc;
+
+macro_rules! syntax_kinds {
+    ($($kind:ident),* $(,)?) => {
+        /// One kind per `Node` variant.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum SyntaxKind {
+            $($kind),*
+        }
+
+        /// Typed views over [`SyntaxNode`], one per [`SyntaxKind`].
+        pub mod ast {
+            use super::{AstNode, SyntaxKind, SyntaxNode};
+
+            $(
+                #[derive(Debug, Clone)]
+                pub struct $kind(SyntaxNode);
+
+                impl AstNode for $kind {
+                    fn cast(node: SyntaxNode) -> Option<Self> {
+                        (node.kind() == SyntaxKind::$kind).then_some($kind(node))
+                    }
+
+                    fn syntax(&self) -> &SyntaxNode {
+                        &self.0
+                    }
+                }
+            )*
+        }
+    };
+}
+
+syntax_kinds!(
+    Program,
+    EmptyStatement,
+    BlockStatement,
+    ExpressionStatement,
+    FunctionDeclaration,
+    VariableDeclaration,
+    VariableDeclarator,
+    IfStatement,
+    ForStatement,
+    ForInStatement,
+    WhileStatement,
+    DoWhileStatement,
+    WithStatement,
+    TryStatement,
+    CatchClause,
+    ReturnStatement,
+    SwitchStatement,
+    SwitchCase,
+    LabeledStatement,
+    BreakStatement,
+    ContinueStatement,
+    ThrowStatement,
+    Invalid,
+    Directive,
+    Identifier,
+    ThisExpression,
+    NullLiteral,
+    BooleanLiteral,
+    NumericLiteral,
+    BigIntLiteral,
+    StringLiteral,
+    RegExpLiteral,
+    TemplateLiteral,
+    ArrayExpression,
+    ObjectExpression,
+    ObjectProperty,
+    ObjectMethod,
+    FunctionExpression,
+    ArrowFunctionExpression,
+    SequenceExpression,
+    AssignmentExpression,
+    BinaryExpression,
+    LogicalExpression,
+    UnaryExpression,
+    UpdateExpression,
+    AwaitExpression,
+    MemberExpression,
+    ConditionalExpression,
+    CallExpression,
+    NewExpression,
+    ParenthesizedExpression,
+    ObjectPattern,
+    ObjectPatternProperty,
+    ArrayPattern,
+    AssignmentPattern,
+);
+
+pub trait AstNode: Sized {
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+impl ast::Identifier {
+    pub fn name(&self) -> String {
+        self.syntax()
+            .tokens()
+            .first()
+            .map(|token| token.text().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Field accessors mirroring [`crate::node`]. Fields of a fixed kind are
+/// typed; expressions, statements and patterns come back as [`SyntaxNode`].
+impl ast::Program {
+    pub fn directives(&self) -> Vec<ast::Directive> {
+        self.syntax().children_of()
+    }
+
+    pub fn body(&self) -> Vec<SyntaxNode> {
+        statements(self.syntax())
+    }
+}
+
+impl ast::BlockStatement {
+    pub fn directives(&self) -> Vec<ast::Directive> {
+        self.syntax().children_of()
+    }
+
+    pub fn body(&self) -> Vec<SyntaxNode> {
+        statements(self.syntax())
+    }
+}
+
+impl ast::ExpressionStatement {
+    pub fn expression(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::FunctionDeclaration {
+    pub fn id(&self) -> Option<ast::Identifier> {
+        first(self.syntax().group(&["("], 0))
+    }
+
+    pub fn params(&self) -> Vec<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1)
+    }
+
+    pub fn body(&self) -> Option<ast::BlockStatement> {
+        first(self.syntax().group(&["(", ")"], 2))
+    }
+}
+
+impl ast::FunctionExpression {
+    pub fn id(&self) -> Option<ast::Identifier> {
+        first(self.syntax().group(&["("], 0))
+    }
+
+    pub fn params(&self) -> Vec<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1)
+    }
+
+    pub fn body(&self) -> Option<ast::BlockStatement> {
+        first(self.syntax().group(&["(", ")"], 2))
+    }
+}
+
+impl ast::ArrowFunctionExpression {
+    pub fn params(&self) -> Vec<SyntaxNode> {
+        self.syntax().group(&["=>"], 0)
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["=>"], 1).into_iter().next()
+    }
+}
+
+impl ast::VariableDeclaration {
+    pub fn declarations(&self) -> Vec<ast::VariableDeclarator> {
+        self.syntax().children_of()
+    }
+}
+
+impl ast::VariableDeclarator {
+    pub fn id(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn init(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["="], 1).into_iter().next()
+    }
+}
+
+impl ast::IfStatement {
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1).into_iter().next()
+    }
+
+    pub fn consequent(&self) -> Option<SyntaxNode> {
+        self.syntax()
+            .group(&["(", ")", "else"], 2)
+            .into_iter()
+            .next()
+    }
+
+    pub fn alternate(&self) -> Option<SyntaxNode> {
+        self.syntax()
+            .group(&["(", ")", "else"], 3)
+            .into_iter()
+            .next()
+    }
+}
+
+impl ast::ForStatement {
+    pub fn init(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ";"], 1).into_iter().next()
+    }
+
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ";", ";"], 2).into_iter().next()
+    }
+
+    pub fn update(&self) -> Option<SyntaxNode> {
+        self.syntax()
+            .group(&["(", ";", ";", ")"], 3)
+            .into_iter()
+            .next()
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax()
+            .group(&["(", ";", ";", ")"], 4)
+            .into_iter()
+            .next()
+    }
+}
+
+impl ast::ForInStatement {
+    pub fn left(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", "in"], 1).into_iter().next()
+    }
+
+    pub fn right(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", "in", ")"], 2).into_iter().next()
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", "in", ")"], 3).into_iter().next()
+    }
+}
+
+impl ast::WhileStatement {
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1).into_iter().next()
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 2).into_iter().next()
+    }
+}
+
+impl ast::DoWhileStatement {
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["while"], 0).into_iter().next()
+    }
+
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["while"], 1).into_iter().next()
+    }
+}
+
+impl ast::WithStatement {
+    pub fn object(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1).into_iter().next()
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 2).into_iter().next()
+    }
+}
+
+impl ast::TryStatement {
+    pub fn block(&self) -> Option<ast::BlockStatement> {
+        self.syntax().child()
+    }
+
+    pub fn handler(&self) -> Option<ast::CatchClause> {
+        self.syntax().child()
+    }
+
+    pub fn finalizer(&self) -> Option<ast::BlockStatement> {
+        first(self.syntax().group(&["finally"], 1))
+    }
+}
+
+impl ast::CatchClause {
+    pub fn param(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1).into_iter().next()
+    }
+
+    pub fn body(&self) -> Option<ast::BlockStatement> {
+        self.syntax()
+            .children()
+            .into_iter()
+            .last()
+            .and_then(AstNode::cast)
+    }
+}
+
+impl ast::ReturnStatement {
+    pub fn argument(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::ThrowStatement {
+    pub fn argument(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::SwitchStatement {
+    pub fn discriminant(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["(", ")"], 1).into_iter().next()
+    }
+
+    pub fn cases(&self) -> Vec<ast::SwitchCase> {
+        self.syntax().children_of()
+    }
+}
+
+impl ast::SwitchCase {
+    /// `None` for `default`.
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&[":"], 0).into_iter().next()
+    }
+
+    pub fn consequent(&self) -> Vec<SyntaxNode> {
+        self.syntax().group(&[":"], 1)
+    }
+}
+
+impl ast::LabeledStatement {
+    pub fn label(&self) -> Option<ast::Identifier> {
+        self.syntax().child()
+    }
+
+    pub fn body(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&[":"], 1).into_iter().next()
+    }
+}
+
+impl ast::BreakStatement {
+    pub fn label(&self) -> Option<ast::Identifier> {
+        self.syntax().child()
+    }
+}
+
+impl ast::ContinueStatement {
+    pub fn label(&self) -> Option<ast::Identifier> {
+        self.syntax().child()
+    }
+}
+
+impl ast::SequenceExpression {
+    pub fn expressions(&self) -> Vec<SyntaxNode> {
+        self.syntax().children()
+    }
+}
+
+impl ast::AssignmentExpression {
+    pub fn left(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn right(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(1)
+    }
+}
+
+impl ast::BinaryExpression {
+    pub fn left(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn right(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(1)
+    }
+}
+
+impl ast::LogicalExpression {
+    pub fn left(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn right(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(1)
+    }
+}
+
+impl ast::UnaryExpression {
+    pub fn argument(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::UpdateExpression {
+    pub fn argument(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::AwaitExpression {
+    pub fn argument(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+}
+
+impl ast::MemberExpression {
+    pub fn object(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn property(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(1)
+    }
+}
+
+impl ast::ConditionalExpression {
+    pub fn test(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["?"], 0).into_iter().next()
+    }
+
+    pub fn consequent(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["?", ":"], 1).into_iter().next()
+    }
+
+    pub fn alternate(&self) -> Option<SyntaxNode> {
+        self.syntax().group(&["?", ":"], 2).into_iter().next()
+    }
+}
+
+impl ast::CallExpression {
+    pub fn callee(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn arguments(&self) -> Vec<SyntaxNode> {
+        self.syntax().children().into_iter().skip(1).collect()
+    }
+}
+
+impl ast::NewExpression {
+    pub fn callee(&self) -> Option<SyntaxNode> {
+        self.syntax().nth(0)
+    }
+
+    pub fn arguments(&self) -> Vec<SyntaxNode> {
+        self.syntax().children().into_iter().skip(1).collect()
+    }
+}
+
+/// The children of a program or block that are not directives.
+fn statements(node: &SyntaxNode) -> Vec<SyntaxNode> {
+    node.children()
+        .into_iter()
+        .filter(|child| child.kind() != SyntaxKind::Directive)
+        .collect()
+}
+
+fn first<N: AstNode>(nodes: Vec<SyntaxNode>) -> Option<N> {
+    nodes.into_iter().find_map(N::cast)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    pub token: Token,
+    /// The token exactly as written.
+    pub text: String,
+    pub leading_trivia: Vec<Trivia>,
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl GreenToken {
+    fn leading_width(&self) -> usize {
+        trivia_width(&self.leading_trivia)
+    }
+
+    pub fn width(&self) -> usize {
+        self.leading_width() + self.text.len() + trivia_width(&self.trailing_trivia)
+    }
+
+    fn write(&self, out: &mut String) {
+        for trivia in &self.leading_trivia {
+            out.push_str(&trivia.text);
+        }
+        out.push_str(&self.text);
+        for trivia in &self.trailing_trivia {
+            out.push_str(&trivia.text);
+        }
+    }
+}
+
+fn trivia_width(trivia: &[Trivia]) -> usize {
+    trivia.iter().map(|trivia| trivia.text.len()).sum()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<GreenElement>,
+    width: usize,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(GreenElement::width).sum();
+        GreenNode {
+            kind,
+            children,
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    fn write(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write(out),
+                GreenElement::Token(token) => token.write(out),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width(),
+            GreenElement::Token(token) => token.width(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+#[derive(Debug)]
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    /// The parent and this node's index among its children.
+    parent: Option<(SyntaxNode, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+    index: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// Replace `range` of the old text with `text` to get the new text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn apply(&self, source: &str) -> String {
+        let mut result = source.to_string();
+        result.replace_range(self.range.clone(), &self.text);
+        result
+    }
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.as_ref().map(|(parent, _)| parent.clone())
+    }
+
+    /// The range of the node, trivia included.
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.width()
+    }
+
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.0.green.write(&mut out);
+        out
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut elements = vec![];
+        for (index, child) in self.0.green.children.iter().enumerate() {
+            elements.push(match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    offset,
+                    parent: Some((self.clone(), index)),
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    offset,
+                    parent: self.clone(),
+                    index,
+                }),
+            });
+            offset += child.width();
+        }
+        elements
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// This node and every node below it, in source order.
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut result = vec![self.clone()];
+        for child in self.children() {
+            result.extend(child.descendants());
+        }
+        result
+    }
+
+    /// Every token below this node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut result = vec![];
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => result.extend(node.tokens()),
+                SyntaxElement::Token(token) => result.push(token),
+            }
+        }
+        result
+    }
+
+    pub fn child<N: AstNode>(&self) -> Option<N> {
+        self.children().into_iter().find_map(N::cast)
+    }
+
+    pub fn children_of<N: AstNode>(&self) -> Vec<N> {
+        self.children().into_iter().filter_map(N::cast).collect()
+    }
+
+    fn nth(&self, index: usize) -> Option<SyntaxNode> {
+        self.children().into_iter().nth(index)
+    }
+
+    /// The child nodes between this node's own `separators` tokens, matched
+    /// in order: group `index` follows the `index`th separator. Tokens that
+    /// are not the next separator, like extra parentheses, are skipped.
+    fn group(&self, separators: &[&str], index: usize) -> Vec<SyntaxNode> {
+        let mut group = 0;
+        let mut nodes = vec![];
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Token(token)
+                    if separators
+                        .get(group)
+                        .is_some_and(|&text| token.text() == text) =>
+                {
+                    group += 1;
+                }
+                SyntaxElement::Node(node) if group == index => nodes.push(node),
+                _ => {}
+            }
+        }
+        nodes
+    }
+
+    /// Swaps in `green` for this node and returns the root of the new tree,
+    /// with the edit that turns the old text into the new one.
+    pub fn replace_with(&self, green: GreenNode) -> (SyntaxNode, TextEdit) {
+        let mut text = String::new();
+        green.write(&mut text);
+        let edit = TextEdit {
+            range: self.text_range(),
+            text,
+        };
+        let root = match &self.0.parent {
+            Some((parent, index)) => {
+                parent.replace_child(*index, GreenElement::Node(Rc::new(green)))
+            }
+            None => SyntaxNode::new_root(Rc::new(green)),
+        };
+        (root, edit)
+    }
+
+    /// Rebuilds the path from here to the root; siblings are shared.
+    fn replace_child(&self, index: usize, element: GreenElement) -> SyntaxNode {
+        let mut children = self.0.green.children.clone();
+        children[index] = element;
+        let green = Rc::new(GreenNode::new(self.kind(), children));
+        match &self.0.parent {
+            Some((parent, index)) => parent.replace_child(*index, GreenElement::Node(green)),
+            None => SyntaxNode::new_root(green),
+        }
+    }
+
+    /// An indented outline of the tree, for debugging and tests.
+    pub fn debug_tree(&self) -> String {
+        let mut out = String::new();
+        self.write_debug(&mut out, 0);
+        out
+    }
+
+    fn write_debug(&self, out: &mut String, depth: usize) {
+        let range = self.text_range();
+        out.push_str(&format!(
+            "{}{:?}@{}..{}\n",
+            "  ".repeat(depth),
+            self.kind(),
+            range.start,
+            range.end
+        ));
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => node.write_debug(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    out.push_str(&format!("{}{:?}\n", "  ".repeat(depth + 1), token.text()))
+                }
+            }
+        }
+    }
+}
+
+impl SyntaxToken {
+    pub fn token(&self) -> &Token {
+        &self.green.token
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia] {
+        &self.green.leading_trivia
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia] {
+        &self.green.trailing_trivia
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+
+    /// The range of the token itself, trivia excluded.
+    pub fn text_range(&self) -> Range<usize> {
+        let start = self.offset + self.green.leading_width();
+        start..start + self.green.text.len()
+    }
+
+    /// Swaps the token's text for `text`, which must lex as one token, and
+    /// returns the root of the new tree with the edit. Trivia is kept.
+    pub fn replace(&self, text: &str) -> Result<(SyntaxNode, TextEdit), String> {
+        let mut lex = Lex::new(text.to_string());
+        let (token, loc) = lex.next()?;
+        if loc.end.index != text.chars().count() || token == Token::EOF {
+            return Err(format!("`{text}` is not a single token"));
+        }
+        let green = GreenToken {
+            token,
+            text: text.to_string(),
+            leading_trivia: self.green.leading_trivia.clone(),
+            trailing_trivia: self.green.trailing_trivia.clone(),
+        };
+        let edit = TextEdit {
+            range: self.text_range(),
+            text: text.to_string(),
+        };
+        let root = self
+            .parent
+            .replace_child(self.index, GreenElement::Token(Rc::new(green)));
+        Ok((root, edit))
+    }
+}
+
+/// Parses `source` into a lossless syntax tree whose text is `source`.
+pub fn parse(source: &str) -> Result<SyntaxNode, String> {
+    let program = Parser::new(source.to_string())?.parse()?;
+    let mut spans = Spans { list: vec![] };
+    spans.program(&program);
+    let mut builder = Builder {
+        tokens: lex(source, &spans.list)?,
+        token: 0,
+        spans: spans.list,
+        span: 0,
+    };
+    let green = builder.node(SyntaxKind::Program, usize::MAX);
+    Ok(SyntaxNode::new_root(Rc::new(green)))
+}
+
+/// A token with its trivia and its char range in the source.
+struct Lexed {
+    green: GreenToken,
+    start: usize,
+    end: usize,
+}
+
+/// Lexes with trivia; `/` is read as a regex wherever the AST has one, and
+/// a template as one token per part.
+fn lex(source: &str, spans: &[(SyntaxKind, usize, usize)]) -> Result<Vec<Lexed>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut lex = Lex::new(source.to_string());
+    let mut tokens: Vec<Lexed> = vec![];
+    // One entry per open `{`, set for the `${` of a template.
+    let mut braces: Vec<bool> = vec![];
+    loop {
+        let mut leading_trivia = lex.read_trivia()?;
+        if let Some(last) = tokens.last_mut() {
+            let end = leading_trivia
+                .iter()
+                .position(|trivia| trivia.kind == TriviaKind::Newline)
+                .map_or(leading_trivia.len(), |index| index + 1);
+            last.green.trailing_trivia = leading_trivia.drain(..end).collect();
+        }
+        let (mut token, mut loc) = lex.next()?;
+        if matches!(&token, Token::Control(s) if s == "/" || s == "/=")
+            && spans.iter().any(|&(kind, start, _)| {
+                kind == SyntaxKind::RegExpLiteral && start == loc.start.index
+            })
+        {
+            (token, loc) = lex.reread_as_regex()?;
+        }
+        match &token {
+            Token::Control(s) if s == "{" => braces.push(false),
+            Token::Control(s) if s == "}" => {
+                if braces.pop() == Some(true) {
+                    (token, loc) = lex.reread_as_template_part()?;
+                }
+            }
+            Token::TemplateStr(_) => (token, loc) = lex.reread_as_template_part()?,
+            _ => {}
+        }
+        if let Token::TemplateStr(_) = token
+            && chars[loc.end.index - 1] == '{'
+        {
+            braces.push(true);
+        }
+        let eof = token == Token::EOF;
+        tokens.push(Lexed {
+            green: GreenToken {
+                token,
+                text: chars[loc.start.index..loc.end.index].iter().collect(),
+                leading_trivia,
+                trailing_trivia: vec![],
+            },
+            start: loc.start.index,
+            end: loc.end.index,
+        });
+        if eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+struct Builder {
+    tokens: Vec<Lexed>,
+    token: usize,
+    spans: Vec<(SyntaxKind, usize, usize)>,
+    span: usize,
+}
+
+impl Builder {
+    /// Takes tokens up to char offset `end`, opening a child node wherever
+    /// one starts; the root takes everything.
+    fn node(&mut self, kind: SyntaxKind, end: usize) -> GreenNode {
+        let mut children = vec![];
+        while let Some(token) = self.tokens.get(self.token) {
+            match self.spans.get(self.span) {
+                Some(&(child, start, child_end)) if start <= token.start && start < end => {
+                    self.span += 1;
+                    debug_assert!(
+                        child_end <= end,
+                        "{child:?}@{start}..{child_end} runs past the end of {kind:?} at {end}"
+                    );
+                    let node = self.node(child, child_end.min(end));
+                    children.push(GreenElement::Node(Rc::new(node)));
+                }
+                _ if token.end <= end => {
+                    let green = std::mem::replace(
+                        &mut self.tokens[self.token].green,
+                        GreenToken {
+                            token: Token::EOF,
+                            text: String::new(),
+                            leading_trivia: vec![],
+                            trailing_trivia: vec![],
+                        },
+                    );
+                    children.push(GreenElement::Token(Rc::new(green)));
+                    self.token += 1;
+                }
+                _ => break,
+            }
+        }
+        GreenNode::new(kind, children)
+    }
+}
+
+/// The kind and char range of every AST node, parents before children.
+struct Spans {
+    list: Vec<(SyntaxKind, usize, usize)>,
+}
+
+impl Spans {
+    fn add(&mut self, kind: SyntaxKind, loc: &Loc) {
+        self.list.push((kind, loc.start.index, loc.end.index));
+    }
+
+    fn program(&mut self, program: &Program) {
+        self.directives(&program.directives);
+        for ModuleItem::Statement(statement) in &program.body {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        let loc = statement.loc();
+        match statement {
+            Statement::BlockStatement(block) => self.block(block),
+            Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+                self.add(SyntaxKind::FunctionDeclaration, loc);
+                self.function(function);
+            }
+            Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+                self.declaration(declaration)
+            }
+            Statement::EmptyStatement { .. } => self.add(SyntaxKind::EmptyStatement, loc),
+            Statement::Invalid { .. } => self.add(SyntaxKind::Invalid, loc),
+            Statement::ExpressionStatement { expression, .. } => {
+                self.add(SyntaxKind::ExpressionStatement, loc);
+                self.expression(expression);
+            }
+            Statement::ThrowStatement { argument, .. } => {
+                self.add(SyntaxKind::ThrowStatement, loc);
+                self.expression(argument);
+            }
+            Statement::ReturnStatement { argument, .. } => {
+                self.add(SyntaxKind::ReturnStatement, loc);
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Statement::IfStatement {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.add(SyntaxKind::IfStatement, loc);
+                self.expression(test);
+                self.statement(consequent);
+                if let Some(alternate) = alternate {
+                    self.statement(alternate);
+                }
+            }
+            Statement::ForStatement {
+                init,
+                test,
+                update,
+                body,
+                ..
+            } => {
+                self.add(SyntaxKind::ForStatement, loc);
+                match init {
+                    Some(ForInit::VariableDeclaration(declaration)) => {
+                        self.declaration(declaration)
+                    }
+                    Some(ForInit::Expression(expression)) => self.expression(expression),
+                    None => {}
+                }
+                for expression in test.iter().chain(update) {
+                    self.expression(expression);
+                }
+                self.statement(body);
+            }
+            Statement::ForInStatement {
+                left, right, body, ..
+            } => {
+                self.add(SyntaxKind::ForInStatement, loc);
+                match left {
+                    ForInLeft::VariableDeclaration(declaration) => self.declaration(declaration),
+                    ForInLeft::Pattern(pattern) => self.pattern(pattern),
+                }
+                self.expression(right);
+                self.statement(body);
+            }
+            Statement::WhileStatement { test, body, .. } => {
+                self.add(SyntaxKind::WhileStatement, loc);
+                self.expression(test);
+                self.statement(body);
+            }
+            Statement::WithStatement { object, body, .. } => {
+                self.add(SyntaxKind::WithStatement, loc);
+                self.expression(object);
+                self.statement(body);
+            }
+            Statement::DoWhileStatement { body, test, .. } => {
+                self.add(SyntaxKind::DoWhileStatement, loc);
+                self.statement(body);
+                self.expression(test);
+            }
+            Statement::TryStatement {
+                block,
+                handler,
+                finalizer,
+                ..
+            } => {
+                self.add(SyntaxKind::TryStatement, loc);
+                self.block(block);
+                if let Some(CatchClause { param, body, loc }) = handler {
+                    self.add(SyntaxKind::CatchClause, loc);
+                    if let Some(param) = param {
+                        self.pattern(param);
+                    }
+                    self.block(body);
+                }
+                if let Some(finalizer) = finalizer {
+                    self.block(finalizer);
+                }
+            }
+            Statement::SwitchStatement {
+                discriminant,
+                cases,
+                ..
+            } => {
+                self.add(SyntaxKind::SwitchStatement, loc);
+                self.expression(discriminant);
+                for case in cases {
+                    self.add(SyntaxKind::SwitchCase, &case.loc);
+                    if let Some(test) = &case.test {
+                        self.expression(test);
+                    }
+                    for statement in &case.consequent {
+                        self.statement(statement);
+                    }
+                }
+            }
+            Statement::LabeledStatement { label, body, .. } => {
+                self.add(SyntaxKind::LabeledStatement, loc);
+                self.add(SyntaxKind::Identifier, &label.loc);
+                self.statement(body);
+            }
+            Statement::BreakStatement { label, .. }
+            | Statement::ContinueStatement { label, .. } => {
+                let kind = match statement {
+                    Statement::BreakStatement { .. } => SyntaxKind::BreakStatement,
+                    _ => SyntaxKind::ContinueStatement,
+                };
+                self.add(kind, loc);
+                if let Some(label) = label {
+                    self.add(SyntaxKind::Identifier, &label.loc);
+                }
+            }
+        }
+    }
+
+    fn block(&mut self, block: &BlockStatement) {
+        self.add(SyntaxKind::BlockStatement, &block.loc);
+        self.directives(&block.directives);
+        for statement in &block.body {
+            self.statement(statement);
+        }
+    }
+
+    fn directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            self.add(SyntaxKind::Directive, &directive.loc);
+            self.add(SyntaxKind::StringLiteral, &directive.value.loc);
+        }
+    }
+
+    fn declaration(&mut self, declaration: &VariableDeclaration) {
+        self.add(SyntaxKind::VariableDeclaration, &declaration.loc);
+        for declarator in &declaration.declarations {
+            self.add(SyntaxKind::VariableDeclarator, &declarator.loc);
+            self.pattern(&declarator.id);
+            if let Some(init) = &declarator.init {
+                self.expression(init);
+            }
+        }
+    }
+
+    /// The children of a function; the caller adds the function itself.
+    fn function(&mut self, function: &Function) {
+        if let Some(id) = &function.id {
+            self.add(SyntaxKind::Identifier, &id.loc);
+        }
+        for param in &function.params {
+            self.pattern(param);
+        }
+        self.block(&function.body);
+    }
+
+    fn key(&mut self, key: &PropertyKey) {
+        let kind = match key {
+            PropertyKey::Identifier(_) => SyntaxKind::Identifier,
+            PropertyKey::StringLiteral(_) => SyntaxKind::StringLiteral,
+            PropertyKey::NumericLiteral(_) => SyntaxKind::NumericLiteral,
+        };
+        self.add(kind, key.loc());
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        let loc = expression.loc();
+        match expression {
+            Expression::Identifier(_) => self.add(SyntaxKind::Identifier, loc),
+            Expression::ThisExpression { .. } => self.add(SyntaxKind::ThisExpression, loc),
+            Expression::NullLiteral { .. } => self.add(SyntaxKind::NullLiteral, loc),
+            Expression::BooleanLiteral { .. } => self.add(SyntaxKind::BooleanLiteral, loc),
+            Expression::NumericLiteral(_) => self.add(SyntaxKind::NumericLiteral, loc),
+            Expression::BigIntLiteral { .. } => self.add(SyntaxKind::BigIntLiteral, loc),
+            Expression::StringLiteral(_) => self.add(SyntaxKind::StringLiteral, loc),
+            Expression::RegExpLiteral { .. } => self.add(SyntaxKind::RegExpLiteral, loc),
+            // Quasis sit inside the template part tokens, so they get no node.
+            Expression::TemplateLiteral { expressions, .. } => {
+                self.add(SyntaxKind::TemplateLiteral, loc);
+                for expression in expressions {
+                    self.expression(expression);
+                }
+            }
+            Expression::FunctionExpression(function) => {
+                self.add(SyntaxKind::FunctionExpression, loc);
+                self.function(function);
+            }
+            Expression::ArrowFunctionExpression { params, body, .. } => {
+                self.add(SyntaxKind::ArrowFunctionExpression, loc);
+                for param in params {
+                    self.pattern(param);
+                }
+                match body {
+                    ArrowFunctionBody::BlockStatement(block) => self.block(block),
+                    ArrowFunctionBody::Expression(expression) => self.expression(expression),
+                }
+            }
+            Expression::ArrayExpression { elements, .. } => {
+                self.add(SyntaxKind::ArrayExpression, loc);
+                for element in elements.iter().flatten() {
+                    self.expression(element);
+                }
+            }
+            Expression::ObjectExpression { properties, .. } => {
+                self.add(SyntaxKind::ObjectExpression, loc);
+                for property in properties {
+                    match property {
+                        ObjectMember::ObjectProperty {
+                            key,
+                            value,
+                            shorthand,
+                            loc,
+                        } => {
+                            self.add(SyntaxKind::ObjectProperty, loc);
+                            self.key(key);
+                            if !shorthand {
+                                self.expression(value);
+                            }
+                        }
+                        ObjectMember::ObjectMethod { key, function, loc } => {
+                            self.add(SyntaxKind::ObjectMethod, loc);
+                            self.key(key);
+                            self.function(function);
+                        }
+                    }
+                }
+            }
+            Expression::SequenceExpression { expressions, .. } => {
+                self.add(SyntaxKind::SequenceExpression, loc);
+                for expression in expressions {
+                    self.expression(expression);
+                }
+            }
+            Expression::AssignmentExpression { left, right, .. } => {
+                self.add(SyntaxKind::AssignmentExpression, loc);
+                self.pattern(left);
+                self.expression(right);
+            }
+            Expression::BinaryExpression { left, right, .. }
+            | Expression::LogicalExpression { left, right, .. } => {
+                let kind = match expression {
+                    Expression::BinaryExpression { .. } => SyntaxKind::BinaryExpression,
+                    _ => SyntaxKind::LogicalExpression,
+                };
+                self.add(kind, loc);
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::UnaryExpression { argument, .. } => {
+                self.add(SyntaxKind::UnaryExpression, loc);
+                self.expression(argument);
+            }
+            Expression::UpdateExpression { argument, .. } => {
+                self.add(SyntaxKind::UpdateExpression, loc);
+                self.expression(argument);
+            }
+            Expression::AwaitExpression { argument, .. } => {
+                self.add(SyntaxKind::AwaitExpression, loc);
+                self.expression(argument);
+            }
+            Expression::MemberExpression(member) => {
+                self.add(SyntaxKind::MemberExpression, loc);
+                self.expression(&member.object);
+                self.expression(&member.property);
+            }
+            Expression::ConditionalExpression {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.add(SyntaxKind::ConditionalExpression, loc);
+                self.expression(test);
+                self.expression(consequent);
+                self.expression(alternate);
+            }
+            Expression::CallExpression {
+                callee, arguments, ..
+            }
+            | Expression::NewExpression {
+                callee, arguments, ..
+            } => {
+                let kind = match expression {
+                    Expression::CallExpression { .. } => SyntaxKind::CallExpression,
+                    _ => SyntaxKind::NewExpression,
+                };
+                self.add(kind, loc);
+                self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::ParenthesizedExpression { expression, .. } => {
+                self.add(SyntaxKind::ParenthesizedExpression, loc);
+                self.expression(expression);
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        let loc = pattern.loc();
+        match pattern {
+            Pattern::Identifier(_) => self.add(SyntaxKind::Identifier, loc),
+            Pattern::MemberExpression(member) => {
+                self.add(SyntaxKind::MemberExpression, loc);
+                self.expression(&member.object);
+                self.expression(&member.property);
+            }
+            Pattern::ObjectPattern { properties, .. } => {
+                self.add(SyntaxKind::ObjectPattern, loc);
+                for property in properties {
+                    self.add(SyntaxKind::ObjectPatternProperty, &property.loc);
+                    match (&property.value, property.shorthand) {
+                        (Pattern::AssignmentPattern { right, loc, .. }, true) => {
+                            self.add(SyntaxKind::AssignmentPattern, loc);
+                            self.key(&property.key);
+                            self.expression(right);
+                        }
+                        (_, true) => self.key(&property.key),
+                        (value, false) => {
+                            self.key(&property.key);
+                            self.pattern(value);
+                        }
+                    }
+                }
+            }
+            Pattern::ArrayPattern { elements, .. } => {
+                self.add(SyntaxKind::ArrayPattern, loc);
+                for element in elements.iter().flatten() {
+                    self.pattern(element);
+                }
+            }
+            Pattern::AssignmentPattern { left, right, .. } => {
+                self.add(SyntaxKind::AssignmentPattern, loc);
+                self.pattern(left);
+                self.expression(right);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cst {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// Every fixture must come back byte for byte.
+    #[test]
+    fn test_lossless_fixtures() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let mut count = 0;
+        for dir in fs::read_dir(&root).unwrap() {
+            let dir = dir.unwrap().path();
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_none_or(|ext| ext != "js") {
+                    continue;
+                }
+                let source = fs::read_to_string(&path).unwrap();
+                let tree = parse(&source).unwrap();
+                assert_eq!(tree.text(), source, "{}", path.display());
+                assert_eq!(tree.text_range(), 0..source.len());
+                count += 1;
+            }
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn test_tree() -> Result<(), String> {
+        let tree = parse("// c\nif (a) /re/g.test(b); // t\n")?;
+        assert_eq!(
+            tree.debug_tree(),
+            "Program@0..32
+  IfStatement@0..32
+    \"if\"
+    \"(\"
+    Identifier@9..10
+      \"a\"
+    \")\"
+    ExpressionStatement@12..32
+      CallExpression@12..25
+        MemberExpression@12..22
+          RegExpLiteral@12..17
+            \"/re/g\"
+          \".\"
+          Identifier@18..22
+            \"test\"
+        \"(\"
+        Identifier@23..24
+          \"b\"
+        \")\"
+      \";\"
+  \"\"
+"
+        );
+        let tokens = tree.tokens();
+        assert_eq!(tokens[0].leading_trivia().len(), 2);
+        assert_eq!(tokens[10].text(), ";");
+        assert_eq!(tokens[10].trailing_trivia().len(), 3);
+        let statement = tree.child::<ast::IfStatement>().unwrap();
+        let names: Vec<String> = statement
+            .syntax()
+            .descendants()
+            .into_iter()
+            .filter_map(ast::Identifier::cast)
+            .map(|identifier| identifier.name())
+            .collect();
+        assert_eq!(names, ["a", "test", "b"]);
+        // A node right after a block is a sibling of it, not dropped.
+        let kinds: Vec<SyntaxKind> = parse("{}\nx: a;")?
+            .children()
+            .iter()
+            .map(SyntaxNode::kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [SyntaxKind::BlockStatement, SyntaxKind::LabeledStatement]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_accessors() -> Result<(), String> {
+        let text = |node: Option<SyntaxNode>| node.map(|node| node.text().trim().to_string());
+        let tree = parse("if ((a)) b; else c\nfunction f(x, y = 1) { 'use asm'; return x }")?;
+        let program = ast::Program::cast(tree).unwrap();
+        let [statement, function] = &program.body()[..] else {
+            panic!("expect two statements");
+        };
+        let statement = ast::IfStatement::cast(statement.clone()).unwrap();
+        assert_eq!(text(statement.test()).as_deref(), Some("a"));
+        assert_eq!(text(statement.consequent()).as_deref(), Some("b;"));
+        assert_eq!(text(statement.alternate()).as_deref(), Some("c"));
+        let function = ast::FunctionDeclaration::cast(function.clone()).unwrap();
+        assert_eq!(function.id().unwrap().name(), "f");
+        let params: Vec<SyntaxKind> = function.params().iter().map(SyntaxNode::kind).collect();
+        assert_eq!(
+            params,
+            [SyntaxKind::Identifier, SyntaxKind::AssignmentPattern]
+        );
+        let body = function.body().unwrap();
+        assert_eq!(body.directives().len(), 1);
+        assert_eq!(body.body()[0].kind(), SyntaxKind::ReturnStatement);
+
+        let tree = parse("for (let i = 0; ; i++) x ? y : z;")?;
+        let statement = tree.child::<ast::ForStatement>().unwrap();
+        assert_eq!(text(statement.init()).as_deref(), Some("let i = 0"));
+        assert_eq!(statement.test().map(|node| node.kind()), None);
+        assert_eq!(text(statement.update()).as_deref(), Some("i++"));
+        let body = ast::ExpressionStatement::cast(statement.body().unwrap()).unwrap();
+        let conditional = ast::ConditionalExpression::cast(body.expression().unwrap()).unwrap();
+        assert_eq!(text(conditional.alternate()).as_deref(), Some("z"));
+
+        let tree = parse("try {} catch {} finally { f(a, b) }")?;
+        let statement = tree.child::<ast::TryStatement>().unwrap();
+        assert!(statement.handler().unwrap().param().is_none());
+        let finalizer = statement.finalizer().unwrap();
+        let call = ast::ExpressionStatement::cast(finalizer.body()[0].clone())
+            .and_then(|statement| ast::CallExpression::cast(statement.expression()?))
+            .unwrap();
+        assert_eq!(call.arguments().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "runs past the end of ExpressionStatement")]
+    fn test_child_past_parent() {
+        let spans = vec![
+            (SyntaxKind::ExpressionStatement, 0, 1),
+            (SyntaxKind::Identifier, 0, 3),
+        ];
+        let mut builder = Builder {
+            tokens: lex("ab;", &spans).unwrap(),
+            token: 0,
+            spans,
+            span: 0,
+        };
+        builder.node(SyntaxKind::Program, usize::MAX);
+    }
+
+    #[test]
+    fn test_edit() -> Result<(), String> {
+        let source = "let  a = 1; /* keep */\nf( a ,\tb );\n";
+        let tree = parse(source)?;
+        let token = tree
+            .tokens()
+            .into_iter()
+            .filter(|token| token.text() == "a")
+            .nth(1)
+            .unwrap();
+        let (root, edit) = token.replace("renamed")?;
+        assert_eq!(edit.range, 26..27);
+        let expected = "let  a = 1; /* keep */\nf( renamed ,\tb );\n";
+        assert_eq!(edit.apply(source), expected);
+        assert_eq!(root.text(), expected);
+        let old = tree.children();
+        let new = root.children();
+        assert!(Rc::ptr_eq(old[0].green(), new[0].green()));
+        assert!(!Rc::ptr_eq(old[1].green(), new[1].green()));
+        assert!(token.replace("a b").is_err());
+
+        let declaration = root.child::<ast::VariableDeclaration>().unwrap();
+        let (_, edit) = declaration
+            .syntax()
+            .replace_with(GreenNode::new(SyntaxKind::EmptyStatement, vec![]));
+        assert_eq!(edit.range, 0..23);
+        assert_eq!(edit.text, "");
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+//! Diagnostics and their rendering: a header with the message and the
+//! `file:line:column` of the error, then a code frame of the lines around
+//! it with the span underlined. Secondary labels get their own underline
+//! and text, help notes follow the frame.
+
+use crate::lex::Loc;
+use crate::unicode::is_line_terminator;
+use std::collections::BTreeSet;
+
+/// Lines shown before and after every underlined one.
+const CONTEXT: usize = 2;
+
+const RED: &str = "1;31";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+/// A syntax error, as [`crate::parser::Parser::parse`] and
+/// [`crate::parser::Parser::parse_tolerant`] report it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub loc: Loc,
+    pub labels: Vec<Label>,
+    pub help: Vec<String>,
+}
+
+/// A secondary span, such as where an unclosed bracket was opened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub loc: Loc,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(message: String, loc: Loc) -> Diagnostic {
+        Diagnostic {
+            message,
+            loc,
+            labels: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, loc: Loc, message: &str) -> Diagnostic {
+        self.labels.push(Label {
+            loc,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help.push(help.to_string());
+        self
+    }
+}
+
+/// Just the message, for code whose errors are strings.
+impl From<Diagnostic> for String {
+    fn from(diagnostic: Diagnostic) -> String {
+        diagnostic.message
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Colour the output with ANSI escapes for a terminal.
+    pub color: bool,
+}
+
+pub fn render(
+    diagnostic: &Diagnostic,
+    file: &str,
+    source: &str,
+    options: &RenderOptions,
+) -> String {
+    let paint = |style: &str, text: &str| match options.color {
+        true => format!("\x1b[{style}m{text}\x1b[0m"),
+        false => text.to_string(),
+    };
+    let lines = lines(source);
+    // The primary span first, then the labels.
+    let mut markers = vec![(&diagnostic.loc, '^', RED, "")];
+    for label in &diagnostic.labels {
+        markers.push((&label.loc, '-', BLUE, label.message.as_str()));
+    }
+    let mut shown = BTreeSet::new();
+    for (loc, ..) in &markers {
+        let line = loc.start.line;
+        shown.extend(line.saturating_sub(CONTEXT).max(1)..=(line + CONTEXT).min(lines.len()));
+    }
+    let width = shown.last().map_or(1, |line| line.to_string().len());
+    let gutter = |number: &str| paint(BLUE, &format!("{number:>width$} |"));
+
+    let start = &diagnostic.loc.start;
+    let mut out = format!(
+        "{}{}\n{}{} {file}:{}:{}\n{}\n",
+        paint(RED, "error"),
+        paint(BOLD, &format!(": {}", diagnostic.message)),
+        " ".repeat(width),
+        paint(BLUE, "-->"),
+        start.line,
+        start.column,
+        gutter(""),
+    );
+    let mut previous = None;
+    for line in shown {
+        if previous.is_some_and(|previous| previous + 1 < line) {
+            out.push_str(&paint(BLUE, "..."));
+            out.push('\n');
+        }
+        previous = Some(line);
+        let text = lines[line - 1];
+        out.push_str(&gutter(&line.to_string()));
+        if !text.is_empty() {
+            out.push(' ');
+            out.push_str(text);
+        }
+        out.push('\n');
+        for (loc, marker, style, message) in &markers {
+            if loc.start.line != line {
+                continue;
+            }
+            out.push_str(&format!(
+                "{} {}{}",
+                gutter(""),
+                padding(text, loc.start.column),
+                paint(style, &marker.to_string().repeat(underline_len(text, loc))),
+            ));
+            if !message.is_empty() {
+                out.push_str(&format!(" {}", paint(style, message)));
+            }
+            out.push('\n');
+        }
+    }
+    for help in &diagnostic.help {
+        out.push_str(&format!(
+            "{} = {}: {help}\n",
+            " ".repeat(width),
+            paint(BOLD, "help")
+        ));
+    }
+    out
+}
+
+/// The lines of `source` as the lexer counts them: split at every line
+/// terminator, with CRLF a single break.
+fn lines(source: &str) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !is_line_terminator(c) {
+            continue;
+        }
+        lines.push(&source[start..i]);
+        start = i + c.len_utf8();
+        if c == '\r' && chars.next_if(|&(_, c)| c == '\n').is_some() {
+            start += 1;
+        }
+    }
+    lines.push(&source[start..]);
+    lines
+}
+
+/// Blanks up to `column`, keeping tabs so the underline lines up.
+fn padding(text: &str, column: usize) -> String {
+    text.chars()
+        .chain(std::iter::repeat(' '))
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect()
+}
+
+/// A span over several lines is underlined to the end of its first.
+fn underline_len(text: &str, loc: &Loc) -> usize {
+    let len = match loc.end.line == loc.start.line {
+        true => loc.end.column.saturating_sub(loc.start.column),
+        false => (text.chars().count() + 1).saturating_sub(loc.start.column),
+    };
+    len.max(1)
+}
+
+#[cfg(test)]
+mod test_diagnostic {
+    use super::*;
+    use crate::lex::Position;
+    use crate::parser::Parser;
+
+    fn loc(line: usize, column: usize, end: usize) -> Loc {
+        let position = |column| Position {
+            line,
+            column,
+            index: 0,
+        };
+        Loc {
+            start: position(column),
+            end: position(end),
+        }
+    }
+
+    #[test]
+    fn test_render() -> Result<(), String> {
+        let source = "let a = 1;\n\nif (x) {\n\tg(; h()\n}\nf()\nlet b = 2;\n";
+        let (_, diagnostics) = Parser::new(source.to_string())?.parse_tolerant();
+        let diagnostic = diagnostics[0]
+            .clone()
+            .with_label(loc(4, 3, 4), "opening `(` was here")
+            .with_help("close the call with `)`");
+        assert_eq!(
+            render(&diagnostic, "a.js", source, &RenderOptions::default()),
+            format!(
+                "error: {}
+ --> a.js:4:4
+  |
+2 |
+3 | if (x) {{
+4 | \tg(; h()
+  | \t  ^
+  | \t - opening `(` was here
+5 | }}
+6 | f()
+  = help: close the call with `)`
+",
+                diagnostic.message
+            )
+        );
+        let colored = render(&diagnostic, "a.js", source, &RenderOptions { color: true });
+        assert!(colored.starts_with("\x1b[1;31merror\x1b[0m"));
+        assert!(colored.contains("\x1b[1;31m^\x1b[0m"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error() -> Result<(), String> {
+        let source = "if (a) {\n  f(b, c;\n}\n";
+        let diagnostic = Parser::new(source.to_string())?.parse().unwrap_err();
+        assert_eq!(
+            render(&diagnostic, "c.js", source, &RenderOptions::default()),
+            "error: Unexpected token ;, expected `)`
+ --> c.js:2:9
+  |
+1 | if (a) {
+2 |   f(b, c;
+  |         ^
+  |    - opening `(` was here
+3 | }
+4 |
+"
+        );
+        // Only the innermost open bracket is pointed at.
+        let diagnostic = Parser::new("[a, (b]".to_string())?.parse().unwrap_err();
+        assert_eq!(diagnostic.loc.start.column, 7);
+        assert_eq!(diagnostic.labels[0].loc.start.column, 5);
+        let diagnostic = Parser::new("a b".to_string())?.parse().unwrap_err();
+        assert_eq!(diagnostic.loc.start.column, 3);
+        assert!(diagnostic.labels.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_breaks() -> Result<(), String> {
+        for source in ["a;\r\nb c", "a;\rb c", "a;\u{2028}b c", "a;\u{2029}b c"] {
+            let diagnostic = Parser::new(source.to_string())?.parse().unwrap_err();
+            assert_eq!(
+                render(&diagnostic, "d.js", source, &RenderOptions::default()),
+                "error: syntax error:\n --> d.js:2:3\n  |\n1 | a;\n2 | b c\n  |   ^\n",
+                "{source:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame() {
+        let source: Vec<String> = (1..=9).map(|n| format!("line{n}")).collect();
+        let diagnostic =
+            Diagnostic::new("bad".to_string(), loc(9, 1, 5)).with_label(loc(1, 5, 6), "here");
+        assert_eq!(
+            render(
+                &diagnostic,
+                "b.js",
+                &source.join("\n"),
+                &RenderOptions::default()
+            ),
+            "error: bad
+ --> b.js:9:1
+  |
+1 | line1
+  |     - here
+2 | line2
+3 | line3
+...
+7 | line7
+8 | line8
+9 | line9
+  | ^^^^
+"
+        );
+    }
+}
@@ -0,0 +1,850 @@
+//! Serializes the AST as ESTree JSON, with `start`/`end` and `loc` on every
+//! node, and `range` too with [`EstreeOptions::ranges`]. Offsets and columns
+//! count chars; columns are 0-based.
+//!
+//! Only standard ESTree fields are written, so parentheses and trailing
+//! commas are lost; [`crate::babel`] keeps them in `extra`.
+
+use crate::json::{Json, array, optional, string};
+use crate::lex::{Loc, Position};
+use crate::node::{
+    ArrowFunctionBody, BlockStatement, CatchClause, Declaration, Directive, Expression, ForInLeft,
+    ForInit, Function, Identifier, MemberExpression, ModuleItem, NumericLiteral, ObjectMember,
+    Pattern, Program, PropertyKey, SourceType, Statement, StringLiteral, SwitchCase,
+    VariableDeclaration, VariableDeclarator, VariableKind,
+};
+use crate::token::Token;
+use crate::tokenize::{SourceToken, TokenizeOptions, tokenize};
+
+pub fn to_json(program: &Program) -> String {
+    program_to_estree(program).to_string_pretty()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EstreeOptions {
+    /// Write `range` next to `loc` on every node.
+    pub ranges: bool,
+}
+
+pub fn program_to_estree(program: &Program) -> Json {
+    program_to_estree_with(program, &EstreeOptions::default())
+}
+
+pub fn program_to_estree_with(program: &Program, options: &EstreeOptions) -> Json {
+    let json = node(
+        "Program",
+        &program.loc,
+        vec![
+            (
+                "body",
+                Json::Array(
+                    program
+                        .directives
+                        .iter()
+                        .map(directive_to_estree)
+                        .chain(program.body.iter().map(|item| match item {
+                            ModuleItem::Statement(statement) => statement_to_estree(statement),
+                        }))
+                        .collect(),
+                ),
+            ),
+            // ESTree only knows scripts and modules.
+            (
+                "sourceType",
+                string(match program.source_type {
+                    SourceType::Module => "module",
+                    _ => "script",
+                }),
+            ),
+        ],
+    );
+    match options.ranges {
+        true => json,
+        false => without_ranges(json),
+    }
+}
+
+fn without_ranges(json: Json) -> Json {
+    match json {
+        Json::Object(members) => Json::Object(
+            members
+                .into_iter()
+                .filter(|(key, _)| key != "range")
+                .map(|(key, value)| (key, without_ranges(value)))
+                .collect(),
+        ),
+        Json::Array(items) => Json::Array(items.into_iter().map(without_ranges).collect()),
+        json => json,
+    }
+}
+
+/// [`program_to_estree`] with esprima's `tokens: true`: every token of
+/// `source` but the comments, in a `tokens` array after `sourceType`.
+pub fn program_to_estree_with_tokens(program: &Program, source: &str) -> Result<Json, String> {
+    let tokens = tokenize(source, TokenizeOptions::default()).collect::<Result<Vec<_>, _>>()?;
+    let mut json = program_to_estree(program);
+    if let Json::Object(members) = &mut json {
+        members.push(("tokens".to_string(), tokens_to_estree(&tokens)));
+    }
+    Ok(json)
+}
+
+/// Tokens the way esprima's `tokenize` reports them, with `range` and `loc`.
+pub fn tokens_to_estree(tokens: &[SourceToken]) -> Json {
+    array(tokens, |token| {
+        let mut members = vec![
+            ("type".to_string(), string(token.kind.name())),
+            ("value".to_string(), string(&token.value)),
+        ];
+        if let Token::Regex(pattern, flags) = &token.token {
+            members.push((
+                "regex".to_string(),
+                Json::Object(vec![
+                    ("pattern".to_string(), string(pattern)),
+                    ("flags".to_string(), string(flags)),
+                ]),
+            ));
+        }
+        members.push((
+            "range".to_string(),
+            Json::Array(vec![offset(&token.loc.start), offset(&token.loc.end)]),
+        ));
+        members.push((
+            "loc".to_string(),
+            Json::Object(vec![
+                ("start".to_string(), position(&token.loc.start)),
+                ("end".to_string(), position(&token.loc.end)),
+            ]),
+        ));
+        Json::Object(members)
+    })
+}
+
+pub fn statement_to_estree(statement: &Statement) -> Json {
+    match statement {
+        Statement::EmptyStatement { loc } => node("EmptyStatement", loc, vec![]),
+        Statement::BlockStatement(block) => block_to_estree(block),
+        Statement::ExpressionStatement { expression, loc } => node(
+            "ExpressionStatement",
+            loc,
+            vec![("expression", expression_to_estree(expression))],
+        ),
+        Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+            function_to_estree("FunctionDeclaration", function)
+        }
+        Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+            variable_declaration_to_estree(declaration)
+        }
+        Statement::IfStatement {
+            test,
+            consequent,
+            alternate,
+            loc,
+        } => node(
+            "IfStatement",
+            loc,
+            vec![
+                ("test", expression_to_estree(test)),
+                ("consequent", statement_to_estree(consequent)),
+                (
+                    "alternate",
+                    optional(alternate.as_deref(), statement_to_estree),
+                ),
+            ],
+        ),
+        Statement::ForStatement {
+            init,
+            test,
+            update,
+            body,
+            loc,
+        } => node(
+            "ForStatement",
+            loc,
+            vec![
+                (
+                    "init",
+                    match init {
+                        Some(ForInit::VariableDeclaration(declaration)) => {
+                            variable_declaration_to_estree(declaration)
+                        }
+                        Some(ForInit::Expression(expression)) => expression_to_estree(expression),
+                        None => Json::Null,
+                    },
+                ),
+                ("test", optional(test.as_deref(), expression_to_estree)),
+                ("update", optional(update.as_deref(), expression_to_estree)),
+                ("body", statement_to_estree(body)),
+            ],
+        ),
+        Statement::ForInStatement {
+            left,
+            right,
+            body,
+            loc,
+        } => node(
+            "ForInStatement",
+            loc,
+            vec![
+                (
+                    "left",
+                    match left {
+                        ForInLeft::VariableDeclaration(declaration) => {
+                            variable_declaration_to_estree(declaration)
+                        }
+                        ForInLeft::Pattern(pattern) => pattern_to_estree(pattern),
+                    },
+                ),
+                ("right", expression_to_estree(right)),
+                ("body", statement_to_estree(body)),
+            ],
+        ),
+        Statement::WhileStatement { test, body, loc } => node(
+            "WhileStatement",
+            loc,
+            vec![
+                ("test", expression_to_estree(test)),
+                ("body", statement_to_estree(body)),
+            ],
+        ),
+        Statement::DoWhileStatement { body, test, loc } => node(
+            "DoWhileStatement",
+            loc,
+            vec![
+                ("body", statement_to_estree(body)),
+                ("test", expression_to_estree(test)),
+            ],
+        ),
+        Statement::WithStatement { object, body, loc } => node(
+            "WithStatement",
+            loc,
+            vec![
+                ("object", expression_to_estree(object)),
+                ("body", statement_to_estree(body)),
+            ],
+        ),
+        Statement::TryStatement {
+            block,
+            handler,
+            finalizer,
+            loc,
+        } => node(
+            "TryStatement",
+            loc,
+            vec![
+                ("block", block_to_estree(block)),
+                (
+                    "handler",
+                    optional(handler.as_ref(), catch_clause_to_estree),
+                ),
+                ("finalizer", optional(finalizer.as_ref(), block_to_estree)),
+            ],
+        ),
+        Statement::ReturnStatement { argument, loc } => node(
+            "ReturnStatement",
+            loc,
+            vec![(
+                "argument",
+                optional(argument.as_deref(), expression_to_estree),
+            )],
+        ),
+        Statement::SwitchStatement {
+            discriminant,
+            cases,
+            loc,
+        } => node(
+            "SwitchStatement",
+            loc,
+            vec![
+                ("discriminant", expression_to_estree(discriminant)),
+                ("cases", array(cases, switch_case_to_estree)),
+            ],
+        ),
+        Statement::LabeledStatement { label, body, loc } => node(
+            "LabeledStatement",
+            loc,
+            vec![
+                ("label", identifier_to_estree(label)),
+                ("body", statement_to_estree(body)),
+            ],
+        ),
+        Statement::BreakStatement { label, loc } => node(
+            "BreakStatement",
+            loc,
+            vec![("label", optional(label.as_ref(), identifier_to_estree))],
+        ),
+        Statement::ContinueStatement { label, loc } => node(
+            "ContinueStatement",
+            loc,
+            vec![("label", optional(label.as_ref(), identifier_to_estree))],
+        ),
+        Statement::ThrowStatement { argument, loc } => node(
+            "ThrowStatement",
+            loc,
+            vec![("argument", expression_to_estree(argument))],
+        ),
+        Statement::Invalid { loc } => node("Invalid", loc, vec![]),
+    }
+}
+
+pub fn expression_to_estree(expression: &Expression) -> Json {
+    match expression {
+        Expression::Identifier(identifier) => identifier_to_estree(identifier),
+        Expression::ThisExpression { loc, .. } => node("ThisExpression", loc, vec![]),
+        Expression::NullLiteral { loc, .. } => literal(loc, Json::Null, "null", vec![]),
+        Expression::BooleanLiteral { value, loc, .. } => {
+            literal(loc, Json::Bool(*value), &value.to_string(), vec![])
+        }
+        Expression::NumericLiteral(literal) => numeric_literal_to_estree(literal),
+        Expression::StringLiteral(literal) => string_literal_to_estree(literal),
+        Expression::BigIntLiteral {
+            value, raw, loc, ..
+        } => literal(loc, Json::Null, raw, vec![("bigint", string(value))]),
+        Expression::RegExpLiteral {
+            pattern,
+            flags,
+            loc,
+            ..
+        } => literal(
+            loc,
+            Json::Null,
+            &format!("/{pattern}/{flags}"),
+            vec![(
+                "regex",
+                Json::Object(vec![
+                    ("pattern".to_string(), string(pattern)),
+                    ("flags".to_string(), string(flags)),
+                ]),
+            )],
+        ),
+        Expression::TemplateLiteral {
+            expressions,
+            quasis,
+            loc,
+            ..
+        } => node(
+            "TemplateLiteral",
+            loc,
+            vec![
+                ("expressions", array(expressions, expression_to_estree)),
+                (
+                    "quasis",
+                    Json::Array(
+                        quasis
+                            .iter()
+                            .enumerate()
+                            .map(|(i, quasi)| {
+                                node(
+                                    "TemplateElement",
+                                    &quasi.loc,
+                                    vec![
+                                        (
+                                            "value",
+                                            Json::Object(vec![
+                                                ("raw".to_string(), string(&quasi.value)),
+                                                ("cooked".to_string(), string(&quasi.cooked)),
+                                            ]),
+                                        ),
+                                        ("tail", Json::Bool(i == quasis.len() - 1)),
+                                    ],
+                                )
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        Expression::ArrayExpression { elements, loc, .. } => node(
+            "ArrayExpression",
+            loc,
+            vec![(
+                "elements",
+                Json::Array(
+                    elements
+                        .iter()
+                        .map(|element| optional(element.as_ref(), expression_to_estree))
+                        .collect(),
+                ),
+            )],
+        ),
+        Expression::ObjectExpression {
+            properties, loc, ..
+        } => node(
+            "ObjectExpression",
+            loc,
+            vec![("properties", array(properties, object_member_to_estree))],
+        ),
+        Expression::FunctionExpression(function) => {
+            function_to_estree("FunctionExpression", function)
+        }
+        Expression::ArrowFunctionExpression {
+            params, body, loc, ..
+        } => node(
+            "ArrowFunctionExpression",
+            loc,
+            vec![
+                ("id", Json::Null),
+                (
+                    "expression",
+                    Json::Bool(matches!(body, ArrowFunctionBody::Expression(_))),
+                ),
+                ("generator", Json::Bool(false)),
+                ("async", Json::Bool(false)),
+                ("params", array(params, pattern_to_estree)),
+                (
+                    "body",
+                    match body {
+                        ArrowFunctionBody::BlockStatement(block) => block_to_estree(block),
+                        ArrowFunctionBody::Expression(expression) => {
+                            expression_to_estree(expression)
+                        }
+                    },
+                ),
+            ],
+        ),
+        Expression::SequenceExpression {
+            expressions, loc, ..
+        } => node(
+            "SequenceExpression",
+            loc,
+            vec![("expressions", array(expressions, expression_to_estree))],
+        ),
+        Expression::AssignmentExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node(
+            "AssignmentExpression",
+            loc,
+            vec![
+                ("operator", string(operator)),
+                ("left", pattern_to_estree(left)),
+                ("right", expression_to_estree(right)),
+            ],
+        ),
+        Expression::BinaryExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node("BinaryExpression", loc, binary(left, operator, right)),
+        Expression::LogicalExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } => node("LogicalExpression", loc, binary(left, operator, right)),
+        Expression::UnaryExpression {
+            operator,
+            prefix,
+            argument,
+            loc,
+            ..
+        } => unary("UnaryExpression", operator, *prefix, argument, loc),
+        Expression::UpdateExpression {
+            operator,
+            prefix,
+            argument,
+            loc,
+            ..
+        } => unary("UpdateExpression", operator, *prefix, argument, loc),
+        Expression::AwaitExpression { argument, loc, .. } => node(
+            "AwaitExpression",
+            loc,
+            vec![("argument", expression_to_estree(argument))],
+        ),
+        Expression::MemberExpression(member) => member_to_estree(member),
+        Expression::ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+            loc,
+            ..
+        } => node(
+            "ConditionalExpression",
+            loc,
+            vec![
+                ("test", expression_to_estree(test)),
+                ("consequent", expression_to_estree(consequent)),
+                ("alternate", expression_to_estree(alternate)),
+            ],
+        ),
+        Expression::CallExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => node(
+            "CallExpression",
+            loc,
+            vec![
+                ("callee", expression_to_estree(callee)),
+                ("arguments", array(arguments, expression_to_estree)),
+                ("optional", Json::Bool(false)),
+            ],
+        ),
+        Expression::NewExpression {
+            callee,
+            arguments,
+            loc,
+            ..
+        } => node(
+            "NewExpression",
+            loc,
+            vec![
+                ("callee", expression_to_estree(callee)),
+                ("arguments", array(arguments, expression_to_estree)),
+            ],
+        ),
+        Expression::ParenthesizedExpression { expression, loc } => node(
+            "ParenthesizedExpression",
+            loc,
+            vec![("expression", expression_to_estree(expression))],
+        ),
+    }
+}
+
+pub fn pattern_to_estree(pattern: &Pattern) -> Json {
+    match pattern {
+        Pattern::Identifier(identifier) => identifier_to_estree(identifier),
+        Pattern::MemberExpression(member) => member_to_estree(member),
+        Pattern::ObjectPattern {
+            properties, loc, ..
+        } => node(
+            "ObjectPattern",
+            loc,
+            vec![(
+                "properties",
+                Json::Array(
+                    properties
+                        .iter()
+                        .map(|property| {
+                            property_to_estree(
+                                &property.loc,
+                                &property.key,
+                                pattern_to_estree(&property.value),
+                                false,
+                                property.shorthand,
+                            )
+                        })
+                        .collect(),
+                ),
+            )],
+        ),
+        Pattern::ArrayPattern { elements, loc, .. } => node(
+            "ArrayPattern",
+            loc,
+            vec![(
+                "elements",
+                Json::Array(
+                    elements
+                        .iter()
+                        .map(|element| optional(element.as_ref(), pattern_to_estree))
+                        .collect(),
+                ),
+            )],
+        ),
+        Pattern::AssignmentPattern { left, right, loc } => node(
+            "AssignmentPattern",
+            loc,
+            vec![
+                ("left", pattern_to_estree(left)),
+                ("right", expression_to_estree(right)),
+            ],
+        ),
+    }
+}
+
+fn block_to_estree(block: &BlockStatement) -> Json {
+    node(
+        "BlockStatement",
+        &block.loc,
+        vec![(
+            "body",
+            Json::Array(
+                block
+                    .directives
+                    .iter()
+                    .map(directive_to_estree)
+                    .chain(block.body.iter().map(statement_to_estree))
+                    .collect(),
+            ),
+        )],
+    )
+}
+
+/// ESTree keeps directives in the body, as expression statements with the
+/// raw text between the quotes in `directive`.
+fn directive_to_estree(directive: &Directive) -> Json {
+    node(
+        "ExpressionStatement",
+        &directive.loc,
+        vec![
+            ("expression", string_literal_to_estree(&directive.value)),
+            ("directive", string(directive.raw_value())),
+        ],
+    )
+}
+
+fn catch_clause_to_estree(clause: &CatchClause) -> Json {
+    node(
+        "CatchClause",
+        &clause.loc,
+        vec![
+            ("param", optional(clause.param.as_ref(), pattern_to_estree)),
+            ("body", block_to_estree(&clause.body)),
+        ],
+    )
+}
+
+fn switch_case_to_estree(case: &SwitchCase) -> Json {
+    node(
+        "SwitchCase",
+        &case.loc,
+        vec![
+            ("test", optional(case.test.as_deref(), expression_to_estree)),
+            ("consequent", array(&case.consequent, statement_to_estree)),
+        ],
+    )
+}
+
+fn variable_declaration_to_estree(declaration: &VariableDeclaration) -> Json {
+    let kind = match declaration.kind {
+        VariableKind::Var => "var",
+        VariableKind::Let => "let",
+        VariableKind::Const => "const",
+    };
+    node(
+        "VariableDeclaration",
+        &declaration.loc,
+        vec![
+            (
+                "declarations",
+                array(&declaration.declarations, variable_declarator_to_estree),
+            ),
+            ("kind", string(kind)),
+        ],
+    )
+}
+
+fn variable_declarator_to_estree(declarator: &VariableDeclarator) -> Json {
+    node(
+        "VariableDeclarator",
+        &declarator.loc,
+        vec![
+            ("id", pattern_to_estree(&declarator.id)),
+            (
+                "init",
+                optional(declarator.init.as_deref(), expression_to_estree),
+            ),
+        ],
+    )
+}
+
+fn function_to_estree(kind: &str, function: &Function) -> Json {
+    node(
+        kind,
+        &function.loc,
+        vec![
+            ("id", optional(function.id.as_ref(), identifier_to_estree)),
+            ("expression", Json::Bool(false)),
+            ("generator", Json::Bool(false)),
+            ("async", Json::Bool(false)),
+            ("params", array(&function.params, pattern_to_estree)),
+            ("body", block_to_estree(&function.body)),
+        ],
+    )
+}
+
+fn object_member_to_estree(member: &ObjectMember) -> Json {
+    match member {
+        ObjectMember::ObjectProperty {
+            key,
+            value,
+            shorthand,
+            loc,
+        } => property_to_estree(loc, key, expression_to_estree(value), false, *shorthand),
+        ObjectMember::ObjectMethod { key, function, loc } => property_to_estree(
+            loc,
+            key,
+            function_to_estree("FunctionExpression", function),
+            true,
+            false,
+        ),
+    }
+}
+
+fn property_to_estree(
+    loc: &Loc,
+    key: &PropertyKey,
+    value: Json,
+    method: bool,
+    shorthand: bool,
+) -> Json {
+    node(
+        "Property",
+        loc,
+        vec![
+            ("method", Json::Bool(method)),
+            ("shorthand", Json::Bool(shorthand)),
+            ("computed", Json::Bool(false)),
+            (
+                "key",
+                match key {
+                    PropertyKey::Identifier(identifier) => identifier_to_estree(identifier),
+                    PropertyKey::StringLiteral(literal) => string_literal_to_estree(literal),
+                    PropertyKey::NumericLiteral(literal) => numeric_literal_to_estree(literal),
+                },
+            ),
+            ("value", value),
+            ("kind", string("init")),
+        ],
+    )
+}
+
+fn member_to_estree(member: &MemberExpression) -> Json {
+    node(
+        "MemberExpression",
+        &member.loc,
+        vec![
+            ("object", expression_to_estree(&member.object)),
+            ("property", expression_to_estree(&member.property)),
+            ("computed", Json::Bool(member.computed)),
+            ("optional", Json::Bool(false)),
+        ],
+    )
+}
+
+fn identifier_to_estree(identifier: &Identifier) -> Json {
+    node(
+        "Identifier",
+        &identifier.loc,
+        vec![("name", string(&identifier.name))],
+    )
+}
+
+fn numeric_literal_to_estree(literal: &NumericLiteral) -> Json {
+    self::literal(
+        &literal.loc,
+        Json::Number(literal.value),
+        &literal.raw,
+        vec![],
+    )
+}
+
+fn string_literal_to_estree(literal: &StringLiteral) -> Json {
+    self::literal(&literal.loc, string(&literal.value), &literal.raw, vec![])
+}
+
+fn literal(loc: &Loc, value: Json, raw: &str, extra: Vec<(&str, Json)>) -> Json {
+    let mut fields = vec![("value", value), ("raw", string(raw))];
+    fields.extend(extra);
+    node("Literal", loc, fields)
+}
+
+fn binary(left: &Expression, operator: &str, right: &Expression) -> Vec<(&'static str, Json)> {
+    vec![
+        ("left", expression_to_estree(left)),
+        ("operator", string(operator)),
+        ("right", expression_to_estree(right)),
+    ]
+}
+
+fn unary(kind: &str, operator: &str, prefix: bool, argument: &Expression, loc: &Loc) -> Json {
+    node(
+        kind,
+        loc,
+        vec![
+            ("operator", string(operator)),
+            ("prefix", Json::Bool(prefix)),
+            ("argument", expression_to_estree(argument)),
+        ],
+    )
+}
+
+fn node(kind: &str, loc: &Loc, fields: Vec<(&str, Json)>) -> Json {
+    let mut members = vec![
+        ("type".to_string(), string(kind)),
+        ("start".to_string(), offset(&loc.start)),
+        ("end".to_string(), offset(&loc.end)),
+        (
+            "loc".to_string(),
+            Json::Object(vec![
+                ("start".to_string(), position(&loc.start)),
+                ("end".to_string(), position(&loc.end)),
+            ]),
+        ),
+        (
+            "range".to_string(),
+            Json::Array(vec![offset(&loc.start), offset(&loc.end)]),
+        ),
+    ];
+    members.extend(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value)),
+    );
+    Json::Object(members)
+}
+
+fn position(position: &Position) -> Json {
+    Json::Object(vec![
+        ("line".to_string(), Json::Number(position.line as f64)),
+        (
+            "column".to_string(),
+            Json::Number(position.column.saturating_sub(1) as f64),
+        ),
+    ])
+}
+
+fn offset(position: &Position) -> Json {
+    Json::Number(position.index as f64)
+}
+
+#[cfg(test)]
+mod test_estree {
+    use super::*;
+    use crate::fixtures;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_fixtures() {
+        fixtures::check_snapshots("estree", to_json);
+    }
+
+    #[test]
+    fn test_ranges() -> Result<(), String> {
+        let program = Parser::new("a".to_string())?.parse()?;
+        assert!(!to_json(&program).contains("range"));
+        let options = EstreeOptions { ranges: true };
+        let json = program_to_estree_with(&program, &options).to_string_compact();
+        assert!(json.contains(r#""name":"a""#));
+        assert_eq!(json.matches(r#""range":[0,1]"#).count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tokens() -> Result<(), String> {
+        let source = "x = /a/g; // c";
+        let program = Parser::new(source.to_string())?.parse()?;
+        let json = program_to_estree_with_tokens(&program, source)?;
+        let Json::Object(members) = &json else {
+            panic!("expected an object");
+        };
+        let (key, tokens) = members.last().unwrap();
+        assert_eq!(key, "tokens");
+        let Json::Array(tokens) = tokens else {
+            panic!("expected an array");
+        };
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            tokens[2].to_string_compact(),
+            r#"{"type":"RegularExpression","value":"/a/g","regex":{"pattern":"a","flags":"g"},"range":[4,8],"loc":{"start":{"line":1,"column":4},"end":{"line":1,"column":8}}}"#
+        );
+        Ok(())
+    }
+}
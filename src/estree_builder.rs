@@ -0,0 +1,950 @@
+//! Builds the AST back from ESTree JSON, as written by [`crate::estree`] or
+//! by other tools. Errors start with the JSON path of the offending node,
+//! e.g. `$.body[0].expression.left: expect a pattern, find Literal`.
+//!
+//! Positions are optional: a node without `loc`, `start` and `end` gets an
+//! empty [`Loc`]. A literal without `raw` gets one derived from its value.
+//! Parentheses and trailing commas are not part of ESTree, so they only come
+//! back when the input has the `extra` Babel's estree plugin writes; a round
+//! trip through [`crate::estree`] drops them. Operators and identifier
+//! names are checked, since [`crate::codegen`] prints them as they are.
+
+use crate::codegen::quote;
+use crate::json::Json;
+use crate::lex::{Loc, Position};
+use crate::node::{
+    ArrowFunctionBody, AttachedComments, BlockStatement, CatchClause, Declaration, Directive,
+    Expression, Extra, ForInLeft, ForInit, Function, Identifier, MemberExpression, ModuleItem,
+    NumericLiteral, ObjectMember, ObjectPatternProperty, Pattern, Program, PropertyKey, SourceType,
+    Statement, StringLiteral, SwitchCase, TemplateElement, VariableDeclaration, VariableDeclarator,
+    VariableKind,
+};
+use crate::unicode::{is_identifier_part, is_identifier_start};
+
+const ASSIGNMENT_OPERATORS: &[&str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "**=", "<<=", ">>=", ">>>=", "|=", "^=", "&=", "||=", "&&=",
+    "??=",
+];
+
+const BINARY_OPERATORS: &[&str] = &[
+    "==",
+    "!=",
+    "===",
+    "!==",
+    "<",
+    "<=",
+    ">",
+    ">=",
+    "<<",
+    ">>",
+    ">>>",
+    "+",
+    "-",
+    "*",
+    "/",
+    "%",
+    "**",
+    "|",
+    "^",
+    "&",
+    "in",
+    "instanceof",
+];
+
+/// With `throw` from the throw expressions proposal.
+const UNARY_OPERATORS: &[&str] = &["-", "+", "!", "~", "typeof", "void", "delete", "throw"];
+
+pub fn from_json(input: &str) -> Result<Program, String> {
+    let json = Json::parse(input)?;
+    build_program(&Object::new(&json, "$".to_string())?)
+}
+
+/// A JSON object together with its path from the document root.
+struct Object<'a> {
+    members: &'a [(String, Json)],
+    path: String,
+}
+
+impl<'a> Object<'a> {
+    fn new(json: &'a Json, path: String) -> Result<Object<'a>, String> {
+        match json {
+            Json::Object(members) => Ok(Object { members, path }),
+            _ => Err(format!("{path}: expect an object")),
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{}: {message}", self.path)
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Json> {
+        self.members
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    fn field(&self, key: &str) -> Result<&'a Json, String> {
+        self.get(key)
+            .ok_or_else(|| self.error(&format!("missing field `{key}`")))
+    }
+
+    fn kind(&self) -> Result<&'a str, String> {
+        match self.field("type")? {
+            Json::String(kind) => Ok(kind),
+            _ => Err(self.error("`type` must be a string")),
+        }
+    }
+
+    fn expect_kind(&self, kind: &str) -> Result<(), String> {
+        let found = self.kind()?;
+        if found != kind {
+            return Err(self.error(&format!("expect {kind}, find {found}")));
+        }
+        Ok(())
+    }
+
+    fn object(&self, key: &str) -> Result<Object<'a>, String> {
+        Object::new(self.field(key)?, format!("{}.{key}", self.path))
+    }
+
+    fn optional(&self, key: &str) -> Result<Option<Object<'a>>, String> {
+        match self.field(key)? {
+            Json::Null => Ok(None),
+            _ => self.object(key).map(Some),
+        }
+    }
+
+    /// An array of nodes where `null` stands for a hole.
+    fn list(&self, key: &str) -> Result<Vec<Option<Object<'a>>>, String> {
+        let Json::Array(items) = self.field(key)? else {
+            return Err(self.error(&format!("`{key}` must be an array")));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match item {
+                Json::Null => Ok(None),
+                _ => Object::new(item, format!("{}.{key}[{i}]", self.path)).map(Some),
+            })
+            .collect()
+    }
+
+    fn objects(&self, key: &str) -> Result<Vec<Object<'a>>, String> {
+        self.list(key)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.ok_or_else(|| format!("{}.{key}[{i}]: expect an object", self.path))
+            })
+            .collect()
+    }
+
+    fn string(&self, key: &str) -> Result<String, String> {
+        match self.field(key)? {
+            Json::String(s) => Ok(s.to_string()),
+            _ => Err(self.error(&format!("`{key}` must be a string"))),
+        }
+    }
+
+    /// `operator`, which must be one of `allowed`: codegen prints it as is.
+    fn operator(&self, allowed: &[&str]) -> Result<String, String> {
+        let operator = self.string("operator")?;
+        if !allowed.contains(&operator.as_str()) {
+            return Err(self.error(&format!("invalid operator {operator:?}")));
+        }
+        Ok(operator)
+    }
+
+    fn bool(&self, key: &str) -> Result<bool, String> {
+        match self.field(key)? {
+            Json::Bool(b) => Ok(*b),
+            _ => Err(self.error(&format!("`{key}` must be a boolean"))),
+        }
+    }
+
+    fn number(&self, key: &str) -> Result<f64, String> {
+        match self.field(key)? {
+            Json::Number(n) => Ok(*n),
+            _ => Err(self.error(&format!("`{key}` must be a number"))),
+        }
+    }
+
+    fn index(&self, key: &str) -> Result<usize, String> {
+        let n = self.number(key)?;
+        if n < 0.0 || n.fract() != 0.0 {
+            return Err(self.error(&format!("`{key}` must be a non-negative integer")));
+        }
+        Ok(n as usize)
+    }
+
+    fn loc(&self) -> Result<Loc, String> {
+        let loc = match self.get("loc") {
+            None | Some(Json::Null) => None,
+            Some(_) => Some(self.object("loc")?),
+        };
+        let position = |key: &str| -> Result<Position, String> {
+            let index = match self.get(key) {
+                None | Some(Json::Null) => 0,
+                Some(_) => self.index(key)?,
+            };
+            match &loc {
+                Some(loc) => position(&loc.object(key)?, index),
+                None => Ok(Position {
+                    index,
+                    ..Position::default()
+                }),
+            }
+        };
+        Ok(Loc {
+            start: position("start")?,
+            end: position("end")?,
+        })
+    }
+
+    fn extra(&self) -> Result<Option<Object<'a>>, String> {
+        match self.get("extra") {
+            None | Some(Json::Null) => Ok(None),
+            Some(_) => self.object("extra").map(Some),
+        }
+    }
+
+    fn parenthesized(&self) -> Result<Extra, String> {
+        match self.extra()? {
+            Some(extra) if extra.get("parenthesized").is_some() => Ok(Extra::Parenthesized {
+                paren_start: extra.index("parenStart")?,
+            }),
+            _ => Ok(Extra::None),
+        }
+    }
+
+    fn trailing_comma(&self) -> Result<bool, String> {
+        match self.extra()? {
+            // Babel writes the offset of the comma rather than `true`.
+            Some(extra) => Ok(!matches!(
+                extra.get("trailingComma"),
+                None | Some(Json::Null | Json::Bool(false))
+            )),
+            None => Ok(false),
+        }
+    }
+}
+
+fn position(position: &Object, index: usize) -> Result<Position, String> {
+    let line = position.index("line")?;
+    let column = position.index("column")?;
+    Ok(Position {
+        line,
+        column: column + 1,
+        index,
+    })
+}
+
+fn build_program(object: &Object) -> Result<Program, String> {
+    object.expect_kind("Program")?;
+    let (directives, body) = build_body(object)?;
+    // ESTree makes a program without `sourceType` a script.
+    let source_type = match object.get("sourceType") {
+        None => SourceType::Script,
+        Some(_) => match object.string("sourceType")?.as_str() {
+            "script" => SourceType::Script,
+            "module" => SourceType::Module,
+            "commonjs" => SourceType::CommonJs,
+            other => return Err(object.error(&format!("unknown sourceType {other:?}"))),
+        },
+    };
+    Ok(Program {
+        directives,
+        body: body.into_iter().map(ModuleItem::Statement).collect(),
+        comments: vec![],
+        attached_comments: AttachedComments::new(),
+        source_type,
+        loc: object.loc()?,
+    })
+}
+
+fn build_statement(object: &Object) -> Result<Statement, String> {
+    let loc = object.loc()?;
+    Ok(match object.kind()? {
+        "EmptyStatement" => Statement::EmptyStatement { loc },
+        "BlockStatement" => Statement::BlockStatement(build_block(object)?),
+        "ExpressionStatement" => Statement::ExpressionStatement {
+            expression: Box::new(build_expression(&object.object("expression")?)?),
+            loc,
+        },
+        "FunctionDeclaration" => {
+            Statement::Declaration(Declaration::FunctionDeclaration(build_function(object)?))
+        }
+        "VariableDeclaration" => Statement::Declaration(Declaration::VariableDeclaration(
+            build_variable_declaration(object)?,
+        )),
+        "IfStatement" => Statement::IfStatement {
+            test: Box::new(build_expression(&object.object("test")?)?),
+            consequent: Box::new(build_statement(&object.object("consequent")?)?),
+            alternate: match object.optional("alternate")? {
+                Some(alternate) => Some(Box::new(build_statement(&alternate)?)),
+                None => None,
+            },
+            loc,
+        },
+        "ForStatement" => Statement::ForStatement {
+            init: match object.optional("init")? {
+                Some(init) if init.kind()? == "VariableDeclaration" => Some(
+                    ForInit::VariableDeclaration(build_variable_declaration(&init)?),
+                ),
+                Some(init) => Some(ForInit::Expression(Box::new(build_expression(&init)?))),
+                None => None,
+            },
+            test: build_optional_expression(object, "test")?,
+            update: build_optional_expression(object, "update")?,
+            body: Box::new(build_statement(&object.object("body")?)?),
+            loc,
+        },
+        "ForInStatement" => {
+            let left = object.object("left")?;
+            Statement::ForInStatement {
+                left: if left.kind()? == "VariableDeclaration" {
+                    ForInLeft::VariableDeclaration(build_variable_declaration(&left)?)
+                } else {
+                    ForInLeft::Pattern(build_pattern(&left)?)
+                },
+                right: Box::new(build_expression(&object.object("right")?)?),
+                body: Box::new(build_statement(&object.object("body")?)?),
+                loc,
+            }
+        }
+        "WhileStatement" => Statement::WhileStatement {
+            test: Box::new(build_expression(&object.object("test")?)?),
+            body: Box::new(build_statement(&object.object("body")?)?),
+            loc,
+        },
+        "DoWhileStatement" => Statement::DoWhileStatement {
+            body: Box::new(build_statement(&object.object("body")?)?),
+            test: Box::new(build_expression(&object.object("test")?)?),
+            loc,
+        },
+        "WithStatement" => Statement::WithStatement {
+            object: Box::new(build_expression(&object.object("object")?)?),
+            body: Box::new(build_statement(&object.object("body")?)?),
+            loc,
+        },
+        "TryStatement" => Statement::TryStatement {
+            block: build_block(&object.object("block")?)?,
+            handler: match object.optional("handler")? {
+                Some(handler) => Some(build_catch_clause(&handler)?),
+                None => None,
+            },
+            finalizer: match object.optional("finalizer")? {
+                Some(finalizer) => Some(build_block(&finalizer)?),
+                None => None,
+            },
+            loc,
+        },
+        "ReturnStatement" => Statement::ReturnStatement {
+            argument: build_optional_expression(object, "argument")?,
+            loc,
+        },
+        "SwitchStatement" => Statement::SwitchStatement {
+            discriminant: Box::new(build_expression(&object.object("discriminant")?)?),
+            cases: object
+                .objects("cases")?
+                .iter()
+                .map(build_switch_case)
+                .collect::<Result<_, _>>()?,
+            loc,
+        },
+        "LabeledStatement" => Statement::LabeledStatement {
+            label: build_identifier(&object.object("label")?)?,
+            body: Box::new(build_statement(&object.object("body")?)?),
+            loc,
+        },
+        "BreakStatement" => Statement::BreakStatement {
+            label: build_optional_identifier(object, "label")?,
+            loc,
+        },
+        "ContinueStatement" => Statement::ContinueStatement {
+            label: build_optional_identifier(object, "label")?,
+            loc,
+        },
+        "ThrowStatement" => Statement::ThrowStatement {
+            argument: Box::new(build_expression(&object.object("argument")?)?),
+            loc,
+        },
+        "Invalid" => Statement::Invalid { loc },
+        kind => return Err(object.error(&format!("expect a statement, find {kind}"))),
+    })
+}
+
+fn build_block(object: &Object) -> Result<BlockStatement, String> {
+    object.expect_kind("BlockStatement")?;
+    let (directives, body) = build_body(object)?;
+    Ok(BlockStatement {
+        directives,
+        body,
+        loc: object.loc()?,
+    })
+}
+
+/// Splits the directives, expression statements with a `directive`, off
+/// the start of the body.
+fn build_body(object: &Object) -> Result<(Vec<Directive>, Vec<Statement>), String> {
+    let mut directives = vec![];
+    let mut body = vec![];
+    for statement in object.objects("body")? {
+        if !body.is_empty() || statement.get("directive").is_none() {
+            body.push(build_statement(&statement)?);
+            continue;
+        }
+        let Expression::StringLiteral(value) = build_expression(&statement.object("expression")?)?
+        else {
+            return Err(statement.error("a directive must be a string literal"));
+        };
+        directives.push(Directive {
+            value,
+            loc: statement.loc()?,
+        });
+    }
+    Ok((directives, body))
+}
+
+fn build_catch_clause(object: &Object) -> Result<CatchClause, String> {
+    object.expect_kind("CatchClause")?;
+    Ok(CatchClause {
+        param: match object.optional("param")? {
+            Some(param) => Some(build_pattern(&param)?),
+            None => None,
+        },
+        body: build_block(&object.object("body")?)?,
+        loc: object.loc()?,
+    })
+}
+
+fn build_switch_case(object: &Object) -> Result<SwitchCase, String> {
+    object.expect_kind("SwitchCase")?;
+    Ok(SwitchCase {
+        test: build_optional_expression(object, "test")?,
+        consequent: object
+            .objects("consequent")?
+            .iter()
+            .map(build_statement)
+            .collect::<Result<_, _>>()?,
+        loc: object.loc()?,
+    })
+}
+
+fn build_variable_declaration(object: &Object) -> Result<VariableDeclaration, String> {
+    object.expect_kind("VariableDeclaration")?;
+    let kind = match object.string("kind")?.as_str() {
+        "var" => VariableKind::Var,
+        "let" => VariableKind::Let,
+        "const" => VariableKind::Const,
+        kind => return Err(object.error(&format!("unknown variable kind {kind}"))),
+    };
+    let declarations = object
+        .objects("declarations")?
+        .iter()
+        .map(|declarator| {
+            declarator.expect_kind("VariableDeclarator")?;
+            Ok(VariableDeclarator {
+                id: build_pattern(&declarator.object("id")?)?,
+                init: build_optional_expression(declarator, "init")?,
+                loc: declarator.loc()?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(VariableDeclaration {
+        kind,
+        declarations,
+        loc: object.loc()?,
+    })
+}
+
+fn build_function(object: &Object) -> Result<Function, String> {
+    for flag in ["generator", "async"] {
+        if object.bool(flag)? {
+            return Err(object.error(&format!("{flag} functions are not supported")));
+        }
+    }
+    Ok(Function {
+        id: build_optional_identifier(object, "id")?,
+        params: build_patterns(object, "params")?,
+        body: build_block(&object.object("body")?)?,
+        trailing_comma: object.trailing_comma()?,
+        extra: Extra::None,
+        loc: object.loc()?,
+    })
+}
+
+fn build_expression(object: &Object) -> Result<Expression, String> {
+    let loc = object.loc()?;
+    let mut expression = match object.kind()? {
+        "Identifier" => Expression::Identifier(build_identifier(object)?),
+        "ThisExpression" => Expression::ThisExpression {
+            extra: Extra::None,
+            loc,
+        },
+        "Literal" => build_literal(object)?,
+        "TemplateLiteral" => Expression::TemplateLiteral {
+            expressions: build_expressions(object, "expressions")?,
+            quasis: object
+                .objects("quasis")?
+                .iter()
+                .map(|quasi| {
+                    quasi.expect_kind("TemplateElement")?;
+                    let value = quasi.object("value")?;
+                    Ok(TemplateElement {
+                        value: value.string("raw")?,
+                        cooked: value.string("cooked")?,
+                        loc: quasi.loc()?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            extra: Extra::None,
+            loc,
+        },
+        "ArrayExpression" => Expression::ArrayExpression {
+            elements: object
+                .list("elements")?
+                .iter()
+                .map(|element| element.as_ref().map(build_expression).transpose())
+                .collect::<Result<_, _>>()?,
+            trailing_comma: object.trailing_comma()?,
+            extra: Extra::None,
+            loc,
+        },
+        "ObjectExpression" => Expression::ObjectExpression {
+            properties: object
+                .objects("properties")?
+                .iter()
+                .map(build_object_member)
+                .collect::<Result<_, _>>()?,
+            trailing_comma: object.trailing_comma()?,
+            extra: Extra::None,
+            loc,
+        },
+        "FunctionExpression" => Expression::FunctionExpression(build_function(object)?),
+        "ArrowFunctionExpression" => {
+            let body = object.object("body")?;
+            Expression::ArrowFunctionExpression {
+                params: build_patterns(object, "params")?,
+                body: if body.kind()? == "BlockStatement" {
+                    ArrowFunctionBody::BlockStatement(build_block(&body)?)
+                } else {
+                    ArrowFunctionBody::Expression(Box::new(build_expression(&body)?))
+                },
+                trailing_comma: object.trailing_comma()?,
+                extra: Extra::None,
+                loc,
+            }
+        }
+        "SequenceExpression" => Expression::SequenceExpression {
+            expressions: build_expressions(object, "expressions")?,
+            extra: Extra::None,
+            loc,
+        },
+        "AssignmentExpression" => Expression::AssignmentExpression {
+            left: Box::new(build_pattern(&object.object("left")?)?),
+            operator: object.operator(ASSIGNMENT_OPERATORS)?,
+            right: Box::new(build_expression(&object.object("right")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "BinaryExpression" => Expression::BinaryExpression {
+            left: Box::new(build_expression(&object.object("left")?)?),
+            operator: object.operator(BINARY_OPERATORS)?,
+            right: Box::new(build_expression(&object.object("right")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "LogicalExpression" => Expression::LogicalExpression {
+            left: Box::new(build_expression(&object.object("left")?)?),
+            operator: object.operator(&["||", "&&", "??"])?,
+            right: Box::new(build_expression(&object.object("right")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "UnaryExpression" => Expression::UnaryExpression {
+            operator: object.operator(UNARY_OPERATORS)?,
+            prefix: object.bool("prefix")?,
+            argument: Box::new(build_expression(&object.object("argument")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "UpdateExpression" => Expression::UpdateExpression {
+            operator: object.operator(&["++", "--"])?,
+            prefix: object.bool("prefix")?,
+            argument: Box::new(build_expression(&object.object("argument")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "AwaitExpression" => Expression::AwaitExpression {
+            argument: Box::new(build_expression(&object.object("argument")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "MemberExpression" => Expression::MemberExpression(build_member(object)?),
+        "ConditionalExpression" => Expression::ConditionalExpression {
+            test: Box::new(build_expression(&object.object("test")?)?),
+            consequent: Box::new(build_expression(&object.object("consequent")?)?),
+            alternate: Box::new(build_expression(&object.object("alternate")?)?),
+            extra: Extra::None,
+            loc,
+        },
+        "CallExpression" => Expression::CallExpression {
+            callee: Box::new(build_expression(&object.object("callee")?)?),
+            arguments: build_expressions(object, "arguments")?,
+            trailing_comma: object.trailing_comma()?,
+            extra: Extra::None,
+            loc,
+        },
+        "NewExpression" => Expression::NewExpression {
+            callee: Box::new(build_expression(&object.object("callee")?)?),
+            arguments: build_expressions(object, "arguments")?,
+            trailing_comma: object.trailing_comma()?,
+            extra: Extra::None,
+            loc,
+        },
+        "ParenthesizedExpression" => Expression::ParenthesizedExpression {
+            expression: Box::new(build_expression(&object.object("expression")?)?),
+            loc,
+        },
+        kind => return Err(object.error(&format!("expect an expression, find {kind}"))),
+    };
+    if let Some(extra) = expression.extra_mut() {
+        *extra = object.parenthesized()?;
+    }
+    Ok(expression)
+}
+
+fn build_expressions(object: &Object, key: &str) -> Result<Vec<Expression>, String> {
+    object.objects(key)?.iter().map(build_expression).collect()
+}
+
+fn build_optional_expression(
+    object: &Object,
+    key: &str,
+) -> Result<Option<Box<Expression>>, String> {
+    match object.optional(key)? {
+        Some(expression) => Ok(Some(Box::new(build_expression(&expression)?))),
+        None => Ok(None),
+    }
+}
+
+fn build_literal(object: &Object) -> Result<Expression, String> {
+    let loc = object.loc()?;
+    let raw = match object.get("raw") {
+        None | Some(Json::Null) => None,
+        Some(_) => Some(object.string("raw")?),
+    };
+    Ok(match object.field("value")? {
+        Json::Null if object.get("regex").is_some() => {
+            let regex = object.object("regex")?;
+            Expression::RegExpLiteral {
+                pattern: regex.string("pattern")?,
+                flags: regex.string("flags")?,
+                extra: Extra::None,
+                loc,
+            }
+        }
+        Json::Null if object.get("bigint").is_some() => {
+            let value = object.string("bigint")?;
+            Expression::BigIntLiteral {
+                raw: raw.unwrap_or_else(|| format!("{value}n")),
+                value,
+                extra: Extra::None,
+                loc,
+            }
+        }
+        Json::Null => Expression::NullLiteral {
+            extra: Extra::None,
+            loc,
+        },
+        Json::Bool(value) => Expression::BooleanLiteral {
+            value: *value,
+            extra: Extra::None,
+            loc,
+        },
+        Json::Number(value) => Expression::NumericLiteral(NumericLiteral {
+            value: *value,
+            raw: raw.unwrap_or_else(|| value.to_string()),
+            extra: Extra::None,
+            loc,
+        }),
+        Json::String(value) => Expression::StringLiteral(StringLiteral {
+            value: value.to_string(),
+            raw: raw.unwrap_or_else(|| quote(value)),
+            extra: Extra::None,
+            loc,
+        }),
+        _ => return Err(object.error("unsupported literal value")),
+    })
+}
+
+fn build_object_member(object: &Object) -> Result<ObjectMember, String> {
+    let (key, value) = build_property(object)?;
+    let loc = object.loc()?;
+    if object.bool("method")? {
+        value.expect_kind("FunctionExpression")?;
+        return Ok(ObjectMember::ObjectMethod {
+            key,
+            function: build_function(&value)?,
+            loc,
+        });
+    }
+    Ok(ObjectMember::ObjectProperty {
+        key,
+        value: Box::new(build_expression(&value)?),
+        shorthand: object.bool("shorthand")?,
+        loc,
+    })
+}
+
+/// Checks the parts of a `Property` the AST cannot represent and returns its
+/// key and value.
+fn build_property<'a>(object: &Object<'a>) -> Result<(PropertyKey, Object<'a>), String> {
+    object.expect_kind("Property")?;
+    if object.bool("computed")? {
+        return Err(object.error("computed keys are not supported"));
+    }
+    if object.string("kind")? != "init" {
+        return Err(object.error("getters and setters are not supported"));
+    }
+    let key = object.object("key")?;
+    let key = match key.kind()? {
+        "Identifier" => PropertyKey::Identifier(build_identifier(&key)?),
+        "Literal" => match build_literal(&key)? {
+            Expression::StringLiteral(literal) => PropertyKey::StringLiteral(literal),
+            Expression::NumericLiteral(literal) => PropertyKey::NumericLiteral(literal),
+            _ => return Err(key.error("expect a string or number key")),
+        },
+        kind => return Err(key.error(&format!("expect a property key, find {kind}"))),
+    };
+    Ok((key, object.object("value")?))
+}
+
+fn build_pattern(object: &Object) -> Result<Pattern, String> {
+    let loc = object.loc()?;
+    Ok(match object.kind()? {
+        "Identifier" => Pattern::Identifier(build_identifier(object)?),
+        "MemberExpression" => Pattern::MemberExpression(build_member(object)?),
+        "ObjectPattern" => Pattern::ObjectPattern {
+            properties: object
+                .objects("properties")?
+                .iter()
+                .map(|property| {
+                    let (key, value) = build_property(property)?;
+                    Ok(ObjectPatternProperty {
+                        key,
+                        value: build_pattern(&value)?,
+                        shorthand: property.bool("shorthand")?,
+                        loc: property.loc()?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            trailing_comma: object.trailing_comma()?,
+            loc,
+        },
+        "ArrayPattern" => Pattern::ArrayPattern {
+            elements: object
+                .list("elements")?
+                .iter()
+                .map(|element| element.as_ref().map(build_pattern).transpose())
+                .collect::<Result<_, _>>()?,
+            trailing_comma: object.trailing_comma()?,
+            loc,
+        },
+        "AssignmentPattern" => Pattern::AssignmentPattern {
+            left: Box::new(build_pattern(&object.object("left")?)?),
+            right: Box::new(build_expression(&object.object("right")?)?),
+            loc,
+        },
+        kind => return Err(object.error(&format!("expect a pattern, find {kind}"))),
+    })
+}
+
+fn build_patterns(object: &Object, key: &str) -> Result<Vec<Pattern>, String> {
+    object.objects(key)?.iter().map(build_pattern).collect()
+}
+
+fn build_member(object: &Object) -> Result<MemberExpression, String> {
+    Ok(MemberExpression {
+        object: Box::new(build_expression(&object.object("object")?)?),
+        property: Box::new(build_expression(&object.object("property")?)?),
+        computed: object.bool("computed")?,
+        extra: object.parenthesized()?,
+        loc: object.loc()?,
+    })
+}
+
+fn build_identifier(object: &Object) -> Result<Identifier, String> {
+    object.expect_kind("Identifier")?;
+    let name = object.string("name")?;
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(is_identifier_start) || !chars.all(is_identifier_part) {
+        return Err(object.error(&format!("invalid identifier name {name:?}")));
+    }
+    Ok(Identifier {
+        name,
+        extra: object.parenthesized()?,
+        loc: object.loc()?,
+    })
+}
+
+fn build_optional_identifier(object: &Object, key: &str) -> Result<Option<Identifier>, String> {
+    object
+        .optional(key)?
+        .as_ref()
+        .map(build_identifier)
+        .transpose()
+}
+
+#[cfg(test)]
+mod test_estree_builder {
+    use super::*;
+    use crate::codegen::print;
+    use crate::estree::to_json;
+    use crate::fixtures;
+    use crate::parser::Parser;
+
+    /// Compares as JSON, since ESTree has no room for parentheses and
+    /// trailing commas.
+    fn round_trip(source: &str) -> Result<(), String> {
+        let program = Parser::new(source.to_string())?.parse()?;
+        let json = to_json(&program);
+        assert_eq!(to_json(&from_json(&json)?), json, "{source}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip_fixtures() -> Result<(), String> {
+        for (_, source) in fixtures::sources("estree") {
+            round_trip(&source)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip_extra() -> Result<(), String> {
+        round_trip("x = (a + b) * (\n (c, d));\nf(a,); new A(b,);")?;
+        round_trip("function g([a, , b,], {d,},) {} h = function (i,) {}; j = (k,) => [k,];")?;
+        let program = from_json(&to_json(&Parser::new("f((a),)".to_string())?.parse()?))?;
+        let [ModuleItem::Statement(Statement::ExpressionStatement { expression, .. })] =
+            &program.body[..]
+        else {
+            panic!("expect an expression statement");
+        };
+        let Expression::CallExpression {
+            arguments,
+            trailing_comma,
+            ..
+        } = expression.as_ref()
+        else {
+            panic!("expect a call");
+        };
+        assert_eq!(
+            (arguments[0].extra(), *trailing_comma),
+            (&Extra::None, false)
+        );
+
+        // As Babel's estree plugin writes them.
+        let program = from_json(
+            r#"{"type": "Program", "sourceType": "script", "body": [
+                {"type": "ExpressionStatement", "expression": {
+                    "type": "ArrayExpression", "extra": {"trailingComma": 3}, "elements": [
+                        {"type": "Identifier", "name": "a",
+                         "extra": {"parenthesized": true, "parenStart": 1}}
+                    ]}}
+            ]}"#,
+        )?;
+        let [ModuleItem::Statement(Statement::ExpressionStatement { expression, .. })] =
+            &program.body[..]
+        else {
+            panic!("expect an expression statement");
+        };
+        let Expression::ArrayExpression {
+            elements,
+            trailing_comma,
+            ..
+        } = expression.as_ref()
+        else {
+            panic!("expect an array");
+        };
+        assert_eq!(
+            (elements[0].as_ref().unwrap().extra(), *trailing_comma),
+            (&Extra::Parenthesized { paren_start: 1 }, true)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_without_positions() -> Result<(), String> {
+        let program = from_json(
+            r#"{"type": "Program", "sourceType": "script", "body": [
+                {"type": "ExpressionStatement", "expression": {
+                    "type": "BinaryExpression", "operator": "+",
+                    "left": {"type": "Literal", "value": "a\"b"},
+                    "right": {"type": "Literal", "value": 1.5, "start": 7, "end": 10}
+                }},
+                {"type": "ExpressionStatement", "expression": {
+                    "type": "Literal", "value": null, "bigint": "10"
+                }}
+            ]}"#,
+        )?;
+        assert_eq!(program.loc, Loc::default());
+        assert_eq!(print(&program), "'a\"b' + 1.5;\n10n;\n");
+        let [
+            ModuleItem::Statement(Statement::ExpressionStatement { expression, .. }),
+            _,
+        ] = &program.body[..]
+        else {
+            panic!("expect two expression statements");
+        };
+        let Expression::BinaryExpression { right, .. } = expression.as_ref() else {
+            panic!("expect a binary expression");
+        };
+        assert_eq!((right.loc().start.index, right.loc().end.index), (7, 10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let program = Parser::new("a = 1".to_string()).unwrap().parse().unwrap();
+        let json = to_json(&program).replacen("\"Identifier\"", "\"Literal\"", 1);
+        assert_eq!(
+            from_json(&json),
+            Err("$.body[0].expression.left: expect a pattern, find Literal".to_string())
+        );
+        let json = to_json(&program).replacen("\"Literal\"", "\"Spread\"", 1);
+        assert_eq!(
+            from_json(&json),
+            Err("$.body[0].expression.right: expect an expression, find Spread".to_string())
+        );
+        assert_eq!(
+            from_json("{\"type\": \"Program\", \"body\": [1]}"),
+            Err("$.body[0]: expect an object".to_string())
+        );
+        assert!(from_json("{\"type\": \"Program\"").is_err());
+    }
+
+    #[test]
+    fn test_invalid_values() -> Result<(), String> {
+        let program = Parser::new("a = b + 1".to_string())?.parse()?;
+        let json = to_json(&program);
+        assert_eq!(
+            from_json(&json.replacen("\"+\"", "\"**&\"", 1)),
+            Err("$.body[0].expression.right: invalid operator \"**&\"".to_string())
+        );
+        assert_eq!(
+            from_json(&json.replacen("\"b\"", "\"a b\"", 1)),
+            Err("$.body[0].expression.right.left: invalid identifier name \"a b\"".to_string())
+        );
+        // A program without `sourceType` is a script.
+        let program = from_json("{\"type\": \"Program\", \"body\": []}")?;
+        assert_eq!(program.source_type, SourceType::Script);
+        assert_eq!(
+            from_json("{\"type\": \"Program\", \"body\": [], \"sourceType\": \"x\"}"),
+            Err("$: unknown sourceType \"x\"".to_string())
+        );
+        Ok(())
+    }
+}
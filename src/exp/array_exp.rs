@@ -1,29 +1,45 @@
 use crate::express::{expect, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::ArrayExpression;
+use crate::node::Expression::ArrayExpression;
+use crate::node::{Expression, Extra};
 use crate::parser::Parser;
 
-pub fn build_array(parser: &mut Parser) -> Result<Box<Node>, String> {
+pub fn build_array(parser: &mut Parser) -> Result<Box<Expression>, String> {
     let mut elements = vec![];
+    let mut trailing_comma = false;
+    let start = parser.start_position();
 
     expect(parser, "[")?;
     loop {
         if is_ctrl_word(&parser.current, "]") {
             break;
         } else if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
+            elements.push(None);
             parser.next()?;
             continue;
         }
+        parser.maybe_pattern = true;
         let item = parse_expression(parser, 2)?;
-        elements.push(*item);
+        elements.push(Some(*item));
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, "]");
+        } else {
+            break;
+        }
     }
     expect(parser, "]")?;
-    ok_box(ArrayExpression { elements })
+    ok_box(ArrayExpression {
+        elements,
+        trailing_comma,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    })
 }
 
 #[cfg(test)]
 mod test_array {
+    use crate::express::parse_expression;
+    use crate::node::Expression::ArrayExpression;
     use crate::parser::Parser;
     use crate::token::Token;
 
@@ -50,4 +66,37 @@ mod test_array {
         println!("{ast:#?}");
         assert_eq!(parser.current, Token::EOF)
     }
+
+    #[test]
+    fn test_holes() -> Result<(), String> {
+        for (input, len, holes, trailing) in [
+            ("[1,,3]", 3, 1, false),
+            ("[,]", 1, 1, false),
+            ("[1,2,]", 2, 0, true),
+            ("[1,,]", 2, 1, false),
+            ("[,,]", 2, 2, false),
+        ] {
+            let mut parser = Parser::new(input.to_string())?;
+            let ast = parse_expression(&mut parser, 0)?;
+            if let ArrayExpression {
+                elements,
+                trailing_comma,
+                ..
+            } = &*ast
+            {
+                assert_eq!(elements.len(), len, "{input}");
+                assert_eq!(elements.iter().filter(|e| e.is_none()).count(), holes);
+                assert_eq!(*trailing_comma, trailing, "{input}");
+            } else {
+                panic!("expect array");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_comma() {
+        let mut parser = Parser::new("[1 2]".to_string()).unwrap();
+        assert!(parser.parse().is_err());
+    }
 }
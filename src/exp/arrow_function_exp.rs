@@ -1,235 +1,318 @@
 use crate::express::{expect, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node::{
-    ArrayExpression, ArrowFunctionExpression, AssignmentExpression, AssignmentPattern, Identity,
-    NumericLiteral, ObjectExpression, ObjectProperty, SequenceExpression, StringLiteral,
+use crate::lex::Loc;
+use crate::node::Expression::{
+    ArrayExpression, ArrowFunctionExpression, AssignmentExpression, ObjectExpression,
+    SequenceExpression,
 };
-use crate::node::{Extra, Node};
-use crate::parser::{IsArrowFunction, Parser};
-use std::cmp::PartialEq;
-use crate::token::Token;
+use crate::node::Pattern::{ArrayPattern, AssignmentPattern, ObjectPattern};
+use crate::node::{
+    ArrowFunctionBody, Expression, Extra, ObjectMember, ObjectPatternProperty, Pattern,
+};
+use crate::parser::{EcmaVersion, Parser};
 
-pub fn build_possible_arrow_function(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let mut params = vec![];
-    let body: Box<Node>;
+pub fn build_possible_arrow_function(parser: &mut Parser) -> Result<Box<Expression>, String> {
+    let mut items = vec![];
+    let mut trailing_comma = false;
+    let start = parser.start_position();
 
-    parser.regex_allowed = true;
     expect(parser, "(")?;
-    parser.is_arrow_function = IsArrowFunction::Maybe;
     loop {
         if is_ctrl_word(&parser.current, ")") {
             break;
-        } else if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
+        }
+        parser.maybe_pattern = true;
+        items.push(*parse_expression(parser, 2)?);
+        if is_ctrl_word(&parser.current, ",") {
             parser.next()?;
-            continue;
-        } else if is_ctrl_word(&parser.current, "{") {
-            params.push(*build_possible_object(parser)?);
-        } else if is_ctrl_word(&parser.current, "(") {
-            parser.is_arrow_function = IsArrowFunction::Impossible;
-            params.push(*parse_expression(parser, 0)?);
-        } else if is_ctrl_word(&parser.current, "[") {
-            params.push(*build_possible_array(parser)?);
+            trailing_comma = is_ctrl_word(&parser.current, ")");
         } else {
-            let exp = *parse_expression(parser, 2)?;
-            if let Identity { .. } = &exp {
-            } else if let AssignmentExpression { operator, .. } = &exp {
-                if operator != "=" {
-                    parser.is_arrow_function = IsArrowFunction::Impossible;
-                }
-            } else {
-                parser.is_arrow_function = IsArrowFunction::Impossible;
-            }
-            params.push(exp);
+            break;
         }
     }
-
     expect(parser, ")")?;
+
     if !is_ctrl_word(&parser.current, "=>") {
-        return if parser.is_arrow_function == IsArrowFunction::Must {
-            Err("syntax error".to_string())
-        } else {
-            if params.len() == 0 {
-                Err("syntax error, ()".to_string())
-            } else if params.len() == 1 {
-                ok_box(params.remove(0))
-            } else {
-                ok_box(SequenceExpression {
-                    expressions: params,
-                    extra: Extra::Parenthesized,
-                })
+        if items.is_empty() {
+            return Err("syntax error, ()".to_string());
+        }
+        if trailing_comma {
+            return Err("syntax error, trailing comma in parenthesized expression".to_string());
+        }
+        if items.iter().any(has_cover_init) {
+            return Err("Invalid shorthand property initializer".to_string());
+        }
+        if parser.options.preserve_parens {
+            let expression = match items.len() {
+                1 => items.remove(0),
+                _ => {
+                    let loc = Loc {
+                        start: items[0].loc().start.clone(),
+                        end: items[items.len() - 1].loc().end.clone(),
+                    };
+                    SequenceExpression {
+                        expressions: items,
+                        extra: Extra::None,
+                        loc,
+                    }
+                }
+            };
+            return ok_box(Expression::ParenthesizedExpression {
+                expression: Box::new(expression),
+                loc: parser.loc_from(start),
+            });
+        }
+        if items.len() == 1 {
+            let mut item = items.remove(0);
+            if let Some(extra) = item.extra_mut() {
+                *extra = Extra::Parenthesized {
+                    paren_start: start.index,
+                };
             }
+            return ok_box(item);
+        }
+        let loc = Loc {
+            start: items[0].loc().start.clone(),
+            end: items[items.len() - 1].loc().end.clone(),
         };
+        return ok_box(SequenceExpression {
+            expressions: items,
+            extra: Extra::Parenthesized {
+                paren_start: start.index,
+            },
+            loc,
+        });
     }
-    if parser.is_arrow_function == IsArrowFunction::Impossible {
-        return Err("syntax error".to_string());
+
+    parser.require(EcmaVersion::Es2015, "Arrow function")?;
+    if trailing_comma {
+        parser.require(EcmaVersion::Es2017, "Trailing comma after parameters")?;
     }
-    parser.regex_allowed = true;
+    let mut params = vec![];
+    for item in items {
+        params.push(to_pattern(item, true)?);
+    }
+    parser.check_arrow_params(&params)?;
     parser.next()?;
-    if is_ctrl_word(&parser.current, "{") {
-        body = Parser::parse_block(parser)?
+    let body = if is_ctrl_word(&parser.current, "{") {
+        ArrowFunctionBody::BlockStatement(Parser::parse_function_body(parser, None, &params)?)
     } else {
-        body = parse_expression(parser, 2)?
-    }
+        if parser.strict {
+            parser.check_params(None, &params)?;
+        }
+        ArrowFunctionBody::Expression(Parser::parse_arrow_body(parser)?)
+    };
 
-    ok_box(ArrowFunctionExpression { params, body })
+    ok_box(ArrowFunctionExpression {
+        params,
+        body,
+        trailing_comma,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    })
 }
 
-fn build_possible_object(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let mut properties = vec![];
-
-    expect(parser, "{")?;
-    loop {
-        if is_ctrl_word(&parser.current, "}") {
-            break;
+/// Reinterprets an expression parsed through the cover grammar as a pattern.
+/// Binding patterns (arrow params) only accept identifiers as targets, while
+/// assignment patterns also accept member expressions.
+pub fn to_pattern(node: Expression, binding: bool) -> Result<Pattern, String> {
+    match node {
+        // Same targets as the `ParenthesizedExpression` arm below.
+        node if node.is_parenthesized()
+            && (binding
+                || !matches!(
+                    node,
+                    Expression::Identifier(_) | Expression::MemberExpression(_)
+                )) =>
+        {
+            Err("Invalid destructuring target".to_string())
         }
-        if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
-            parser.next()?;
-            continue;
+        Expression::Identifier(identifier) => Ok(Pattern::Identifier(identifier)),
+        Expression::MemberExpression(_) if binding => {
+            Err("Binding member expression is not allowed in params".to_string())
         }
-        let key: Node;
-
-        match &parser.current {
-            Token::Variable(s) => {
-                key = StringLiteral {
-                    value: s.to_string(),
-                };
-            }
-            Token::String(s) => {
-                parser.is_arrow_function = IsArrowFunction::Impossible;
-                key = StringLiteral {
-                    value: s.to_string(),
-                };
-            }
-            Token::Digit(s) => {
-                parser.is_arrow_function = IsArrowFunction::Impossible;
-                key = NumericLiteral {
-                    value: s.to_string(),
-                };
-            }
-            _ => {
-                return Err("object property type error".to_string());
+        Expression::MemberExpression(member) => Ok(Pattern::MemberExpression(member)),
+        // Patterns have no parentheses to keep; `(a) = 1` assigns to `a`.
+        Expression::ParenthesizedExpression { expression, .. }
+            if !binding
+                && matches!(
+                    *expression,
+                    Expression::Identifier(_)
+                        | Expression::MemberExpression(_)
+                        | Expression::ParenthesizedExpression { .. }
+                ) =>
+        {
+            to_pattern(*expression, false)
+        }
+        ObjectExpression {
+            properties,
+            trailing_comma,
+            loc,
+            ..
+        } => {
+            let mut list = vec![];
+            for property in properties {
+                if let ObjectMember::ObjectProperty {
+                    key,
+                    value,
+                    shorthand,
+                    loc,
+                } = property
+                {
+                    list.push(ObjectPatternProperty {
+                        key,
+                        value: to_pattern(*value, binding)?,
+                        shorthand,
+                        loc,
+                    });
+                } else {
+                    return Err("Invalid destructuring target, object method".to_string());
+                }
             }
+            Ok(ObjectPattern {
+                properties: list,
+                trailing_comma,
+                loc,
+            })
         }
-        parser.next()?;
-        if is_ctrl_word(&parser.current, ":") {
-            parser.regex_allowed = true;
-            parser.next()?;
-            if is_ctrl_word(&parser.current, "{") {
-                properties.push(ObjectProperty {
-                    key: Box::new(key),
-                    value: build_possible_object(parser)?,
-                });
-            } else if is_ctrl_word(&parser.current, "[") {
-                properties.push(ObjectProperty {
-                    key: Box::new(key),
-                    value: build_possible_array(parser)?,
-                });
-            } else {
-                parser.is_arrow_function = IsArrowFunction::Impossible;
-                properties.push(ObjectProperty {
-                    key: Box::new(key),
-                    value: parse_expression(parser, 2)?,
+        ArrayExpression {
+            elements,
+            trailing_comma,
+            loc,
+            ..
+        } => {
+            let mut list = vec![];
+            for element in elements {
+                list.push(match element {
+                    Some(element) => Some(to_pattern(element, binding)?),
+                    None => None,
                 });
             }
-        } else if is_ctrl_word(&parser.current, "=") {
-            parser.is_arrow_function = IsArrowFunction::Must;
-            parser.regex_allowed = true;
-            parser.next()?;
-            let default_value = parse_expression(parser, 2)?;
-            properties.push(AssignmentPattern {
-                left: Box::new(key),
-                right: default_value,
+            Ok(ArrayPattern {
+                elements: list,
+                trailing_comma,
+                loc,
             })
         }
+        AssignmentExpression {
+            left,
+            operator,
+            right,
+            loc,
+            ..
+        } if operator == "=" => {
+            if binding {
+                check_binding(&left)?;
+            }
+            Ok(AssignmentPattern { left, right, loc })
+        }
+        _ => Err("Invalid destructuring target".to_string()),
     }
-
-    expect(parser, "}")?;
-    Ok(Box::new(ObjectExpression { properties }))
 }
 
-fn build_possible_array(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let mut elements: Vec<Node> = vec![];
-    parser.regex_allowed = true;
-    parser.next()?;
-    loop {
-        if is_ctrl_word(&parser.current, "]") {
-            break;
-        } else if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
-            parser.next()?;
-            continue;
-        } else if is_ctrl_word(&parser.current, "{") {
-            elements.push(*build_possible_object(parser)?)
-        } else if is_ctrl_word(&parser.current, "[") {
-            elements.push(*build_possible_array(parser)?)
-        } else {
-            elements.push(*parse_expression(parser, 2)?)
+/// The left side of an `=` was already turned into an assignment pattern,
+/// which may hold member expressions a binding pattern does not allow.
+fn check_binding(pattern: &Pattern) -> Result<(), String> {
+    match pattern {
+        Pattern::Identifier(_) => Ok(()),
+        Pattern::MemberExpression(_) => {
+            Err("Binding member expression is not allowed in params".to_string())
         }
+        ObjectPattern { properties, .. } => properties
+            .iter()
+            .try_for_each(|property| check_binding(&property.value)),
+        ArrayPattern { elements, .. } => elements.iter().flatten().try_for_each(check_binding),
+        AssignmentPattern { left, .. } => check_binding(left),
     }
-    expect(parser, "]")?;
-    Ok(Box::new(ArrayExpression { elements }))
 }
 
-fn convert_params(properties: Vec<Node>) {
-    let mut result = vec![];
-    for property in properties {
-        if let ObjectProperty { key, value } = property {
-            result.push(ObjectProperty { key, value })
-        }
+/// Whether an object or array literal still holds a `{a = 1}` shorthand
+/// initializer, which is only valid once the literal becomes a pattern.
+pub fn has_cover_init(node: &Expression) -> bool {
+    match node {
+        ObjectExpression { properties, .. } => properties.iter().any(|property| match property {
+            ObjectMember::ObjectProperty {
+                value, shorthand, ..
+            } => {
+                (*shorthand && matches!(**value, AssignmentExpression { .. }))
+                    || has_cover_init(value)
+            }
+            _ => false,
+        }),
+        ArrayExpression { elements, .. } => elements.iter().flatten().any(has_cover_init),
+        _ => false,
     }
 }
 
 #[cfg(test)]
 mod test_arrow_function {
-    use crate::exp::arrow_function_exp::{
-        IsArrowFunction, build_possible_array, build_possible_arrow_function, build_possible_object,
-    };
+    use crate::exp::declaration_exp::build_let;
+    use crate::express::parse_expression;
+    use crate::node::Expression::{ArrowFunctionExpression, AssignmentExpression};
+    use crate::node::Pattern::{ArrayPattern, AssignmentPattern, ObjectPattern};
+    use crate::node::{Expression, Extra, Identifier, Pattern, VariableDeclarator};
     use crate::parser::Parser;
     use crate::token::Token;
 
     #[test]
     fn arrow_function_param() -> Result<(), String> {
-        let mut parser = Parser::new("{a=1}".to_string())?;
-        let r = build_possible_object(&mut parser)?;
-        assert_eq!(parser.is_arrow_function, IsArrowFunction::Must);
-        println!("{:#?}", r);
+        let mut parser = Parser::new("({a=1}) => a".to_string())?;
+        let ast = parse_expression(&mut parser, 0)?;
+        if let ArrowFunctionExpression { params, .. } = &*ast {
+            assert!(matches!(&params[0], ObjectPattern { properties, .. }
+                if matches!(properties[0].value, AssignmentPattern { .. })));
+        } else {
+            panic!("expect arrow function");
+        }
         Ok(())
     }
 
     #[test]
     fn object() -> Result<(), String> {
-        let mut parser = Parser::new("{a: {b: {c:1}}}".to_string())?;
-        let r = build_possible_object(&mut parser)?;
-        assert_eq!(parser.is_arrow_function, IsArrowFunction::Impossible);
-        println!("{:#?}", r);
+        let mut parser = Parser::new("({a: {b: {c:1}}})".to_string())?;
+        parser.parse()?;
+        assert_eq!(parser.current, Token::EOF);
         Ok(())
     }
 
     #[test]
     fn arrow_function_or_object() -> Result<(), String> {
-        let mut parser = Parser::new("{a: {b: {c=1}}}".to_string())?;
-        let r = build_possible_object(&mut parser)?;
-        assert_eq!(parser.is_arrow_function, IsArrowFunction::Must);
-        println!("{:#?}", r);
+        let mut parser = Parser::new("({a: {b: {c=1}}})".to_string())?;
+        assert_eq!(
+            parser.parse().map_err(String::from),
+            Err("Invalid shorthand property initializer".to_string())
+        );
+        let mut parser = Parser::new("({a: {b: {c=1}}}) => c".to_string())?;
+        parser.parse()?;
+        assert_eq!(parser.current, Token::EOF);
         Ok(())
     }
 
     #[test]
     fn arrow_function_param_array() -> Result<(), String> {
-        let mut parser = Parser::new("([{},a,b])".to_string())?;
-        let r = build_possible_arrow_function(&mut parser)?;
-        assert_eq!(parser.is_arrow_function, IsArrowFunction::Maybe);
-        println!("{:#?}", r);
+        let mut parser = Parser::new("([{},a,b]) => a".to_string())?;
+        let ast = parse_expression(&mut parser, 0)?;
+        if let ArrowFunctionExpression { params, .. } = &*ast {
+            assert!(matches!(&params[0], ArrayPattern { elements, .. } if elements.len() == 3));
+        } else {
+            panic!("expect arrow function");
+        }
         Ok(())
     }
 
     #[test]
-    fn arrow_function_param_array_object() -> Result<(), String> {
-        let mut parser = Parser::new("[{x: 1},a,b]".to_string())?;
-        let r = build_possible_array(&mut parser)?;
-        assert_eq!(parser.is_arrow_function, IsArrowFunction::Impossible);
-        println!("{:#?}", r);
+    fn arrow_function_nested_default() -> Result<(), String> {
+        let mut parser = Parser::new("(a, {b: [c = 1]}) => 0".to_string())?;
+        let ast = parse_expression(&mut parser, 0)?;
+        if let ArrowFunctionExpression { params, .. } = &*ast {
+            assert!(
+                matches!(&params[0], Pattern::Identifier(Identifier { name, .. }) if name == "a")
+            );
+            assert!(matches!(&params[1], ObjectPattern { properties, .. }
+                if matches!(&properties[0].value, ArrayPattern { elements, .. }
+                    if matches!(elements[0], Some(AssignmentPattern { .. })))));
+        } else {
+            panic!("expect arrow function");
+        }
         Ok(())
     }
 
@@ -278,4 +361,88 @@ mod test_arrow_function {
         let ast = parser.parse();
         assert!(ast.is_err());
     }
+
+    #[test]
+    fn test_arrow_function_member_target() {
+        let mut parser = Parser::new("({a: b.c}) => 0".to_string()).unwrap();
+        assert_eq!(
+            parser.parse().map_err(String::from),
+            Err("Binding member expression is not allowed in params".to_string())
+        );
+        let mut parser = Parser::new("({a: b.c = 1}) => 0".to_string()).unwrap();
+        assert_eq!(
+            parser.parse().map_err(String::from),
+            Err("Binding member expression is not allowed in params".to_string())
+        );
+        let mut parser = Parser::new("(a += 1) => 0".to_string()).unwrap();
+        assert_eq!(
+            parser.parse().map_err(String::from),
+            Err("Invalid destructuring target".to_string())
+        );
+    }
+
+    #[test]
+    fn test_destructuring_assignment() -> Result<(), String> {
+        let mut parser = Parser::new("[a.b, {c = 1, d: e[0]}] = f".to_string())?;
+        parser.parse()?;
+        assert_eq!(parser.current, Token::EOF);
+        let mut parser = Parser::new("let x = ({a = 1} = b)".to_string())?;
+        let declaration = build_let(&mut parser)?;
+        assert!(matches!(
+            &declaration.declarations[0],
+            VariableDeclarator { init: Some(_), .. }
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cover_init_errors() {
+        for input in [
+            "a = {b = 1}",
+            "f({a = 1})",
+            "[{a = 1}]",
+            "({a = 1}).b",
+            "({a=1}) + 1",
+        ] {
+            let mut parser = Parser::new(input.to_string()).unwrap();
+            assert_eq!(
+                parser.parse().map_err(String::from),
+                Err("Invalid shorthand property initializer".to_string()),
+                "{input}"
+            );
+        }
+    }
+    #[test]
+    fn test_parenthesized() -> Result<(), String> {
+        for (input, paren_start) in [("(a).b", 0), ("x = (function () {})", 4), ("((x))", 0)] {
+            let mut parser = Parser::new(input.to_string())?;
+            let mut ast = parse_expression(&mut parser, 0)?;
+            if let AssignmentExpression { right, .. } = *ast {
+                ast = right;
+            }
+            if let Expression::MemberExpression(member) = &*ast {
+                assert_eq!(member.extra, Extra::None, "{input}");
+                ast = member.object.clone();
+            }
+            assert_eq!(
+                ast.extra(),
+                &Extra::Parenthesized { paren_start },
+                "{input}"
+            );
+        }
+        for input in ["((a)) => 1", "([(a)]) => 1", "({a}) = 1", "[(a)] = 1"] {
+            let mut parser = Parser::new(input.to_string())?;
+            let result = parser.parse().map_err(String::from);
+            if input == "[(a)] = 1" {
+                assert!(result.is_ok(), "{input}");
+            } else {
+                assert_eq!(
+                    result,
+                    Err("Invalid destructuring target".to_string()),
+                    "{input}"
+                );
+            }
+        }
+        Ok(())
+    }
 }
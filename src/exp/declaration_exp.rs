@@ -1,51 +1,73 @@
 use crate::express::{expect_keys, is_ctrl_word, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{VariableDeclaration, VariableDeclarator};
-use crate::parser::Parser;
+use crate::node::{
+    Extra, Identifier, Pattern, VariableDeclaration, VariableDeclarator, VariableKind,
+};
+use crate::parser::{EcmaVersion, Parser};
 use crate::token::Token;
 
-pub fn build_let(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let kind = expect_keys(&parser.current, &vec![Token::Var, Token::Let, Token::Const])?;
+pub fn build_let(parser: &mut Parser) -> Result<VariableDeclaration, String> {
+    let start = parser.start_position();
+    let kind = match expect_keys(&parser.current, &vec![Token::Var, Token::Let, Token::Const])? {
+        Token::Var => VariableKind::Var,
+        Token::Let => VariableKind::Let,
+        _ => VariableKind::Const,
+    };
+    if kind != VariableKind::Var {
+        parser.require(
+            EcmaVersion::Es2015,
+            &format!("'{}' declaration", parser.current),
+        )?;
+    }
     parser.next()?;
     let mut declarations = vec![];
-    declarations.push(*build_declarator(parser)?);
+    declarations.push(build_declarator(parser)?);
     loop {
         let c2 = &parser.current;
         match c2 {
             Token::Control(s) => match s.as_str() {
                 "," => {
-                    parser.regex_allowed = true;
                     parser.next()?;
-                    declarations.push(*build_declarator(parser)?);
+                    declarations.push(build_declarator(parser)?);
                 }
                 _ => break,
             },
             _ => break,
         }
     }
-    Ok(Box::new(VariableDeclaration { kind, declarations }))
+    Ok(VariableDeclaration {
+        kind,
+        declarations,
+        loc: parser.loc_from(start),
+    })
 }
 
-fn build_declarator(parser: &mut Parser) -> Result<Box<Node>, String> {
+fn build_declarator(parser: &mut Parser) -> Result<VariableDeclarator, String> {
+    let start = parser.start_position();
     let id = &parser.current;
     if let Token::Variable(s) = id {
-        let id = Box::new(Node::Identity {
+        parser.check_identifier(s)?;
+        let id = Pattern::Identifier(Identifier {
             name: s.to_string(),
+            extra: Extra::None,
+            loc: parser.loc.clone(),
         });
+        parser.check_binding(&id)?;
         parser.next()?;
         let equal = &parser.current;
         if !is_ctrl_word(equal, "=") {
-            return Ok(Box::new(VariableDeclarator {
+            return Ok(VariableDeclarator {
                 id,
                 init: None,
-            }));
+                loc: parser.loc_from(start),
+            });
         }
-        parser.regex_allowed = true;
         parser.next()?;
-        return Ok(Box::new(VariableDeclarator {
+        let init = Some(parse_expression(parser, 2)?);
+        return Ok(VariableDeclarator {
             id,
-            init: Some(parse_expression(parser, 1)?),
-        }));
+            init,
+            loc: parser.loc_from(start),
+        });
     }
     Err(format!("expect Variable, find {id}"))
 }
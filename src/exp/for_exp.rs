@@ -1,108 +1,117 @@
+use crate::exp::arrow_function_exp::to_pattern;
 use crate::exp::declaration_exp::build_let;
 use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{
-    EmptyStatement, ForInStatement, ForStatement, Identity, VariableDeclaration, VariableDeclarator,
-};
+use crate::node::Statement::{ForInStatement, ForStatement};
+use crate::node::{Expression, ForInLeft, ForInit, Statement, VariableDeclaration};
 use crate::parser::{IsForIn, Parser};
 use crate::token::Token;
 
-pub fn build_for(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let init: Box<Node>;
-    let test: Box<Node>;
-    let update: Box<Node>;
+pub fn build_for(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let init: Option<ForInit>;
+    let test: Option<Box<Expression>>;
+    let update: Option<Box<Expression>>;
+    let start = parser.start_position();
     expect_keyword(&parser.current, Token::For)?;
     parser.next()?;
     expect(parser, "(")?;
 
     parser.in_for_init = true;
     parser.is_for_in = IsForIn::Maybe;
+    let mut left = None;
     if parser.current == Token::Let
         || parser.current == Token::Var
         || parser.current == Token::Const
     {
-        init = build_let(parser)?;
+        let declaration = build_let(parser)?;
         if parser.current == Token::In {
             parser.is_for_in = IsForIn::Must;
-            is_single_variable_without_value(&*init)?;
-            parser.regex_allowed = true;
+            is_single_variable_without_value(&declaration)?;
             parser.next()?;
+            left = Some(ForInLeft::VariableDeclaration(declaration));
+            init = None;
         } else {
             parser.is_for_in = IsForIn::Impossible;
+            init = Some(ForInit::VariableDeclaration(declaration));
         }
     } else if let Token::Variable(_) = &parser.current {
-        init = parse_expression(parser, 0)?;
+        let expression = parse_expression(parser, 0)?;
         if parser.current == Token::In {
-            if !matches!(*init, Identity { .. }) {
+            if !matches!(
+                *expression,
+                Expression::Identifier(_) | Expression::MemberExpression(_)
+            ) {
                 return Err("for in: syntax error".to_string());
             }
-            parser.regex_allowed = true;
             parser.next()?;
             parser.is_for_in = IsForIn::Must;
+            let pattern = to_pattern(*expression, false)?;
+            parser.check_assignment(&pattern)?;
+            left = Some(ForInLeft::Pattern(pattern));
+            init = None;
         } else {
             parser.is_for_in = IsForIn::Impossible;
+            init = Some(ForInit::Expression(expression));
         }
     } else {
         parser.is_for_in = IsForIn::Impossible;
         if is_ctrl_word(&parser.current, ";") {
-            init = Box::new(EmptyStatement {});
+            init = None;
         } else {
-            init = parse_expression(parser, 0)?;
+            init = Some(ForInit::Expression(parse_expression(parser, 0)?));
         }
     }
     parser.in_for_init = false;
 
-    if parser.is_for_in == IsForIn::Must {
+    if let Some(left) = left {
         let right = parse_expression(parser, 0)?;
         expect(parser, ")")?;
-        let body = parser.build_maybe_empty_body()?;
+        let body = Parser::parse_statement(parser)?;
         return ok_box(ForInStatement {
-            left: init,
+            left,
             right,
             body,
+            loc: parser.loc_from(start),
         });
     }
 
     expect(parser, ";")?;
     if is_ctrl_word(&parser.current, ";") {
-        test = Box::new(EmptyStatement {});
+        test = None;
     } else {
-        test = parse_expression(parser, 0)?;
+        test = Some(parse_expression(parser, 0)?);
     }
 
     expect(parser, ";")?;
     if is_ctrl_word(&parser.current, ")") {
-        update = Box::new(EmptyStatement {});
+        update = None;
     } else {
-        update = parse_expression(parser, 0)?;
+        update = Some(parse_expression(parser, 0)?);
     }
     expect(parser, ")")?;
+    let body = Parser::parse_statement(parser)?;
     ok_box(ForStatement {
         init,
         test,
         update,
-        body: parser.build_maybe_empty_body()?,
+        body,
+        loc: parser.loc_from(start),
     })
 }
 
-fn is_single_variable_without_value(node: &Node) -> Result<bool, String> {
-    if let VariableDeclaration { declarations, .. } = node {
-        if declarations.len() != 1 {
-            return Err("for in: syntax error, more than one variable".to_string());
-        }
-        if let VariableDeclarator { init, .. } = &declarations[0] {
-            if init.is_some() {
-                return Err("for in: syntax error".to_string());
-            }
-        }
-    } else {
-        panic!("unhandled variable declaration");
+fn is_single_variable_without_value(declaration: &VariableDeclaration) -> Result<bool, String> {
+    if declaration.declarations.len() != 1 {
+        return Err("for in: syntax error, more than one variable".to_string());
+    }
+    if declaration.declarations[0].init.is_some() {
+        return Err("for in: syntax error".to_string());
     }
     Ok(true)
 }
 
 #[cfg(test)]
 mod test {
+    use crate::node::Statement::{ForInStatement, ForStatement};
+    use crate::node::{ForInLeft, ModuleItem, Pattern};
     use crate::parser::Parser;
     use crate::token::Token;
 
@@ -147,7 +156,7 @@ mod test {
     #[test]
     fn test_for_in_err1() {
         let mut parser = Parser::new("for(let i,b in {}) {}".to_string()).unwrap();
-        let ast = parser.parse();
+        let ast = parser.parse().map_err(String::from);
         assert_eq!(
             ast,
             Err("for in: syntax error, more than one variable".to_string())
@@ -157,7 +166,30 @@ mod test {
     #[test]
     fn test_for_in_err2() {
         let mut parser = Parser::new("for(let i=1 in {}) {}".to_string()).unwrap();
-        let ast = parser.parse();
+        let ast = parser.parse().map_err(String::from);
         assert_eq!(ast, Err("for in: syntax error".to_string()))
     }
+
+    #[test]
+    fn test_for_empty_parts() -> Result<(), String> {
+        let mut parser = Parser::new("for (;;) a.b++; for (a.b in c) {}".to_string())?;
+        let ast = parser.parse()?;
+        assert!(matches!(
+            &ast.body[0],
+            ModuleItem::Statement(ForStatement {
+                init: None,
+                test: None,
+                update: None,
+                ..
+            })
+        ));
+        assert!(matches!(
+            &ast.body[1],
+            ModuleItem::Statement(ForInStatement {
+                left: ForInLeft::Pattern(Pattern::MemberExpression(_)),
+                ..
+            })
+        ));
+        Ok(())
+    }
 }
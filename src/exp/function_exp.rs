@@ -1,195 +1,210 @@
-use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{
-    ArrayPattern, AssignmentPattern, FunctionDeclaration, FunctionExpression, Identity,
-    ObjectPattern, ObjectProperty,
-};
-use crate::parser::Parser;
+use crate::express::{expect, expect_keyword, is_ctrl_word, parse_expression};
+use crate::node::Pattern::{ArrayPattern, AssignmentPattern, ObjectPattern};
+use crate::node::{Extra, Function, Identifier, ObjectPatternProperty, Pattern, PropertyKey};
+use crate::parser::{EcmaVersion, Parser};
 use crate::token::Token;
 
-pub fn build_function(parser: &mut Parser, is_declaration: bool) -> Result<Box<Node>, String> {
-    let id: Option<Box<Node>>;
-    let mut params;
-    let body: Box<Node>;
+pub fn build_function(parser: &mut Parser, is_declaration: bool) -> Result<Function, String> {
+    let id: Option<Identifier>;
+    let start = parser.start_position();
 
     expect_keyword(&parser.current, Token::Function)?;
     parser.next()?;
 
     if let Token::Variable(s) = &parser.current {
-        id = Some(Box::new(Identity {
+        parser.check_identifier(s)?;
+        id = Some(Identifier {
             name: s.to_string(),
-        }));
+            extra: Extra::None,
+            loc: parser.loc.clone(),
+        });
         parser.next()?;
     } else if is_declaration {
         return Err("Expected function name".to_string());
     } else {
         id = None;
     }
-    params = handle_function_params(parser)?;
-    body = Parser::parse_block(parser)?;
-    if is_declaration {
-        return ok_box(FunctionDeclaration {
-            id: id.unwrap(),
-            params,
-            body,
-        });
-    }
-    ok_box(FunctionExpression { id, params, body })
+    let (params, trailing_comma) = handle_function_params(parser)?;
+    let body = Parser::parse_function_body(parser, id.as_ref(), &params)?;
+    Ok(Function {
+        id,
+        params,
+        body,
+        trailing_comma,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    })
 }
 
-pub fn handle_function_params(parser: &mut Parser) -> Result<Vec<Node>, String> {
-    let mut params: Vec<Node> = vec![];
+pub fn handle_function_params(parser: &mut Parser) -> Result<(Vec<Pattern>, bool), String> {
+    let mut params: Vec<Pattern> = vec![];
+    let mut trailing_comma = false;
 
     expect(parser, "(")?;
     loop {
         if is_ctrl_word(&parser.current, ")") {
             break;
-        } else if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
-            parser.next()?;
-            continue;
-        } else if let Token::Variable(s) = &parser.current {
-            let param = Identity {
-                name: s.to_string(),
-            };
-            parser.next()?;
-            if is_ctrl_word(&parser.current, "=") {
-                parser.regex_allowed = true;
-                parser.next()?;
-                let default_value = parse_expression(parser, 2)?;
-                params.push(AssignmentPattern {
-                    left: Box::new(param),
-                    right: default_value,
-                });
-            } else {
-                params.push(param);
-            }
+        } else if let Token::Variable(_) = &parser.current {
+            params.push(handle_identifier(parser)?);
         } else if is_ctrl_word(&parser.current, "{") {
             params.push(handle_object(parser)?);
         } else if is_ctrl_word(&parser.current, "[") {
             params.push(handle_array(parser)?);
+        } else {
+            return Err(format!("function param error, find {}", parser.current));
+        }
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, ")");
+        } else {
+            break;
         }
     }
 
     expect(parser, ")")?;
-    Ok(params)
+    if trailing_comma {
+        parser.require(EcmaVersion::Es2017, "Trailing comma after parameters")?;
+    }
+    Ok((params, trailing_comma))
 }
 
-fn handle_object(parser: &mut Parser) -> Result<Node, String> {
+/// A binding name with an optional `= default`.
+fn handle_identifier(parser: &mut Parser) -> Result<Pattern, String> {
+    let Token::Variable(s) = &parser.current else {
+        return Err("expect variable".to_string());
+    };
+    parser.check_identifier(s)?;
+    let start = parser.start_position();
+    let name = Pattern::Identifier(Identifier {
+        name: s.to_string(),
+        extra: Extra::None,
+        loc: parser.loc.clone(),
+    });
+    parser.next()?;
+    if !is_ctrl_word(&parser.current, "=") {
+        return Ok(name);
+    }
+    parser.require(EcmaVersion::Es2015, "Default parameter")?;
+    parser.next()?;
+    let right = parse_expression(parser, 2)?;
+    Ok(AssignmentPattern {
+        left: Box::new(name),
+        right,
+        loc: parser.loc_from(start),
+    })
+}
+
+fn handle_object(parser: &mut Parser) -> Result<Pattern, String> {
     if !is_ctrl_word(&parser.current, "{") {
         return Err("function handle_object expect {".to_string());
     }
+    parser.require(EcmaVersion::Es2015, "Destructuring pattern")?;
+    let start = parser.start_position();
     parser.next()?;
     let mut properties = vec![];
+    let mut trailing_comma = false;
     loop {
         if is_ctrl_word(&parser.current, "}") {
             break;
         } else if let Token::Variable(s) = &parser.current {
-            let name = s.to_string();
-            parser.next()?;
-            if is_ctrl_word(&parser.current, ":") {
-                parser.regex_allowed = true;
+            let property_start = parser.start_position();
+            let key = Identifier {
+                name: s.to_string(),
+                extra: Extra::None,
+                loc: parser.loc.clone(),
+            };
+            if is_ctrl_word(parser.peek_token(1)?, ":") {
+                parser.next()?;
                 parser.next()?;
-                if is_ctrl_word(&parser.current, "{") {
-                    let right = handle_object(parser)?;
-                    properties.push(ObjectProperty {
-                        key: Box::new(Identity {
-                            name: name.to_string(),
-                        }),
-                        value: Box::new(right),
-                    })
+                let value = if is_ctrl_word(&parser.current, "{") {
+                    handle_object(parser)?
                 } else if is_ctrl_word(&parser.current, "[") {
-                    properties.push(ObjectProperty {
-                        key: Box::new(Identity {
-                            name: name.to_string(),
-                        }),
-                        value: Box::new(handle_array(parser)?),
-                    })
+                    handle_array(parser)?
                 } else {
                     return Err("handle_object expect { or [ after :".to_string());
-                }
-            } else if is_ctrl_word(&parser.current, "=") {
-                parser.regex_allowed = true;
-                parser.next()?;
-                let right = parse_expression(parser, 2)?;
-                properties.push(ObjectProperty {
-                    key: Box::new(Identity {
-                        name: name.to_string(),
-                    }),
-                    value: Box::new(AssignmentPattern {
-                        left: Box::new(Identity {
-                            name: name.to_string(),
-                        }),
-                        right,
-                    }),
-                })
-            } else if is_ctrl_word(&parser.current, ",") {
-                parser.regex_allowed = true;
-                parser.next()?;
-                properties.push(ObjectProperty {
-                    key: Box::new(Identity {
-                        name: name.to_string(),
-                    }),
-                    value: Box::new(Identity {
-                        name: name.to_string(),
-                    }),
+                };
+                properties.push(ObjectPatternProperty {
+                    key: PropertyKey::Identifier(key),
+                    value,
+                    shorthand: false,
+                    loc: parser.loc_from(property_start),
                 })
             } else {
-                return Err("handle_object syntax error".to_string());
+                let value = handle_identifier(parser)?;
+                properties.push(ObjectPatternProperty {
+                    key: PropertyKey::Identifier(key),
+                    value,
+                    shorthand: true,
+                    loc: parser.loc_from(property_start),
+                })
             }
         } else {
             return Err("handle_object expect variable".to_string());
         }
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, "}");
+        } else if !is_ctrl_word(&parser.current, "}") {
+            return Err("handle_object syntax error".to_string());
+        }
     }
     if !is_ctrl_word(&parser.current, "}") {
         return Err("function param expect }".to_string());
     }
     parser.next()?;
-    Ok(ObjectPattern { properties })
+    Ok(ObjectPattern {
+        properties,
+        trailing_comma,
+        loc: parser.loc_from(start),
+    })
 }
 
-fn handle_array(parser: &mut Parser) -> Result<Node, String> {
+fn handle_array(parser: &mut Parser) -> Result<Pattern, String> {
     let mut elements = vec![];
+    let mut trailing_comma = false;
     if !is_ctrl_word(&parser.current, "[") {
         return Err("function handle_array expect [".to_string());
     }
+    parser.require(EcmaVersion::Es2015, "Destructuring pattern")?;
+    let start = parser.start_position();
     parser.next()?;
     loop {
         if is_ctrl_word(&parser.current, "]") {
             break;
         } else if is_ctrl_word(&parser.current, ",") {
+            elements.push(None);
             parser.next()?;
-        } else if let Token::Variable(s) = &parser.current {
-            let name = Identity {
-                name: s.to_string(),
-            };
-            parser.next()?;
-            if is_ctrl_word(&parser.current, "=") {
-                parser.next()?;
-                elements.push(AssignmentPattern {
-                    left: Box::new(name),
-                    right: parse_expression(parser, 2)?,
-                });
-            } else {
-                elements.push(name);
-            }
+            continue;
+        } else if let Token::Variable(_) = &parser.current {
+            elements.push(Some(handle_identifier(parser)?));
         } else if is_ctrl_word(&parser.current, "{") {
-            elements.push(handle_object(parser)?);
+            elements.push(Some(handle_object(parser)?));
         } else if is_ctrl_word(&parser.current, "[") {
-            elements.push(handle_array(parser)?);
+            elements.push(Some(handle_array(parser)?));
         } else {
             return Err("handle_array syntax error".to_string());
         }
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, "]");
+        } else if !is_ctrl_word(&parser.current, "]") {
+            return Err("handle_array syntax error".to_string());
+        }
     }
     if !is_ctrl_word(&parser.current, "]") {
         return Err("function handle_array expect ]".to_string());
     }
     parser.next()?;
-    Ok(ArrayPattern { elements })
+    Ok(ArrayPattern {
+        elements,
+        trailing_comma,
+        loc: parser.loc_from(start),
+    })
 }
 
 #[cfg(test)]
 mod test {
+    use crate::node::{Declaration, Function, ModuleItem, Statement};
     use crate::parser::Parser;
     use crate::token::Token;
 
@@ -272,7 +287,8 @@ mod test {
 
     #[test]
     fn test_function_array2() -> Result<(), String> {
-        let mut parser = Parser::new("function a([b = {c: 3}], d) {let z = 1}".to_string()).unwrap();
+        let mut parser =
+            Parser::new("function a([b = {c: 3}], d) {let z = 1}".to_string()).unwrap();
         let ast = parser.parse()?;
         println!("{ast:#?}");
         assert_eq!(parser.current, Token::EOF);
@@ -281,7 +297,8 @@ mod test {
 
     #[test]
     fn test_function_mix() -> Result<(), String> {
-        let mut parser = Parser::new("function b(c, {d: {e: [f, g, {h = 3}]}}) {}".to_string()).unwrap();
+        let mut parser =
+            Parser::new("function b(c, {d: {e: [f, g, {h = 3}]}}) {}".to_string()).unwrap();
         let ast = parser.parse()?;
         println!("{ast:#?}");
         assert_eq!(parser.current, Token::EOF);
@@ -296,4 +313,24 @@ mod test {
         assert_eq!(parser.current, Token::EOF);
         Ok(())
     }
+
+    #[test]
+    fn test_function_trailing_comma() -> Result<(), String> {
+        let mut parser = Parser::new("function a(b, {c, d: [, e,],}, f,) {}".to_string())?;
+        let program = parser.parse()?;
+        if let ModuleItem::Statement(Statement::Declaration(Declaration::FunctionDeclaration(
+            Function {
+                params,
+                trailing_comma,
+                ..
+            },
+        ))) = &program.body[0]
+        {
+            assert!(*trailing_comma);
+            assert_eq!(params.len(), 3);
+        } else {
+            panic!("expect function");
+        }
+        Ok(())
+    }
 }
@@ -1,53 +1,36 @@
-use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{EmptyStatement, IfStatement};
+use crate::express::{expect, expect_keyword, ok_box, parse_expression};
+use crate::node::Statement;
+use crate::node::Statement::IfStatement;
 use crate::parser::Parser;
 use crate::token::Token;
 
-pub fn build_if(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let test: Box<Node>;
-    let consequent: Box<Node>;
-    let alternate: Option<Box<Node>>;
-
+pub fn build_if(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let start = parser.start_position();
     expect_keyword(&parser.current, Token::If)?;
     parser.next()?;
-    parser.regex_allowed = true;
     expect(parser, "(")?;
-    test = parse_expression(parser, 0)?;
+    let test = parse_expression(parser, 0)?;
     expect(parser, ")")?;
 
-    if is_ctrl_word(&parser.current, "{") {
-        consequent = Parser::parse_block(parser)?;
-    } else if is_ctrl_word(&parser.current, ";") {
-        consequent = Box::new(EmptyStatement {});
-        parser.regex_allowed = true;
-        parser.next()?;
-    } else {
-        return Err("if syntax error".to_string());
-    }
-
-    if parser.current == Token::Else {
-        parser.regex_allowed = true;
+    let consequent = Parser::parse_statement(parser)?;
+    let alternate = if parser.current == Token::Else {
         parser.next()?;
-        if parser.current == Token::If {
-            alternate = Some(build_if(parser)?);
-        } else if is_ctrl_word(&parser.current, "{") {
-            alternate = Some(Parser::parse_block(parser)?);
-        } else {
-            alternate = Some(Box::new(EmptyStatement {}));
-        }
+        Some(Parser::parse_statement(parser)?)
     } else {
-        alternate = None;
-    }
+        None
+    };
     ok_box(IfStatement {
         test,
         consequent,
         alternate,
+        loc: parser.loc_from(start),
     })
 }
 
 #[cfg(test)]
 mod test_if_statement {
+    use crate::node::ModuleItem;
+    use crate::node::Statement::IfStatement;
     use crate::parser::Parser;
     use crate::token::Token;
 
@@ -67,6 +50,30 @@ mod test_if_statement {
         assert_eq!(parser.current, Token::EOF)
     }
 
+    #[test]
+    fn test_if_regex_consequent() {
+        let mut parser = Parser::new("if (x) /re/.test(y)".to_string()).unwrap();
+        let ast = parser.parse();
+        println!("{ast:#?}");
+        assert!(ast.is_ok());
+        assert_eq!(parser.current, Token::EOF)
+    }
+
+    #[test]
+    fn test_if_else_statements() -> Result<(), String> {
+        let mut parser = Parser::new("if (a) b(); else c = 1;".to_string())?;
+        let ast = parser.parse()?;
+        assert!(matches!(
+            &ast.body[0],
+            ModuleItem::Statement(IfStatement {
+                alternate: Some(_),
+                ..
+            })
+        ));
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
     #[test]
     fn test_if_else_if() {
         let mut parser = Parser::new("if (1) {} else if(2){} else {}".to_string()).unwrap();
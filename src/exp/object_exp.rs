@@ -1,47 +1,60 @@
 use crate::exp::function_exp::handle_function_params;
 use crate::express::{expect, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{
-    Identity, NumericLiteral, ObjectExpression, ObjectMethod, ObjectProperty, StringLiteral,
+use crate::lex::numeric_value;
+use crate::node::Expression::{AssignmentExpression, ObjectExpression};
+use crate::node::ObjectMember::{ObjectMethod, ObjectProperty};
+use crate::node::{
+    Expression, Extra, Function, Identifier, NumericLiteral, Pattern, PropertyKey, StringLiteral,
 };
-use crate::parser::Parser;
+use crate::parser::{EcmaVersion, Parser};
 use crate::token::{Token, is_keyword};
 
-pub fn build_object(parser: &mut Parser) -> Result<Box<Node>, String> {
+pub fn build_object(parser: &mut Parser) -> Result<Box<Expression>, String> {
     let mut properties = vec![];
+    let mut trailing_comma = false;
+    let start = parser.start_position();
 
     expect(parser, "{")?;
 
     loop {
         if is_ctrl_word(&parser.current, "}") {
             break;
-        } else if is_ctrl_word(&parser.current, ",") {
-            parser.regex_allowed = true;
-            parser.next()?;
-            continue;
         }
-        let key: Node;
+        let key: PropertyKey;
+        let property_start = parser.start_position();
+        let is_identifier = matches!(parser.current, Token::Variable(_));
+        parser.check_literal(&parser.current)?;
 
         if is_keyword(&parser.current) {
-            key = Identity {
+            key = PropertyKey::Identifier(Identifier {
                 name: parser.current.to_string(),
-            }
+                extra: Extra::None,
+                loc: parser.loc.clone(),
+            })
         } else {
             match &parser.current {
                 Token::Variable(s) => {
-                    key = StringLiteral {
-                        value: s.to_string(),
-                    };
+                    key = PropertyKey::Identifier(Identifier {
+                        name: s.to_string(),
+                        extra: Extra::None,
+                        loc: parser.loc.clone(),
+                    });
                 }
-                Token::String(s) => {
-                    key = StringLiteral {
+                Token::String(s, raw) => {
+                    key = PropertyKey::StringLiteral(StringLiteral {
                         value: s.to_string(),
-                    };
+                        raw: raw.to_string(),
+                        extra: Extra::None,
+                        loc: parser.loc.clone(),
+                    });
                 }
                 Token::Digit(s) => {
-                    key = NumericLiteral {
-                        value: s.to_string(),
-                    };
+                    key = PropertyKey::NumericLiteral(NumericLiteral {
+                        value: numeric_value(s),
+                        raw: s.to_string(),
+                        extra: Extra::None,
+                        loc: parser.loc.clone(),
+                    });
                 }
                 _ => {
                     return Err("object property type error".to_string());
@@ -49,35 +62,85 @@ pub fn build_object(parser: &mut Parser) -> Result<Box<Node>, String> {
             }
         }
         parser.next()?;
-        if is_ctrl_word(&parser.current, ",") {
-            properties.push(ObjectProperty {
-                key: Box::new(key.clone()),
-                value: Box::new(key),
-            });
-        } else if is_ctrl_word(&parser.current, "(") {
-            let params = handle_function_params(parser)?;
-            let body = Parser::parse_block(parser)?;
+        if is_ctrl_word(&parser.current, "(") {
+            parser.require(EcmaVersion::Es2015, "Method definition")?;
+            let function_start = parser.start_position();
+            let (params, params_trailing_comma) = handle_function_params(parser)?;
+            let body = Parser::parse_function_body(parser, None, &params)?;
             properties.push(ObjectMethod {
-                key: Box::new(key),
-                params,
-                body,
+                key,
+                function: Function {
+                    id: None,
+                    params,
+                    body,
+                    trailing_comma: params_trailing_comma,
+                    extra: Extra::None,
+                    loc: parser.loc_from(function_start),
+                },
+                loc: parser.loc_from(property_start),
             })
         } else if is_ctrl_word(&parser.current, ":") {
-            parser.regex_allowed = true;
             parser.next()?;
+            parser.maybe_pattern = true;
+            let value = parse_expression(parser, 2)?;
             properties.push(ObjectProperty {
-                key: Box::new(key),
-                value: parse_expression(parser, 2)?,
+                key,
+                value,
+                shorthand: false,
+                loc: parser.loc_from(property_start),
+            });
+        } else if !is_identifier {
+            return Err(format!(
+                "expect : after object key, find {}",
+                parser.current
+            ));
+        } else if let PropertyKey::Identifier(name) = &key {
+            parser.require(EcmaVersion::Es2015, "Shorthand property")?;
+            parser.check_identifier(&name.name)?;
+            let value = if is_ctrl_word(&parser.current, "=") {
+                // `{a = 1}` is only valid once the object is reinterpreted as a pattern
+                parser.next()?;
+                let right = parse_expression(parser, 2)?;
+                AssignmentExpression {
+                    left: Box::new(Pattern::Identifier(name.clone())),
+                    operator: "=".to_string(),
+                    right,
+                    extra: Extra::None,
+                    loc: parser.loc_from(property_start.clone()),
+                }
+            } else {
+                Expression::Identifier(name.clone())
+            };
+            properties.push(ObjectProperty {
+                key,
+                value: Box::new(value),
+                shorthand: true,
+                loc: parser.loc_from(property_start),
             });
         }
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, "}");
+        } else {
+            break;
+        }
     }
 
     expect(parser, "}")?;
-    ok_box(ObjectExpression { properties })
+    ok_box(ObjectExpression {
+        properties,
+        trailing_comma,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    })
 }
 
 #[cfg(test)]
 mod test_object {
+    use crate::express::parse_expression;
+    use crate::node::Expression::{AssignmentExpression, ObjectExpression};
+    use crate::node::Function;
+    use crate::node::ObjectMember::ObjectMethod;
     use crate::parser::Parser;
     use crate::token::Token;
 
@@ -136,4 +199,52 @@ mod test_object {
         println!("{ast:#?}");
         assert_eq!(parser.current, Token::EOF)
     }
+
+    #[test]
+    fn test_object_trailing_comma() -> Result<(), String> {
+        let mut parser = Parser::new("a = {b, c: 1, d(e,) {},}".to_string())?;
+        let ast = parse_expression(&mut parser, 0)?;
+        if let AssignmentExpression { right, .. } = &*ast
+            && let ObjectExpression {
+                properties,
+                trailing_comma,
+                ..
+            } = &**right
+        {
+            assert_eq!(properties.len(), 3);
+            assert!(*trailing_comma);
+            assert!(matches!(
+                properties[2],
+                ObjectMethod {
+                    function: Function {
+                        trailing_comma: true,
+                        ..
+                    },
+                    ..
+                }
+            ));
+        } else {
+            panic!("expect object");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_missing_comma() {
+        let mut parser = Parser::new("a = {b: 1 c: 2}".to_string()).unwrap();
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_object_shorthand_errors() {
+        for input in [
+            "a = {return}",
+            "a = {'b'}",
+            "({1 = 2}) => 0",
+            "({if = 2}) => 0",
+        ] {
+            let mut parser = Parser::new(input.to_string()).unwrap();
+            assert!(parser.parse().is_err(), "{input}");
+        }
+    }
 }
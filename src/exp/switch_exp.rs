@@ -1,16 +1,16 @@
-use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{SwitchCase, SwitchStatement};
+use crate::express::{expect, expect_keyword, ok_box, parse_expression};
+use crate::node::Statement::SwitchStatement;
+use crate::node::{Expression, Statement, SwitchCase};
 use crate::parser::Parser;
 use crate::token::Token;
 
-pub fn build_switch(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let discriminant: Box<Node>;
-    let mut cases: Vec<Node> = vec![];
+pub fn build_switch(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let discriminant: Box<Expression>;
+    let mut cases: Vec<SwitchCase> = vec![];
+    let start = parser.start_position();
 
     expect_keyword(&parser.current, Token::Switch)?;
     parser.next()?;
-    parser.regex_allowed = true;
     expect(parser, "(")?;
     discriminant = parse_expression(parser, 0)?;
     expect(parser, ")")?;
@@ -18,11 +18,10 @@ pub fn build_switch(parser: &mut Parser) -> Result<Box<Node>, String> {
 
     loop {
         if parser.current == Token::Case || parser.current == Token::Default {
-            let test: Option<Box<Node>>;
-            let mut consequent: Vec<Node>;
+            let test: Option<Box<Expression>>;
+            let case_start = parser.start_position();
 
             if parser.current == Token::Case {
-                parser.regex_allowed = true;
                 parser.next()?;
                 test = Some(parse_expression(parser, 0)?);
             } else {
@@ -30,12 +29,12 @@ pub fn build_switch(parser: &mut Parser) -> Result<Box<Node>, String> {
                 parser.next()?;
             }
             expect(parser, ":")?;
-            if is_ctrl_word(&parser.current, "{") {
-                consequent = vec![*Parser::parse_block(parser)?];
-            } else {
-                consequent = Parser::parse_statement_list(parser)?;
-            }
-            cases.push(SwitchCase { test, consequent });
+            let consequent = Parser::parse_statement_list(parser)?;
+            cases.push(SwitchCase {
+                test,
+                consequent,
+                loc: parser.loc_from(case_start),
+            });
         } else {
             break;
         }
@@ -44,6 +43,7 @@ pub fn build_switch(parser: &mut Parser) -> Result<Box<Node>, String> {
     ok_box(SwitchStatement {
         discriminant,
         cases,
+        loc: parser.loc_from(start),
     })
 }
 
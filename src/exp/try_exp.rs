@@ -1,13 +1,14 @@
 use crate::express::{expect_keyword, is_ctrl_word, ok_box};
-use crate::node::Node;
-use crate::node::Node::{CatchClause, Identity, TryStatement};
-use crate::parser::Parser;
+use crate::node::Statement::TryStatement;
+use crate::node::{BlockStatement, CatchClause, Extra, Identifier, Pattern, Statement};
+use crate::parser::{EcmaVersion, Parser};
 use crate::token::Token;
 
-pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let block: Box<Node>;
-    let handle: Option<Box<Node>>;
-    let finalizer: Option<Box<Node>>;
+pub fn build_try(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let block: BlockStatement;
+    let handler: Option<CatchClause>;
+    let finalizer: Option<BlockStatement>;
+    let start = parser.start_position();
 
     expect_keyword(&parser.current, Token::Try)?;
     parser.next()?;
@@ -15,8 +16,9 @@ pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
     block = Parser::parse_block(parser)?;
 
     if parser.current == Token::Catch {
-        let param: Option<Box<Node>>;
-        let body: Box<Node>;
+        let param: Option<Pattern>;
+        let body: BlockStatement;
+        let catch_start = parser.start_position();
         parser.next()?;
         if is_ctrl_word(&parser.current, "(") {
             parser.next()?;
@@ -26,9 +28,14 @@ pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
             } else if is_ctrl_word(&parser.current, "{") {
                 return Err("catch({}) unsupported now".to_string());
             } else if let Token::Variable(s) = &parser.current {
-                param = Some(Box::new(Identity {
+                parser.check_identifier(s)?;
+                let name = Pattern::Identifier(Identifier {
                     name: s.to_string(),
-                }));
+                    extra: Extra::None,
+                    loc: parser.loc.clone(),
+                });
+                parser.check_binding(&name)?;
+                param = Some(name);
                 parser.next()?;
                 if is_ctrl_word(&parser.current, ")") {
                     parser.next()?;
@@ -39,10 +46,19 @@ pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
                 return Err("catch param error".to_string());
             }
             body = Parser::parse_block(parser)?;
-            handle = Some(Box::new(CatchClause { param, body }))
+            handler = Some(CatchClause {
+                param,
+                body,
+                loc: parser.loc_from(catch_start),
+            })
         } else if is_ctrl_word(&parser.current, "{") {
+            parser.require(EcmaVersion::Es2019, "Optional catch binding")?;
             body = Parser::parse_block(parser)?;
-            handle = Some(Box::new(CatchClause { param: None, body }))
+            handler = Some(CatchClause {
+                param: None,
+                body,
+                loc: parser.loc_from(catch_start),
+            })
         } else {
             return Err("catch syntax error".to_string());
         }
@@ -53,7 +69,7 @@ pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
             finalizer = None;
         }
     } else if parser.current == Token::Finally {
-        handle = None;
+        handler = None;
         parser.next()?;
         finalizer = Some(Parser::parse_block(parser)?);
     } else {
@@ -62,8 +78,9 @@ pub fn build_try(parser: &mut Parser) -> Result<Box<Node>, String> {
 
     ok_box(TryStatement {
         block,
-        handle,
+        handler,
         finalizer,
+        loc: parser.loc_from(start),
     })
 }
 
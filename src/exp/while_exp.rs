@@ -1,36 +1,42 @@
 use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
-use crate::node::Node;
-use crate::node::Node::{DoWhileStatement, EmptyStatement, IfStatement, WhileStatement};
+use crate::node::Statement;
+use crate::node::Statement::{DoWhileStatement, WhileStatement};
 use crate::parser::Parser;
 use crate::token::Token;
 
-pub fn build_while(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let test: Box<Node>;
-    let body: Box<Node>;
-
+pub fn build_while(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let start = parser.start_position();
     expect_keyword(&parser.current, Token::While)?;
     parser.next()?;
-    parser.regex_allowed = true;
     expect(parser, "(")?;
-    test = parse_expression(parser, 0)?;
+    let test = parse_expression(parser, 0)?;
     expect(parser, ")")?;
-    body = parser.build_maybe_empty_body()?;
-    ok_box(WhileStatement { test, body })
+    let body = Parser::parse_statement(parser)?;
+    ok_box(WhileStatement {
+        test,
+        body,
+        loc: parser.loc_from(start),
+    })
 }
 
-pub fn build_do_while(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let body: Box<Node>;
-    let test: Box<Node>;
+pub fn build_do_while(parser: &mut Parser) -> Result<Box<Statement>, String> {
+    let start = parser.start_position();
     expect_keyword(&parser.current, Token::Do)?;
     parser.next()?;
-    body = Parser::parse_block(parser)?;
+    let body = Parser::parse_statement(parser)?;
     expect_keyword(&parser.current, Token::While)?;
     parser.next()?;
-    parser.regex_allowed = true;
     expect(parser, "(")?;
-    test = parse_expression(parser, 0)?;
+    let test = parse_expression(parser, 0)?;
     expect(parser, ")")?;
-    ok_box(DoWhileStatement { body, test })
+    if is_ctrl_word(&parser.current, ";") {
+        parser.next()?;
+    }
+    ok_box(DoWhileStatement {
+        body,
+        test,
+        loc: parser.loc_from(start),
+    })
 }
 
 #[cfg(test)]
@@ -1,43 +1,62 @@
+use crate::diagnostic::Diagnostic;
 use crate::exp::array_exp::build_array;
-use crate::exp::arrow_function_exp::build_possible_arrow_function;
+use crate::exp::arrow_function_exp::{build_possible_arrow_function, has_cover_init, to_pattern};
 use crate::exp::function_exp::build_function;
 use crate::exp::object_exp::build_object;
-use crate::node::Node::{
-    BooleanLiteral, Identity, NewExpression, NullLiteral, RegExpLiteral, SequenceExpression,
-    TemplateElement, TemplateLiteral, ThisExpression, UnaryExpression,
+use crate::lex::{cook_template, numeric_value};
+use crate::node::Expression::{
+    BooleanLiteral, NewExpression, NullLiteral, RegExpLiteral, SequenceExpression, TemplateLiteral,
+    ThisExpression, UnaryExpression,
 };
-use crate::node::{Extra, Node};
-use crate::parser::Parser;
+use crate::node::{
+    ArrowFunctionBody, Expression, Extra, Identifier, MemberExpression, NumericLiteral, Pattern,
+    SourceType, StringLiteral, TemplateElement,
+};
+use crate::parser::{EcmaVersion, Parser};
 use crate::token::{Token, is_keyword};
 
-pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>, String> {
-    let mut left: Box<Node>;
+pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Expression>, String> {
+    let mut left: Box<Expression>;
+    let maybe_pattern = std::mem::take(&mut parser.maybe_pattern);
+    if is_ctrl_word(&parser.current, "/") || is_ctrl_word(&parser.current, "/=") {
+        parser.reread_as_regex()?;
+    }
+    let start = parser.start_position();
     if parser.is_identity_keyword && is_keyword(&parser.current) {
-        left = Box::new(Identity {
+        left = Box::new(Expression::Identifier(Identifier {
             name: parser.current.to_string(),
-        });
+            extra: Extra::None,
+            loc: parser.loc.clone(),
+        }));
         parser.next()?;
     } else if parser.current == Token::Function {
-        left = build_function(parser, false)?;
+        left = Box::new(Expression::FunctionExpression(build_function(
+            parser, false,
+        )?));
     } else if let Token::Control(s) = &parser.current {
         let operator = s.to_string();
         let l = get_level(&parser.current)?;
         match s.as_str() {
             "++" | "--" => {
                 parser.next()?;
-                left = Box::new(Node::UpdateExpression {
+                let argument = parse_expression(parser, l + 1)?;
+                check_update(parser, &argument)?;
+                left = Box::new(Expression::UpdateExpression {
                     operator,
                     prefix: true,
-                    argument: parse_expression(parser, l + 1)?,
+                    argument,
+                    extra: Extra::None,
+                    loc: parser.loc_from(start.clone()),
                 });
             }
             "+" | "-" | "!" | "typeof" | "~" => {
-                parser.regex_allowed = true;
                 parser.next()?;
                 left = Box::new(UnaryExpression {
                     operator,
                     prefix: true,
                     argument: parse_expression(parser, l + 1)?,
+                    extra: Extra::None,
+                    loc: parser.loc_from(start.clone()),
                 });
             }
             "(" => {
@@ -50,84 +69,149 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
             _ => return Err("expect control,".to_string()),
         }
     } else if parser.current == Token::Typeof {
-        parser.regex_allowed = true;
         parser.next()?;
         left = Box::new(UnaryExpression {
             argument: parse_expression(parser, 14)?,
             operator: "typeof".to_string(),
             prefix: true,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
         })
     } else if parser.current == Token::Delete {
-        parser.regex_allowed = true;
+        parser.next()?;
+        let argument = parse_expression(parser, 14)?;
+        let mut operand = argument.as_ref();
+        while let Expression::ParenthesizedExpression { expression, .. } = operand {
+            operand = expression;
+        }
+        if parser.strict && matches!(operand, Expression::Identifier(_)) {
+            return Err("Deleting local variable in strict mode".to_string());
+        }
+        left = Box::new(UnaryExpression {
+            argument,
+            operator: "delete".to_string(),
+            prefix: true,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        })
+    } else if parser.current == Token::Await {
+        left = build_await(parser)?;
+    } else if parser.current == Token::Throw && parser.options.proposals.throw_expressions {
         parser.next()?;
         left = Box::new(UnaryExpression {
             argument: parse_expression(parser, 14)?,
-            operator: "delete".to_string(),
+            operator: "throw".to_string(),
             prefix: true,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
         })
     } else if parser.current == Token::True {
         parser.next()?;
-        left = Box::new(BooleanLiteral { value: true });
+        left = Box::new(BooleanLiteral {
+            value: true,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        });
     } else if parser.current == Token::False {
         parser.next()?;
-        left = Box::new(BooleanLiteral { value: false });
+        left = Box::new(BooleanLiteral {
+            value: false,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        });
     } else if parser.current == Token::This {
         parser.next()?;
-        left = Box::new(ThisExpression {});
+        left = Box::new(ThisExpression {
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        });
     } else if parser.current == Token::Null {
         parser.next()?;
-        left = Box::new(NullLiteral {});
+        left = Box::new(NullLiteral {
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        });
     } else if parser.current == Token::Undefined {
         parser.next()?;
-        left = Box::new(Identity {
+        left = Box::new(Expression::Identifier(Identifier {
             name: "undefined".to_string(),
-        });
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
+        }));
     } else if let Token::Regex(pattern, flags) = &parser.current {
+        require_regex_flags(parser, flags)?;
         left = Box::new(RegExpLiteral {
             pattern: pattern.to_string(),
             flags: flags.to_string(),
+            extra: Extra::None,
+            loc: parser.loc.clone(),
         });
         parser.next()?;
-    } else if let Token::TemplateStr(s) = &parser.current {
-        left = Box::new(TemplateLiteral {
-            expressions: vec![],
-            quasis: vec![TemplateElement {
-                value: s.to_string(),
-            }],
-        });
-        parser.next()?;
+    } else if let Token::TemplateStr(_) = &parser.current {
+        parser.require(EcmaVersion::Es2015, "Template literal")?;
+        left = build_template(parser)?;
     } else if parser.current == Token::New {
         parser.next()?;
         let callee = parse_expression(parser, 18)?;
         let mut arguments = vec![];
+        let mut trailing_comma = false;
         if is_ctrl_word(&parser.current, "(") {
-            parser.next()?;
-            loop {
-                if is_ctrl_word(&parser.current, ")") {
-                    break;
-                }
-                if is_ctrl_word(&parser.current, ",") {
-                    parser.next()?;
-                }
-                arguments.push(*parse_expression(parser, 2)?)
-            }
-            expect(parser, ")")?;
+            (arguments, trailing_comma) = build_arguments(parser)?;
         }
-        left = Box::new(NewExpression { callee, arguments });
-    } else if let Token::Variable(s) = &parser.current {
-        left = Box::new(Identity {
-            name: s.to_string(),
+        left = Box::new(NewExpression {
+            callee,
+            arguments,
+            trailing_comma,
+            extra: Extra::None,
+            loc: parser.loc_from(start.clone()),
         });
+    } else if let Token::Variable(s) = &parser.current {
+        let name = s.to_string();
+        // Not after a `.`, where it names a property.
+        if !parser.is_identity_keyword {
+            if name == "import" {
+                check_import(parser)?;
+            } else {
+                parser.check_identifier(&name)?;
+            }
+        }
+        left = Box::new(Expression::Identifier(Identifier {
+            name,
+            extra: Extra::None,
+            loc: parser.loc.clone(),
+        }));
         parser.next()?;
     } else if let Token::Digit(d) = &parser.current {
-        left = Box::new(Node::NumericLiteral {
-            value: d.to_string(),
+        require_number(parser, d)?;
+        parser.check_literal(&parser.current)?;
+        left = Box::new(Expression::NumericLiteral(NumericLiteral {
+            value: numeric_value(d),
+            raw: d.to_string(),
+            extra: Extra::None,
+            loc: parser.loc.clone(),
+        }));
+        parser.next()?;
+    } else if let Token::BigInt(d) = &parser.current {
+        parser.require(EcmaVersion::Es2020, "BigInt literal")?;
+        require_number(parser, d)?;
+        left = Box::new(Expression::BigIntLiteral {
+            value: d.trim_end_matches('n').replace('_', ""),
+            raw: d.to_string(),
+            extra: Extra::None,
+            loc: parser.loc.clone(),
         });
         parser.next()?;
-    } else if let Token::String(d) = &parser.current {
-        left = Box::new(Node::StringLiteral {
+    } else if let Token::String(d, raw) = &parser.current {
+        if has_code_point_escape(raw) {
+            parser.require(EcmaVersion::Es2015, "Unicode code point escape")?;
+        }
+        parser.check_literal(&parser.current)?;
+        left = Box::new(Expression::StringLiteral(StringLiteral {
             value: d.to_string(),
-        });
+            raw: raw.to_string(),
+            extra: Extra::None,
+            loc: parser.loc.clone(),
+        }));
         parser.next()?;
     } else {
         return Err(format!(
@@ -136,11 +220,22 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
         ));
     }
     parser.is_identity_keyword = false;
+    if has_cover_init(&left)
+        && !is_ctrl_word(&parser.current, "=")
+        && !(maybe_pattern
+            && [",", ")", "]", "}"]
+                .iter()
+                .any(|s| is_ctrl_word(&parser.current, s)))
+    {
+        return Err("Invalid shorthand property initializer".to_string());
+    }
     loop {
         let operator = parser.current.clone();
         match &operator {
             Token::Control(s) => match s.as_str() {
                 ";" | ":" | ")" | "]" | "}" => break,
+                // A line break before a postfix operator ends the statement.
+                "++" | "--" if !parser.is_same_line() => break,
                 _ => {}
             },
             Token::EOF => break,
@@ -150,7 +245,7 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
                 }
                 break;
             }
-            Token::Digit(_) => {
+            Token::Digit(_) | Token::BigInt(_) => {
                 if parser.is_same_line() {
                     return Err("syntax error:".to_string());
                 }
@@ -173,136 +268,168 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
         match &operator {
             Token::Control(s) => match s.as_str() {
                 "," => {
-                    parser.regex_allowed = true;
                     parser.next()?;
                     let right = parse_expression(parser, l + 1)?;
-                    if let SequenceExpression { expressions, .. } = *left {
+                    if let SequenceExpression {
+                        expressions,
+                        extra: Extra::None,
+                        ..
+                    } = *left
+                    {
                         let mut exp = vec![];
                         exp.extend(expressions);
                         exp.push(*right);
                         left = Box::new(SequenceExpression {
                             expressions: exp,
-                            extra: Extra::Parenthesized,
+                            extra: Extra::None,
+                            loc: parser.loc_from(start.clone()),
                         })
                     } else {
                         left = Box::new(SequenceExpression {
                             expressions: vec![*left, *right],
-                            extra: Extra::Parenthesized,
+                            extra: Extra::None,
+                            loc: parser.loc_from(start.clone()),
                         })
                     }
                 }
                 "=" | "+=" | "-=" | "*=" | "/=" | "%=" | ">>=" | "<<=" | "|=" | "&=" => {
-                    parser.regex_allowed = true;
+                    let target = match *left {
+                        Expression::Identifier(_)
+                        | Expression::MemberExpression(_)
+                        | Expression::ParenthesizedExpression { .. } => {
+                            Box::new(to_pattern(*left, false)?)
+                        }
+                        Expression::ObjectExpression { .. }
+                        | Expression::ArrayExpression { .. }
+                            if s == "=" =>
+                        {
+                            parser.require(EcmaVersion::Es2015, "Destructuring assignment")?;
+                            Box::new(to_pattern(*left, false)?)
+                        }
+                        _ => return Err("Invalid left-hand side in assignment".to_string()),
+                    };
+                    parser.check_assignment(&target)?;
                     parser.next()?;
                     let right = parse_expression(parser, l)?;
-                    left = Box::new(Node::AssignmentExpression {
+                    left = Box::new(Expression::AssignmentExpression {
                         operator: s.to_string(),
-                        left,
+                        left: target,
                         right,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     })
                 }
                 "=>" => {
-                    parser.regex_allowed = true;
+                    let Expression::Identifier(param) = *left else {
+                        return Err("Invalid arrow function parameter".to_string());
+                    };
+                    parser.require(EcmaVersion::Es2015, "Arrow function")?;
                     parser.next()?;
-                    let right;
-                    if is_ctrl_word(&parser.current, "{") {
-                        right = Parser::parse_block(parser)?
+                    let params = vec![Pattern::Identifier(param)];
+                    let body = if is_ctrl_word(&parser.current, "{") {
+                        ArrowFunctionBody::BlockStatement(Parser::parse_function_body(
+                            parser, None, &params,
+                        )?)
                     } else {
-                        right = parse_expression(parser, 2)?;
-                    }
-                    left = Box::new(Node::ArrowFunctionExpression {
-                        params: vec![*left],
-                        body: right,
+                        if parser.strict {
+                            parser.check_params(None, &params)?;
+                        }
+                        ArrowFunctionBody::Expression(Parser::parse_arrow_body(parser)?)
+                    };
+                    left = Box::new(Expression::ArrowFunctionExpression {
+                        params,
+                        body,
+                        trailing_comma: false,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     })
                 }
                 "." => {
                     parser.next()?;
                     parser.is_identity_keyword = true;
                     let right = parse_expression(parser, l + 1)?;
-                    left = Box::new(Node::MemberExpression {
+                    left = Box::new(Expression::MemberExpression(MemberExpression {
                         computed: false,
                         object: left,
                         property: right,
-                    })
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
+                    }))
                 }
                 "+" | "-" | "*" | "/" | "%" | ">" | "<" | ">=" | "<=" | "==" | "===" | "!="
                 | "&" | "|" | "<<" | ">>" | "!==" => {
-                    parser.regex_allowed = true;
                     parser.next()?;
                     let right = parse_expression(parser, l + 1)?;
-                    left = Box::new(Node::BinaryExpression {
+                    left = Box::new(Expression::BinaryExpression {
                         operator: s.to_string(),
                         left,
                         right,
-                        extra: Extra::Parenthesized,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     })
                 }
                 "&&" | "||" => {
-                    parser.regex_allowed = true;
                     parser.next()?;
                     let right = parse_expression(parser, l + 1)?;
-                    left = Box::new(Node::LogicalExpression {
+                    left = Box::new(Expression::LogicalExpression {
                         operator: s.to_string(),
                         left,
                         right,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     })
                 }
                 "++" | "--" => {
+                    if !matches!(
+                        *left,
+                        Expression::Identifier(_) | Expression::MemberExpression(_)
+                    ) {
+                        return Err("Invalid left-hand side in postfix operation".to_string());
+                    }
+                    check_update(parser, &left)?;
                     parser.next()?;
-                    return ok_box(Node::UpdateExpression {
+                    left = Box::new(Expression::UpdateExpression {
                         operator: s.to_string(),
                         prefix: false,
                         argument: left,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     });
                 }
                 "?" => {
-                    parser.regex_allowed = true;
                     parser.next()?;
                     let consequent = parse_expression(parser, l)?;
                     expect(parser, ":")?;
                     let alternate = parse_expression(parser, l)?;
-                    return ok_box(Node::ConditionalExpression {
+                    return ok_box(Expression::ConditionalExpression {
                         test: left,
                         consequent,
                         alternate,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     });
                 }
                 "(" => {
-                    parser.regex_allowed = true;
-                    parser.next()?;
-                    let mut arguments: Vec<Node> = vec![];
-                    loop {
-                        let next = &parser.current;
-                        if is_ctrl_word(&next, ")") {
-                            parser.next()?;
-                            break;
-                        }
-                        let express = parse_expression(parser, 2)?;
-                        arguments.push(*express);
-                        let current = &parser.current.clone();
-                        if is_ctrl_word(&current, ",") {
-                            parser.next()?;
-                        }
-                        if is_ctrl_word(&current, ")") {
-                            parser.next()?;
-                            break;
-                        }
-                    }
-                    left = Box::new(Node::CallExpression {
+                    let (arguments, trailing_comma) = build_arguments(parser)?;
+                    left = Box::new(Expression::CallExpression {
                         callee: left,
                         arguments,
+                        trailing_comma,
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
                     });
                 }
                 "[" => {
-                    parser.regex_allowed = true;
                     parser.next()?;
                     let right = parse_expression(parser, 0)?;
                     expect(parser, "]")?;
-                    left = Box::new(Node::MemberExpression {
+                    left = Box::new(Expression::MemberExpression(MemberExpression {
                         computed: true,
                         object: left,
                         property: right,
-                    });
+                        extra: Extra::None,
+                        loc: parser.loc_from(start.clone()),
+                    }));
                 }
                 _ => {
                     return Err(format!("unsupported operator {:?}", &operator));
@@ -314,14 +441,14 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
                 } else {
                     "in"
                 };
-                parser.regex_allowed = true;
                 parser.next()?;
                 let right = parse_expression(parser, l + 1)?;
-                left = Box::new(Node::BinaryExpression {
+                left = Box::new(Expression::BinaryExpression {
                     operator: operator.to_string(),
                     left,
                     right,
-                    extra: Extra::Parenthesized,
+                    extra: Extra::None,
+                    loc: parser.loc_from(start.clone()),
                 })
             }
             _ => {
@@ -332,15 +459,138 @@ pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>,
     Ok(left)
 }
 
-pub fn ok_box(node: Node) -> Result<Box<Node>, String> {
+pub fn build_arguments(parser: &mut Parser) -> Result<(Vec<Expression>, bool), String> {
+    let mut arguments = vec![];
+    let mut trailing_comma = false;
+    expect(parser, "(")?;
+    while !is_ctrl_word(&parser.current, ")") {
+        arguments.push(*parse_expression(parser, 2)?);
+        if is_ctrl_word(&parser.current, ",") {
+            parser.next()?;
+            trailing_comma = is_ctrl_word(&parser.current, ")");
+        } else {
+            break;
+        }
+    }
+    expect(parser, ")")?;
+    if trailing_comma {
+        parser.require(EcmaVersion::Es2017, "Trailing comma after arguments")?;
+    }
+    Ok((arguments, trailing_comma))
+}
+
+/// Fails if the target version predates the syntax of the number `raw`.
+fn require_number(parser: &Parser, raw: &str) -> Result<(), String> {
+    if raw.contains('_') {
+        parser.require(EcmaVersion::Es2021, "Numeric separator")?;
+    }
+    match raw.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0b") => parser.require(EcmaVersion::Es2015, "Binary literal"),
+        Some("0o") => parser.require(EcmaVersion::Es2015, "Octal literal with 0o"),
+        _ => Ok(()),
+    }
+}
+
+fn require_regex_flags(parser: &Parser, flags: &str) -> Result<(), String> {
+    for flag in flags.chars() {
+        let version = match flag {
+            'u' | 'y' => EcmaVersion::Es2015,
+            's' => EcmaVersion::Es2018,
+            'd' => EcmaVersion::Es2022,
+            'v' => EcmaVersion::Es2024,
+            _ => continue,
+        };
+        parser.require(version, &format!("Regular expression flag '{flag}'"))?;
+    }
+    Ok(())
+}
+
+/// Whether the raw string has a `\u{...}` escape.
+fn has_code_point_escape(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next() == Some('u') && chars.clone().next() == Some('{') {
+            return true;
+        }
+    }
+    false
+}
+
+/// `++eval` assigns to `eval` as much as `eval = 1` does.
+fn check_update(parser: &Parser, argument: &Expression) -> Result<(), String> {
+    match argument {
+        Expression::Identifier(identifier) => {
+            parser.check_assignment(&Pattern::Identifier(identifier.clone()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A template literal, read one part at a time: each part runs through the
+/// next `${` or the closing backtick.
+fn build_template(parser: &mut Parser) -> Result<Box<Expression>, String> {
+    let start = parser.start_position();
+    let mut quasis = vec![];
+    let mut expressions = vec![];
+    loop {
+        let substitution = parser.reread_as_template_part()?;
+        let Token::TemplateStr(raw) = &parser.current else {
+            return Err("Unterminated template".to_string());
+        };
+        let value = raw.replace("\r\n", "\n").replace('\r', "\n");
+        let cooked = cook_template(&value)?;
+        // Without the `` ` `` or `}` before and the `${` or `` ` `` after.
+        let mut loc = parser.loc.clone();
+        let end = if substitution { 2 } else { 1 };
+        loc.start.column += 1;
+        loc.start.index += 1;
+        loc.end.column -= end;
+        loc.end.index -= end;
+        quasis.push(TemplateElement { value, cooked, loc });
+        parser.next()?;
+        if !substitution {
+            break;
+        }
+        expressions.push(*parse_expression(parser, 0)?);
+        if !is_ctrl_word(&parser.current, "}") {
+            // Fails, pointing at the `${`.
+            expect(parser, "}")?;
+        }
+    }
+    Ok(Box::new(TemplateLiteral {
+        expressions,
+        quasis,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    }))
+}
+
+/// `import` outside of a declaration is only `import(...)` or `import.meta`.
+fn check_import(parser: &mut Parser) -> Result<(), String> {
+    if is_ctrl_word(parser.peek_token(1)?, "(") {
+        return parser.require(EcmaVersion::Es2020, "Dynamic 'import'");
+    }
+    if !is_ctrl_word(parser.peek_token(1)?, ".") {
+        return Err("Unexpected reserved word 'import'".to_string());
+    }
+    if *parser.peek_token(2)? != Token::Variable("meta".to_string()) {
+        return Err("The only valid meta property for import is 'import.meta'".to_string());
+    }
+    if parser.options.source_type != SourceType::Module {
+        return Err("'import.meta' may appear only with 'sourceType: module'".to_string());
+    }
+    parser.require(EcmaVersion::Es2020, "'import.meta'")
+}
+
+pub fn ok_box<T>(node: T) -> Result<Box<T>, String> {
     Ok(Box::new(node))
 }
 
-pub fn box_(node: Node) -> Box<Node> {
+pub fn box_<T>(node: T) -> Box<T> {
     Box::new(node)
 }
 
-fn get_level(token: &Token) -> Result<u8, String> {
+pub fn get_level(token: &Token) -> Result<u8, String> {
     let d = match token {
         Token::Control(s) => match s.as_str() {
             "." | "[" | "(" | "?." | "{" => 17,
@@ -385,14 +635,50 @@ pub fn is_ctrl_word(word: &Token, str: &str) -> bool {
     }
 }
 
+/// An `await` expression outside of any function, where the options or a
+/// module allow it, otherwise the identifier `await` of a script.
+fn build_await(parser: &mut Parser) -> Result<Box<Expression>, String> {
+    let start = parser.start_position();
+    let module = parser.options.source_type == SourceType::Module;
+    if parser.in_function || !(module || parser.options.allow_await_outside_function) {
+        if module {
+            return Err(
+                "'await' is only allowed within async functions and at the top levels of modules"
+                    .to_string(),
+            );
+        }
+        parser.next()?;
+        return Ok(Box::new(Expression::Identifier(Identifier {
+            name: "await".to_string(),
+            extra: Extra::None,
+            loc: parser.loc_from(start),
+        })));
+    }
+    if parser.options.allow_await_outside_function {
+        parser.require(EcmaVersion::Es2017, "'await'")?;
+    } else {
+        parser.require(EcmaVersion::Es2022, "Top-level 'await'")?;
+    }
+    parser.next()?;
+    Ok(Box::new(Expression::AwaitExpression {
+        argument: parse_expression(parser, 14)?,
+        extra: Extra::None,
+        loc: parser.loc_from(start),
+    }))
+}
+
+/// Consumes `s`. Otherwise the error points at the current token, and for
+/// a closing bracket also at where it was opened.
 pub fn expect(parser: &mut Parser, s: &str) -> Result<(), String> {
-    match &parser.current {
-        Token::Control(next) => {
-            if next != s {
-                return Err(format!("expect() expect: {s}"));
-            }
+    if !is_ctrl_word(&parser.current, s) {
+        let mut diagnostic = Diagnostic::new(
+            format!("Unexpected token {}, expected `{s}`", parser.current),
+            parser.loc.clone(),
+        );
+        if let Some((open, loc)) = parser.opening(s) {
+            diagnostic = diagnostic.with_label(loc.clone(), &format!("opening `{open}` was here"));
         }
-        _ => return Err(format!("expect() expect:  {s}")),
+        return Err(parser.fail(diagnostic));
     }
     parser.next()?;
     Ok(())
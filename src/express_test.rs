@@ -1,9 +1,25 @@
 #[cfg(test)]
 mod test {
-    use crate::node::Node::*;
+    use crate::node::Expression::*;
+    use crate::node::{
+        Expression, Extra, ModuleItem, NumericLiteral, Program, Statement, StringLiteral,
+    };
     use crate::parser::Parser;
     use crate::token::Token;
 
+    fn expressions(program: Program) -> Vec<Expression> {
+        program
+            .body
+            .into_iter()
+            .map(|item| match item {
+                ModuleItem::Statement(Statement::ExpressionStatement { expression, .. }) => {
+                    *expression
+                }
+                _ => panic!("expect expression statement"),
+            })
+            .collect()
+    }
+
     #[test]
     fn test_dot() {
         let mut parser = Parser::new("a.b.c".to_string()).unwrap();
@@ -187,4 +203,171 @@ mod test {
         assert!(ast.is_ok());
         assert_eq!(parser.current, Token::EOF);
     }
+
+    #[test]
+    fn test_divide_chain() -> Result<(), String> {
+        let mut parser = Parser::new("a / b / c".to_string())?;
+        let ast = expressions(parser.parse()?);
+        assert!(matches!(&ast[0], BinaryExpression { operator, .. } if operator == "/"));
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_after_assign() -> Result<(), String> {
+        let mut parser = Parser::new("x = /=/g".to_string())?;
+        let ast = expressions(parser.parse()?);
+        if let AssignmentExpression { right, .. } = &ast[0] {
+            assert!(matches!(&**right, RegExpLiteral { pattern, flags, .. }
+                if pattern == "=" && flags == "g"));
+        } else {
+            panic!("expect assignment");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_after_keyword() -> Result<(), String> {
+        let mut parser = Parser::new("typeof /a/; a in /b/; switch (a) { case /c/: }".to_string())?;
+        parser.parse()?;
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_after_block() -> Result<(), String> {
+        let mut parser = Parser::new("if (a) {}\n/re/g.test(b) / 2".to_string())?;
+        let ast = parser.parse()?;
+        assert_eq!(ast.body.len(), 2);
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_literal() -> Result<(), String> {
+        let mut parser = Parser::new("0x1_0; 10n".to_string())?;
+        let ast = expressions(parser.parse()?);
+        assert!(
+            matches!(&ast[0], Expression::NumericLiteral(NumericLiteral { value, raw, .. })
+            if *value == 16.0 && raw == "0x1_0")
+        );
+        assert!(matches!(&ast[1], BigIntLiteral { value, raw, .. }
+            if value == "10" && raw == "10n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_literal_raw() -> Result<(), String> {
+        let mut parser = Parser::new(r#"'it\'s' + "\x41""#.to_string())?;
+        let ast = expressions(parser.parse()?);
+        if let BinaryExpression { left, right, .. } = &ast[0] {
+            assert!(
+                matches!(&**left, Expression::StringLiteral(StringLiteral { value, raw, .. })
+                if value == "it's" && raw == r"'it\'s'")
+            );
+            assert!(
+                matches!(&**right, Expression::StringLiteral(StringLiteral { value, raw, .. })
+                if value == "A" && raw == r#""\x41""#)
+            );
+        } else {
+            panic!("expect binary expression");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unicode_identifier() -> Result<(), String> {
+        let mut parser = Parser::new("let π = 3; 変数 = café + \\u{3C0}".to_string())?;
+        let ast = parser.parse()?;
+        assert_eq!(ast.body.len(), 2);
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_trailing_comma() -> Result<(), String> {
+        let mut parser = Parser::new("f(a, b,); new A(1,)".to_string())?;
+        let ast = expressions(parser.parse()?);
+        assert!(matches!(
+            &ast[0],
+            CallExpression { arguments, trailing_comma: true, .. } if arguments.len() == 2
+        ));
+        assert!(matches!(
+            &ast[1],
+            NewExpression { arguments, trailing_comma: true, .. } if arguments.len() == 1
+        ));
+        let mut parser = Parser::new("f(a b)".to_string())?;
+        assert!(parser.parse().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parenthesized() -> Result<(), String> {
+        let mut parser = Parser::new("(a + b) * c; a + b; (a, b), c".to_string())?;
+        let ast = expressions(parser.parse()?);
+        assert!(
+            matches!(&ast[0], BinaryExpression { left, extra: Extra::None, .. }
+            if matches!(&**left, BinaryExpression {
+                extra: Extra::Parenthesized { paren_start }, ..
+            } if *paren_start == 0))
+        );
+        assert!(matches!(
+            &ast[1],
+            BinaryExpression {
+                extra: Extra::None,
+                ..
+            }
+        ));
+        assert!(matches!(&ast[2], SequenceExpression { expressions, .. }
+            if expressions.len() == 2 && matches!(&expressions[0], SequenceExpression {
+                extra: Extra::Parenthesized { paren_start }, ..
+            } if *paren_start == 20)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_postfix_update() -> Result<(), String> {
+        let mut parser = Parser::new("a++ / 2; x = a++ + 1; b\n++c".to_string())?;
+        let ast = expressions(parser.parse()?);
+        assert!(matches!(&ast[0], BinaryExpression { left, operator, .. }
+            if operator == "/" && matches!(&**left, UpdateExpression { prefix: false, .. })));
+        assert!(matches!(&ast[1], AssignmentExpression { right, .. }
+            if matches!(&**right, BinaryExpression { left, operator, .. }
+                if operator == "+" && matches!(&**left, UpdateExpression { .. }))));
+        assert!(matches!(&ast[2], Identifier(_)));
+        assert!(matches!(&ast[3], UpdateExpression { prefix: true, .. }));
+        for source in ["a++++", "1++"] {
+            assert!(
+                Parser::new(source.to_string())?.parse().is_err(),
+                "{source}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_template() -> Result<(), String> {
+        let mut parser = Parser::new("`a${b}c${ {d: `e`}.d }`".to_string())?;
+        let ast = expressions(parser.parse()?);
+        let TemplateLiteral {
+            expressions,
+            quasis,
+            ..
+        } = &ast[0]
+        else {
+            panic!("expected a template");
+        };
+        assert_eq!(expressions.len(), 2);
+        let values: Vec<&str> = quasis.iter().map(|quasi| quasi.value.as_str()).collect();
+        assert_eq!(values, ["a", "c", ""]);
+        assert_eq!((quasis[1].loc.start.index, quasis[1].loc.end.index), (6, 7));
+        Parser::new("{ `${f}` }".to_string())?.parse()?;
+        for source in ["`${a`", "`${a b}`", "`${a}", "`\\01`"] {
+            assert!(
+                Parser::new(source.to_string())?.parse().is_err(),
+                "{source}"
+            );
+        }
+        Ok(())
+    }
 }
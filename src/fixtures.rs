@@ -0,0 +1,45 @@
+//! Helpers for the tests that run over the `fixtures/` directory.
+
+use crate::node::Program;
+use crate::parser::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Every `fixtures/<dir>/*.js` with its source.
+pub fn sources(dir: &str) -> Vec<(PathBuf, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(dir);
+    let mut sources = vec![];
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "js") {
+            let source = fs::read_to_string(&path).unwrap();
+            sources.push((path, source));
+        }
+    }
+    assert!(!sources.is_empty(), "no fixtures in {}", dir.display());
+    sources
+}
+
+pub fn parse(path: &Path, source: &str) -> Program {
+    Parser::new(source.to_string())
+        .and_then(|mut parser| Ok(parser.parse()?))
+        .unwrap_or_else(|err| panic!("{}: {err}", path.display()))
+}
+
+/// Compares `render` of every `fixtures/<dir>/*.js` against its sibling
+/// `.json`, the crate's own earlier output. Run with `UPDATE_FIXTURES=1` to
+/// regenerate it.
+pub fn check_snapshots(dir: &str, render: impl Fn(&Program) -> String) {
+    let update = std::env::var_os("UPDATE_FIXTURES").is_some();
+    for (path, source) in sources(dir) {
+        let actual = render(&parse(&path, &source));
+        let expected_path = path.with_extension("json");
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+        }
+        let expected = fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(actual, expected, "{}", path.display());
+    }
+}
@@ -0,0 +1,267 @@
+//! Syntax highlighting from the token stream, rendered as ANSI-coloured
+//! text or as HTML `<span>`s with `js-*` CSS classes. Text between tokens is
+//! copied as is, so the output reads exactly like the source. With
+//! [`highlight_semantic`] identifiers that declare a binding are told apart
+//! from references, using the syntax tree.
+
+use crate::cst::{self, SyntaxKind, SyntaxNode};
+use crate::token::Token;
+use crate::tokenize::{TokenizeOptions, tokenize};
+use std::collections::HashSet;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    Keyword,
+    Identifier,
+    /// An identifier that declares a variable, function, parameter or label.
+    Declaration,
+    String,
+    Number,
+    Regex,
+    Comment,
+    Punctuator,
+    Template,
+}
+
+impl Class {
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Class::Keyword => "js-keyword",
+            Class::Identifier => "js-identifier",
+            Class::Declaration => "js-declaration",
+            Class::String => "js-string",
+            Class::Number => "js-number",
+            Class::Regex => "js-regex",
+            Class::Comment => "js-comment",
+            Class::Punctuator => "js-punctuator",
+            Class::Template => "js-template",
+        }
+    }
+
+    /// The SGR parameters of the ANSI colour, if the class has one.
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("35"),
+            Class::Declaration => Some("1;34"),
+            Class::String => Some("32"),
+            Class::Number => Some("33"),
+            Class::Regex => Some("31"),
+            Class::Comment => Some("90"),
+            Class::Template => Some("36"),
+            Class::Identifier | Class::Punctuator => None,
+        }
+    }
+}
+
+/// A classified token; `range` counts chars, like `Loc`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub class: Class,
+    pub range: Range<usize>,
+}
+
+pub fn classify(token: &Token) -> Class {
+    match token {
+        Token::Variable(_) | Token::Undefined | Token::Async | Token::Await => Class::Identifier,
+        Token::String(..) => Class::String,
+        Token::Digit(_) | Token::BigInt(_) => Class::Number,
+        Token::Regex(..) => Class::Regex,
+        Token::Comment(_) | Token::BlockComment(_) | Token::Hashbang(_) => Class::Comment,
+        Token::Control(_) => Class::Punctuator,
+        Token::TemplateStr(_) => Class::Template,
+        _ => Class::Keyword,
+    }
+}
+
+/// Classifies every token and comment of `source` without parsing it.
+pub fn highlight(source: &str) -> Result<Vec<Span>, String> {
+    tokenize(source, TokenizeOptions { comments: true })
+        .map(|token| {
+            token.map(|token| Span {
+                class: classify(&token.token),
+                range: token.loc.start.index..token.loc.end.index,
+            })
+        })
+        .collect()
+}
+
+/// Like [`highlight`], but parses `source` to mark declarations.
+pub fn highlight_semantic(source: &str) -> Result<Vec<Span>, String> {
+    let tree = cst::parse(source)?;
+    let declared: HashSet<usize> = tree
+        .descendants()
+        .into_iter()
+        .filter(|node| node.kind() == SyntaxKind::Identifier && declares(node))
+        .filter_map(|node| node.tokens().first().map(|token| token.text_range().start))
+        .collect();
+    let mut byte = 0;
+    let bytes: Vec<usize> = source
+        .chars()
+        .map(|c| {
+            byte += c.len_utf8();
+            byte - c.len_utf8()
+        })
+        .collect();
+    let mut spans = highlight(source)?;
+    for span in &mut spans {
+        if span.class == Class::Identifier && declared.contains(&bytes[span.range.start]) {
+            span.class = Class::Declaration;
+        }
+    }
+    Ok(spans)
+}
+
+/// Whether the identifier, or the pattern it sits in, is a binding.
+fn declares(node: &SyntaxNode) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let children = parent.children();
+    let first = children[0].text_range() == node.text_range();
+    let last = children[children.len() - 1].text_range() == node.text_range();
+    match parent.kind() {
+        SyntaxKind::VariableDeclarator | SyntaxKind::LabeledStatement => first,
+        SyntaxKind::FunctionDeclaration
+        | SyntaxKind::FunctionExpression
+        | SyntaxKind::CatchClause => true,
+        // The key of a method and the expression body of an arrow are not.
+        SyntaxKind::ObjectMethod => !first,
+        SyntaxKind::ArrowFunctionExpression => !last,
+        SyntaxKind::ArrayPattern => declares(&parent),
+        SyntaxKind::AssignmentPattern => first && declares(&parent),
+        // The value, or the key of a shorthand.
+        SyntaxKind::ObjectPatternProperty => last && declares(&parent),
+        SyntaxKind::ObjectPattern => declares(&parent),
+        _ => false,
+    }
+}
+
+/// Renders `spans` over `source` with ANSI colours.
+pub fn render_ansi(source: &str, spans: &[Span]) -> String {
+    render(source, spans, |out, class, text| match class.ansi() {
+        Some(color) => out.push_str(&format!("\x1b[{color}m{text}\x1b[0m")),
+        None => out.push_str(text),
+    })
+}
+
+/// Renders `spans` over `source` as HTML; wrap the result in a `<pre>`.
+pub fn render_html(source: &str, spans: &[Span]) -> String {
+    render(source, spans, |out, class, text| {
+        out.push_str(&format!(
+            "<span class=\"{}\">{}</span>",
+            class.css_class(),
+            escape_html(text)
+        ))
+    })
+}
+
+fn render(source: &str, spans: &[Span], mut token: impl FnMut(&mut String, Class, &str)) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut out = String::new();
+    let mut pos = 0;
+    for span in spans {
+        let gap: String = chars[pos..span.range.start].iter().collect();
+        out.push_str(&gap);
+        let text: String = chars[span.range.clone()].iter().collect();
+        token(&mut out, span.class, &text);
+        pos = span.range.end;
+    }
+    let rest: String = chars[pos..].iter().collect();
+    out.push_str(&rest);
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test_highlight {
+    use super::*;
+
+    fn classes(spans: &[Span], source: &str) -> Vec<(String, Class)> {
+        let chars: Vec<char> = source.chars().collect();
+        spans
+            .iter()
+            .map(|span| (chars[span.range.clone()].iter().collect(), span.class))
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() -> Result<(), String> {
+        let source = "if (a) x = /r/g; // é\nf(`t`, 1, 'é', true)";
+        let spans = highlight(source)?;
+        assert_eq!(
+            classes(&spans, source),
+            [
+                ("if", Class::Keyword),
+                ("(", Class::Punctuator),
+                ("a", Class::Identifier),
+                (")", Class::Punctuator),
+                ("x", Class::Identifier),
+                ("=", Class::Punctuator),
+                ("/r/g", Class::Regex),
+                (";", Class::Punctuator),
+                ("// é", Class::Comment),
+                ("f", Class::Identifier),
+                ("(", Class::Punctuator),
+                ("`t`", Class::Template),
+                (",", Class::Punctuator),
+                ("1", Class::Number),
+                (",", Class::Punctuator),
+                ("'é'", Class::String),
+                (",", Class::Punctuator),
+                ("true", Class::Keyword),
+                (")", Class::Punctuator),
+            ]
+            .map(|(text, class)| (text.to_string(), class))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_semantic() -> Result<(), String> {
+        let source = "let é = 1, f = ({a, b: [c], d = e}) => e;\n\
+                      function g(h, i = j) { return h; }\n\
+                      x: for (const k in l) break x;\n\
+                      o = { m(n) {}, p: q => r }; [s] = t;";
+        let declared: Vec<String> = classes(&highlight_semantic(source)?, source)
+            .into_iter()
+            .filter(|(_, class)| *class == Class::Declaration)
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(
+            declared,
+            ["é", "f", "a", "c", "d", "g", "h", "i", "x", "k", "n", "q"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<(), String> {
+        let source = "a <b; /* c */";
+        let spans = highlight(source)?;
+        assert_eq!(
+            render_html(source, &spans),
+            "<span class=\"js-identifier\">a</span> <span class=\"js-punctuator\">&lt;</span>\
+             <span class=\"js-identifier\">b</span><span class=\"js-punctuator\">;</span> \
+             <span class=\"js-comment\">/* c */</span>"
+        );
+        assert_eq!(
+            render_ansi("let a", &highlight("let a")?),
+            "\x1b[35mlet\x1b[0m a"
+        );
+        Ok(())
+    }
+}
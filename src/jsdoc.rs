@@ -0,0 +1,1017 @@
+//! Parses `/** ... */` comments into a description and tags, with a small
+//! parser for Closure-style type expressions like `{Array<string>|null}`.
+//! [`collect`] links each JSDoc comment to the function declaration,
+//! variable declaration or object property it leads.
+
+use crate::lex::Loc;
+use crate::node::{
+    ArrowFunctionBody, AttachedComments, BlockStatement, CatchClause, Comment, CommentKind,
+    Declaration, Expression, ForInLeft, ForInit, Function, ModuleItem, ObjectMember, Pattern,
+    Program, Statement, VariableDeclaration,
+};
+use crate::unicode::{is_identifier_part, is_identifier_start};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsDoc {
+    pub description: String,
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    /// `@param {T} name description`, `[name]` or `[name=default]`; also
+    /// written `@arg` or `@argument`.
+    Param {
+        type_expression: Option<TypeExpression>,
+        name: String,
+        optional: bool,
+        default: Option<String>,
+        description: String,
+    },
+    /// `@returns {T} description`, also written `@return`.
+    Returns {
+        type_expression: Option<TypeExpression>,
+        description: String,
+    },
+    Type {
+        type_expression: TypeExpression,
+    },
+    Typedef {
+        type_expression: Option<TypeExpression>,
+        name: String,
+    },
+    /// `@template {Constraint} T, U`
+    Template {
+        constraint: Option<TypeExpression>,
+        names: Vec<String>,
+    },
+    Deprecated {
+        description: String,
+    },
+    /// Any other tag, with its text unparsed.
+    Custom {
+        name: String,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpression {
+    /// `*`
+    Any,
+    /// `?` on its own.
+    Unknown,
+    /// `string`, `ns.Type`
+    Name(String),
+    /// A string or number literal type, as written.
+    Literal(String),
+    /// `Array<string>` or `Array.<string>`
+    Generic {
+        base: Box<TypeExpression>,
+        arguments: Vec<TypeExpression>,
+    },
+    /// `A|B`
+    Union(Vec<TypeExpression>),
+    /// `T[]`
+    Array(Box<TypeExpression>),
+    /// `?T`
+    Nullable(Box<TypeExpression>),
+    /// `!T`
+    NonNullable(Box<TypeExpression>),
+    /// `T=`
+    Optional(Box<TypeExpression>),
+    /// `...T`
+    Rest(Box<TypeExpression>),
+    /// `{a: number, b}`
+    Record(Vec<(String, Option<TypeExpression>)>),
+    /// `function(string, number): boolean`
+    Function {
+        params: Vec<TypeExpression>,
+        result: Option<Box<TypeExpression>>,
+    },
+}
+
+/// A node preceded by a JSDoc comment.
+#[derive(Debug)]
+pub struct Documented<'a> {
+    pub target: Target<'a>,
+    pub comment: &'a Comment,
+    pub doc: Result<JsDoc, String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Target<'a> {
+    Function(&'a Function),
+    Variable(&'a VariableDeclaration),
+    Property(&'a ObjectMember),
+}
+
+/// `/** */` comments are JSDoc, `/*** */` and `/**/` are not.
+pub fn is_jsdoc(comment: &Comment) -> bool {
+    comment.kind == CommentKind::Block
+        && comment.value.starts_with('*')
+        && !comment.value.starts_with("**")
+}
+
+/// Parses the value of a JSDoc comment, the text between `/*` and `*/`.
+pub fn parse(value: &str) -> Result<JsDoc, String> {
+    let lines: Vec<&str> = value
+        .strip_prefix('*')
+        .unwrap_or(value)
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            match line.strip_prefix('*') {
+                Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+                None => line,
+            }
+        })
+        .collect();
+    let mut blocks: Vec<Vec<&str>> = vec![vec![]];
+    for line in lines {
+        if line.starts_with('@') {
+            blocks.push(vec![]);
+        }
+        blocks.last_mut().unwrap().push(line);
+    }
+    let description = blocks.remove(0).join("\n").trim().to_string();
+    let tags = blocks
+        .iter()
+        .map(|block| parse_tag(&block.join("\n")))
+        .collect::<Result<_, _>>()?;
+    Ok(JsDoc { description, tags })
+}
+
+fn parse_tag(text: &str) -> Result<Tag, String> {
+    let text = &text[1..];
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let (name, rest) = text.split_at(end);
+    let mut rest = Cursor::new(rest);
+    Ok(match name {
+        "param" | "arg" | "argument" => {
+            let type_expression = rest.type_expression()?;
+            let (name, optional, default) = rest.param_name()?;
+            let _ = rest.eat("-");
+            Tag::Param {
+                type_expression,
+                name,
+                optional,
+                default,
+                description: rest.text(),
+            }
+        }
+        "returns" | "return" => Tag::Returns {
+            type_expression: rest.type_expression()?,
+            description: rest.text(),
+        },
+        "type" => Tag::Type {
+            type_expression: rest.type_expression()?.ok_or("`@type` expects a {type}")?,
+        },
+        "typedef" => Tag::Typedef {
+            type_expression: rest.type_expression()?,
+            name: rest.word().ok_or("`@typedef` expects a name")?,
+        },
+        "template" => {
+            let constraint = rest.type_expression()?;
+            let mut names = vec![rest.word().ok_or("`@template` expects a name")?];
+            while rest.eat(",") {
+                names.push(rest.word().ok_or("`@template` expects a name after `,`")?);
+            }
+            Tag::Template { constraint, names }
+        }
+        "deprecated" => Tag::Deprecated {
+            description: rest.text(),
+        },
+        "" => return Err("Expected a tag name after `@`".to_string()),
+        _ => Tag::Custom {
+            name: name.to_string(),
+            text: rest.text(),
+        },
+    })
+}
+
+/// Parses a type expression, without the surrounding braces.
+pub fn parse_type(text: &str) -> Result<TypeExpression, String> {
+    let mut cursor = Cursor::new(text);
+    let result = cursor.union()?;
+    cursor.skip_whitespace();
+    match cursor.peek() {
+        None => Ok(result),
+        Some(c) => Err(format!("Unexpected `{c}` in type expression")),
+    }
+}
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(text: &str) -> Self {
+        Cursor {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace, then consumes `token` if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let matches = token
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c));
+        if matches {
+            self.pos += token.chars().count();
+        }
+        matches
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(match self.peek() {
+            Some(c) => format!("Expected `{token}` but found `{c}` in type expression"),
+            None => format!("Expected `{token}` at the end of the type expression"),
+        })
+    }
+
+    /// The rest of the text, trimmed.
+    fn text(&mut self) -> String {
+        let text: String = self.chars[self.pos..].iter().collect();
+        self.pos = self.chars.len();
+        text.trim().to_string()
+    }
+
+    /// The next run of non-whitespace, stopping at `,`.
+    fn word(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && c != ',') {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| self.chars[start..self.pos].iter().collect())
+    }
+
+    /// A `{type}` if one comes next; the braces may nest.
+    fn type_expression(&mut self) -> Result<Option<TypeExpression>, String> {
+        if !self.eat("{") {
+            return Ok(None);
+        }
+        let start = self.pos;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Some('{') => depth += 1,
+                Some('}') => depth -= 1,
+                Some(_) => {}
+                None => return Err("Unterminated {type}".to_string()),
+            }
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos - 1].iter().collect();
+        parse_type(&text).map(Some)
+    }
+
+    /// `name`, `[name]` or `[name=default]`.
+    fn param_name(&mut self) -> Result<(String, bool, Option<String>), String> {
+        if !self.eat("[") {
+            let name = self.word().ok_or("`@param` expects a name")?;
+            return Ok((name, false, None));
+        }
+        let start = self.pos;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Some('[') => depth += 1,
+                Some(']') => depth -= 1,
+                Some(_) => {}
+                None => return Err("Unterminated [name]".to_string()),
+            }
+            self.pos += 1;
+        }
+        let inner: String = self.chars[start..self.pos - 1].iter().collect();
+        let (name, default) = match inner.split_once('=') {
+            Some((name, default)) => (name, Some(default.trim().to_string())),
+            None => (inner.as_str(), None),
+        };
+        Ok((name.trim().to_string(), true, default))
+    }
+
+    fn union(&mut self) -> Result<TypeExpression, String> {
+        let mut types = vec![self.prefix()?];
+        while self.eat("|") {
+            types.push(self.prefix()?);
+        }
+        Ok(match types.len() {
+            1 => types.remove(0),
+            _ => TypeExpression::Union(types),
+        })
+    }
+
+    fn prefix(&mut self) -> Result<TypeExpression, String> {
+        if self.eat("...") {
+            return Ok(TypeExpression::Rest(Box::new(self.prefix()?)));
+        }
+        if self.eat("!") {
+            return Ok(TypeExpression::NonNullable(Box::new(self.prefix()?)));
+        }
+        if self.eat("?") {
+            self.skip_whitespace();
+            if self
+                .peek()
+                .is_none_or(|c| matches!(c, '|' | ',' | '>' | ')' | ']' | '}' | '='))
+            {
+                return self.postfix(TypeExpression::Unknown);
+            }
+            return Ok(TypeExpression::Nullable(Box::new(self.prefix()?)));
+        }
+        let primary = self.primary()?;
+        self.postfix(primary)
+    }
+
+    fn postfix(&mut self, mut result: TypeExpression) -> Result<TypeExpression, String> {
+        loop {
+            if self.eat("[]") {
+                result = TypeExpression::Array(Box::new(result));
+            } else if self.eat("=") {
+                result = TypeExpression::Optional(Box::new(result));
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<TypeExpression, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Ok(TypeExpression::Any)
+            }
+            Some('(') => {
+                self.pos += 1;
+                let result = self.union()?;
+                self.expect(")")?;
+                Ok(result)
+            }
+            Some('{') => {
+                self.pos += 1;
+                self.record()
+            }
+            Some(quote @ ('"' | '\'')) => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c != quote) {
+                    self.pos += 1;
+                }
+                self.expect(&quote.to_string())?;
+                Ok(TypeExpression::Literal(
+                    self.chars[start..self.pos].iter().collect(),
+                ))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                let start = self.pos;
+                self.pos += 1;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.')
+                {
+                    self.pos += 1;
+                }
+                Ok(TypeExpression::Literal(
+                    self.chars[start..self.pos].iter().collect(),
+                ))
+            }
+            Some(c) if is_identifier_start(c) => {
+                let name = self.name();
+                if name == "function" && self.eat("(") {
+                    return self.function();
+                }
+                let base = TypeExpression::Name(name);
+                if self.eat(".<") || self.eat("<") {
+                    let arguments = self.list(">")?;
+                    return Ok(TypeExpression::Generic {
+                        base: Box::new(base),
+                        arguments,
+                    });
+                }
+                Ok(base)
+            }
+            Some(c) => Err(format!("Unexpected `{c}` in type expression")),
+            None => Err("Unexpected end of type expression".to_string()),
+        }
+    }
+
+    /// A dotted name; stops before the `.` of `Array.<T>`.
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            let dot = c == '.'
+                && self
+                    .chars
+                    .get(self.pos + 1)
+                    .is_some_and(|&c| is_identifier_start(c));
+            if !dot && !is_identifier_part(c) {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Comma-separated types up to `close`, which is consumed.
+    fn list(&mut self, close: &str) -> Result<Vec<TypeExpression>, String> {
+        let mut types = vec![];
+        if self.eat(close) {
+            return Ok(types);
+        }
+        loop {
+            types.push(self.union()?);
+            if self.eat(close) {
+                return Ok(types);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn function(&mut self) -> Result<TypeExpression, String> {
+        let params = self.list(")")?;
+        let result = match self.eat(":") {
+            true => Some(Box::new(self.prefix()?)),
+            false => None,
+        };
+        Ok(TypeExpression::Function { params, result })
+    }
+
+    fn record(&mut self) -> Result<TypeExpression, String> {
+        let mut fields = vec![];
+        if self.eat("}") {
+            return Ok(TypeExpression::Record(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.name();
+            if key.is_empty() {
+                return Err("Expected a field name in record type".to_string());
+            }
+            let value = match self.eat(":") {
+                true => Some(self.union()?),
+                false => None,
+            };
+            fields.push((key, value));
+            if self.eat("}") {
+                return Ok(TypeExpression::Record(fields));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+impl Display for TypeExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        /// Unions need parentheses inside the prefix and postfix forms.
+        fn operand(f: &mut Formatter<'_>, inner: &TypeExpression) -> std::fmt::Result {
+            match inner {
+                TypeExpression::Union(_) => write!(f, "({inner})"),
+                _ => write!(f, "{inner}"),
+            }
+        }
+        fn list(f: &mut Formatter<'_>, types: &[TypeExpression]) -> std::fmt::Result {
+            for (i, item) in types.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{item}")?;
+            }
+            Ok(())
+        }
+        match self {
+            TypeExpression::Any => write!(f, "*"),
+            TypeExpression::Unknown => write!(f, "?"),
+            TypeExpression::Name(name) | TypeExpression::Literal(name) => write!(f, "{name}"),
+            TypeExpression::Generic { base, arguments } => {
+                write!(f, "{base}<")?;
+                list(f, arguments)?;
+                write!(f, ">")
+            }
+            TypeExpression::Union(types) => {
+                for (i, item) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "|")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            TypeExpression::Array(inner) => {
+                operand(f, inner)?;
+                write!(f, "[]")
+            }
+            TypeExpression::Nullable(inner) => {
+                write!(f, "?")?;
+                operand(f, inner)
+            }
+            TypeExpression::NonNullable(inner) => {
+                write!(f, "!")?;
+                operand(f, inner)
+            }
+            TypeExpression::Optional(inner) => {
+                operand(f, inner)?;
+                write!(f, "=")
+            }
+            TypeExpression::Rest(inner) => {
+                write!(f, "...")?;
+                operand(f, inner)
+            }
+            TypeExpression::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key}")?;
+                    if let Some(value) = value {
+                        write!(f, ": {value}")?;
+                    }
+                }
+                write!(f, "}}")
+            }
+            TypeExpression::Function { params, result } => {
+                write!(f, "function(")?;
+                list(f, params)?;
+                write!(f, ")")?;
+                match result {
+                    Some(result) => write!(f, ": {result}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Every documented node of a parsed program, in source order. The JSDoc
+/// comment is the last leading comment of the node.
+pub fn collect(program: &Program) -> Vec<Documented<'_>> {
+    let mut collector = Collector {
+        attached: &program.attached_comments,
+        found: vec![],
+    };
+    for ModuleItem::Statement(statement) in &program.body {
+        collector.statement(statement);
+    }
+    collector.found
+}
+
+/// Compares the top-level `@param` names with the function's parameters.
+/// Dotted names document properties and destructured parameters accept any
+/// name.
+pub fn check_params(function: &Function, doc: &JsDoc) -> Vec<String> {
+    let documented: Vec<&str> = doc
+        .tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Param { name, .. } if !name.contains('.') => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut problems = vec![];
+    for i in 0..documented.len().max(function.params.len()) {
+        let param = function.params.get(i).map(|param| match param {
+            Pattern::Identifier(identifier) => Some(identifier.name.as_str()),
+            Pattern::AssignmentPattern { left, .. } => match left.as_ref() {
+                Pattern::Identifier(identifier) => Some(identifier.name.as_str()),
+                _ => None,
+            },
+            _ => None,
+        });
+        match (documented.get(i), param) {
+            (Some(name), Some(Some(param))) if name != &param => problems.push(format!(
+                "`@param {name}` does not match parameter `{param}`"
+            )),
+            (Some(name), None) => {
+                problems.push(format!("`@param {name}` has no matching parameter"))
+            }
+            (None, Some(Some(param))) => {
+                problems.push(format!("Parameter `{param}` is not documented"))
+            }
+            _ => {}
+        }
+    }
+    problems
+}
+
+struct Collector<'a> {
+    attached: &'a AttachedComments,
+    found: Vec<Documented<'a>>,
+}
+
+impl<'a> Collector<'a> {
+    fn add(&mut self, target: Target<'a>, loc: &'a Loc) {
+        let comment = self
+            .attached
+            .get(&(loc.start.index, loc.end.index))
+            .and_then(|comments| comments.leading_comments.last());
+        if let Some(comment) = comment.filter(|comment| is_jsdoc(comment)) {
+            self.found.push(Documented {
+                target,
+                comment,
+                doc: parse(&comment.value),
+            });
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::BlockStatement(block) => self.block(block),
+            Statement::Declaration(Declaration::FunctionDeclaration(function)) => {
+                self.add(Target::Function(function), &function.loc);
+                self.function(function);
+            }
+            Statement::Declaration(Declaration::VariableDeclaration(declaration)) => {
+                self.add(Target::Variable(declaration), &declaration.loc);
+                self.declaration(declaration);
+            }
+            Statement::ExpressionStatement {
+                expression: argument,
+                ..
+            }
+            | Statement::ThrowStatement { argument, .. } => self.expression(argument),
+            Statement::ReturnStatement { argument, .. } => {
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Statement::IfStatement {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.expression(test);
+                self.statement(consequent);
+                if let Some(alternate) = alternate {
+                    self.statement(alternate);
+                }
+            }
+            Statement::ForStatement {
+                init,
+                test,
+                update,
+                body,
+                ..
+            } => {
+                match init {
+                    Some(ForInit::VariableDeclaration(declaration)) => {
+                        self.declaration(declaration)
+                    }
+                    Some(ForInit::Expression(expression)) => self.expression(expression),
+                    None => {}
+                }
+                for expression in test.iter().chain(update) {
+                    self.expression(expression);
+                }
+                self.statement(body);
+            }
+            Statement::ForInStatement {
+                left, right, body, ..
+            } => {
+                match left {
+                    ForInLeft::VariableDeclaration(declaration) => self.declaration(declaration),
+                    ForInLeft::Pattern(pattern) => self.pattern(pattern),
+                }
+                self.expression(right);
+                self.statement(body);
+            }
+            Statement::WhileStatement { test, body, .. }
+            | Statement::DoWhileStatement { test, body, .. }
+            | Statement::WithStatement {
+                object: test, body, ..
+            } => {
+                self.expression(test);
+                self.statement(body);
+            }
+            Statement::TryStatement {
+                block,
+                handler,
+                finalizer,
+                ..
+            } => {
+                self.block(block);
+                if let Some(CatchClause { param, body, .. }) = handler {
+                    if let Some(param) = param {
+                        self.pattern(param);
+                    }
+                    self.block(body);
+                }
+                if let Some(finalizer) = finalizer {
+                    self.block(finalizer);
+                }
+            }
+            Statement::SwitchStatement {
+                discriminant,
+                cases,
+                ..
+            } => {
+                self.expression(discriminant);
+                for case in cases {
+                    if let Some(test) = &case.test {
+                        self.expression(test);
+                    }
+                    for statement in &case.consequent {
+                        self.statement(statement);
+                    }
+                }
+            }
+            Statement::LabeledStatement { body, .. } => self.statement(body),
+            Statement::EmptyStatement { .. }
+            | Statement::Invalid { .. }
+            | Statement::BreakStatement { .. }
+            | Statement::ContinueStatement { .. } => {}
+        }
+    }
+
+    fn block(&mut self, block: &'a BlockStatement) {
+        for statement in &block.body {
+            self.statement(statement);
+        }
+    }
+
+    fn declaration(&mut self, declaration: &'a VariableDeclaration) {
+        for declarator in &declaration.declarations {
+            self.pattern(&declarator.id);
+            if let Some(init) = &declarator.init {
+                self.expression(init);
+            }
+        }
+    }
+
+    fn function(&mut self, function: &'a Function) {
+        for param in &function.params {
+            self.pattern(param);
+        }
+        self.block(&function.body);
+    }
+
+    fn expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::FunctionExpression(function) => self.function(function),
+            Expression::ArrowFunctionExpression { params, body, .. } => {
+                for param in params {
+                    self.pattern(param);
+                }
+                match body {
+                    ArrowFunctionBody::BlockStatement(block) => self.block(block),
+                    ArrowFunctionBody::Expression(expression) => self.expression(expression),
+                }
+            }
+            Expression::ObjectExpression { properties, .. } => {
+                for property in properties {
+                    match property {
+                        ObjectMember::ObjectProperty { value, loc, .. } => {
+                            self.add(Target::Property(property), loc);
+                            self.expression(value);
+                        }
+                        ObjectMember::ObjectMethod { function, loc, .. } => {
+                            self.add(Target::Property(property), loc);
+                            self.function(function);
+                        }
+                    }
+                }
+            }
+            Expression::TemplateLiteral { expressions, .. }
+            | Expression::SequenceExpression { expressions, .. } => {
+                for expression in expressions {
+                    self.expression(expression);
+                }
+            }
+            Expression::ArrayExpression { elements, .. } => {
+                for element in elements.iter().flatten() {
+                    self.expression(element);
+                }
+            }
+            Expression::AssignmentExpression { left, right, .. } => {
+                self.pattern(left);
+                self.expression(right);
+            }
+            Expression::BinaryExpression { left, right, .. }
+            | Expression::LogicalExpression { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::UnaryExpression { argument, .. }
+            | Expression::UpdateExpression { argument, .. }
+            | Expression::AwaitExpression { argument, .. } => self.expression(argument),
+            Expression::MemberExpression(member) => {
+                self.expression(&member.object);
+                self.expression(&member.property);
+            }
+            Expression::ConditionalExpression {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                self.expression(test);
+                self.expression(consequent);
+                self.expression(alternate);
+            }
+            Expression::CallExpression {
+                callee, arguments, ..
+            }
+            | Expression::NewExpression {
+                callee, arguments, ..
+            } => {
+                self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::ParenthesizedExpression { expression, .. } => self.expression(expression),
+            Expression::Identifier(_)
+            | Expression::ThisExpression { .. }
+            | Expression::NullLiteral { .. }
+            | Expression::BooleanLiteral { .. }
+            | Expression::NumericLiteral(_)
+            | Expression::BigIntLiteral { .. }
+            | Expression::StringLiteral(_)
+            | Expression::RegExpLiteral { .. } => {}
+        }
+    }
+
+    /// Only default values and computed members can hold documented nodes.
+    fn pattern(&mut self, pattern: &'a Pattern) {
+        match pattern {
+            Pattern::ObjectPattern { properties, .. } => {
+                for property in properties {
+                    self.pattern(&property.value);
+                }
+            }
+            Pattern::ArrayPattern { elements, .. } => {
+                for element in elements.iter().flatten() {
+                    self.pattern(element);
+                }
+            }
+            Pattern::AssignmentPattern { left, right, .. } => {
+                self.pattern(left);
+                self.expression(right);
+            }
+            Pattern::MemberExpression(member) => {
+                self.expression(&member.object);
+                self.expression(&member.property);
+            }
+            Pattern::Identifier(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_jsdoc {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let doc = parse(
+            "*\n * Adds two numbers.\n *\n * Really.\n * @param {number} a - the first\n \
+             *   spanning lines\n * @param {number=} [b=1]\n * @returns {number} the sum\n \
+             * @deprecated use plus\n * @since 1.2\n ",
+        )?;
+        assert_eq!(doc.description, "Adds two numbers.\n\nReally.");
+        assert_eq!(
+            doc.tags,
+            vec![
+                Tag::Param {
+                    type_expression: Some(TypeExpression::Name("number".to_string())),
+                    name: "a".to_string(),
+                    optional: false,
+                    default: None,
+                    description: "the first\n  spanning lines".to_string(),
+                },
+                Tag::Param {
+                    type_expression: Some(TypeExpression::Optional(Box::new(
+                        TypeExpression::Name("number".to_string())
+                    ))),
+                    name: "b".to_string(),
+                    optional: true,
+                    default: Some("1".to_string()),
+                    description: String::new(),
+                },
+                Tag::Returns {
+                    type_expression: Some(TypeExpression::Name("number".to_string())),
+                    description: "the sum".to_string(),
+                },
+                Tag::Deprecated {
+                    description: "use plus".to_string(),
+                },
+                Tag::Custom {
+                    name: "since".to_string(),
+                    text: "1.2".to_string(),
+                },
+            ]
+        );
+        let doc = parse("* @typedef {{x: number, y}} Point\n * @template {string} K, V ")?;
+        assert_eq!(
+            doc.tags[0],
+            Tag::Typedef {
+                type_expression: Some(parse_type("{x: number, y}")?),
+                name: "Point".to_string(),
+            }
+        );
+        assert_eq!(
+            doc.tags[1],
+            Tag::Template {
+                constraint: Some(TypeExpression::Name("string".to_string())),
+                names: vec!["K".to_string(), "V".to_string()],
+            }
+        );
+        assert!(parse("* @type").is_err());
+        assert!(parse("* @param {Array<} a").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_type() -> Result<(), String> {
+        assert_eq!(
+            parse_type("Array<string>|null")?,
+            TypeExpression::Union(vec![
+                TypeExpression::Generic {
+                    base: Box::new(TypeExpression::Name("Array".to_string())),
+                    arguments: vec![TypeExpression::Name("string".to_string())],
+                },
+                TypeExpression::Name("null".to_string()),
+            ])
+        );
+        for (input, expected) in [
+            ("Array.<string>", "Array<string>"),
+            ("Object<string, ns.Type>", "Object<string, ns.Type>"),
+            ("(string | number)[]", "(string|number)[]"),
+            ("?string", "?string"),
+            ("!Node", "!Node"),
+            ("?", "?"),
+            ("*", "*"),
+            ("...number", "...number"),
+            (
+                "function(string, ?): boolean",
+                "function(string, ?): boolean",
+            ),
+            ("{a: number, b}", "{a: number, b}"),
+            ("'a'|\"b\"|1", "'a'|\"b\"|1"),
+            ("Map<K, V[]>=", "Map<K, V[]>="),
+        ] {
+            assert_eq!(parse_type(input)?.to_string(), expected, "{input}");
+        }
+        for input in ["", "Array<", "a b", "{a:}", "(a"] {
+            assert!(parse_type(input).is_err(), "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect() -> Result<(), String> {
+        let source = "/** Adds. @param a */\n\
+                      /**\n * @param {number} a\n * @param {number} b\n */\n\
+                      function add(a, c) { return a + c; }\n\
+                      /** @type {number} */ let x = 1;\n\
+                      /* not jsdoc */ let y = 2;\n\
+                      let o = {\n  /** The key. */\n  key: 1,\n  /** @param {string} s */\n  method(s) {},\n};";
+        let program = Parser::new(source.to_string())?.parse()?;
+        let found = collect(&program);
+        assert_eq!(found.len(), 4);
+        let Target::Function(function) = found[0].target else {
+            panic!("expected a function");
+        };
+        let doc = found[0].doc.clone()?;
+        assert_eq!(doc.tags.len(), 2);
+        assert_eq!(
+            check_params(function, &doc),
+            ["`@param b` does not match parameter `c`"]
+        );
+        assert!(matches!(found[1].target, Target::Variable(_)));
+        assert_eq!(
+            found[1].doc.clone()?.tags,
+            [Tag::Type {
+                type_expression: TypeExpression::Name("number".to_string()),
+            }]
+        );
+        assert!(matches!(found[2].target, Target::Property(_)));
+        assert_eq!(found[2].doc.clone()?.description, "The key.");
+        let Target::Property(ObjectMember::ObjectMethod { function, .. }) = found[3].target else {
+            panic!("expected a method");
+        };
+        assert!(check_params(function, &found[3].doc.clone()?).is_empty());
+        Ok(())
+    }
+}
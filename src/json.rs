@@ -0,0 +1,455 @@
+/// A JSON value. Object members keep their insertion order so serialized
+/// output is stable and can be diffed against fixtures.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Same layout as `JSON.stringify(value, null, 2)`.
+    pub fn to_string_pretty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        out
+    }
+
+    /// Same layout as `JSON.stringify(value)`.
+    pub fn to_string_compact(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, None);
+        out
+    }
+
+    /// `depth` is `None` for compact output.
+    fn write(&self, out: &mut String, depth: Option<usize>) {
+        let inner = depth.map(|depth| depth + 1);
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Number(n) => out.push_str(&format_number(*n)),
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, inner);
+                    item.write(out, inner);
+                }
+                newline(out, depth);
+                out.push(']');
+            }
+            Json::Object(members) if members.is_empty() => out.push_str("{}"),
+            Json::Object(members) => {
+                out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, inner);
+                    write_string(out, key);
+                    out.push_str(if depth.is_some() { ": " } else { ":" });
+                    value.write(out, inner);
+                }
+                newline(out, depth);
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl Json {
+    /// Parses a JSON document (RFC 8259). Errors carry the line and column.
+    pub fn parse(input: &str) -> Result<Json, String> {
+        let mut reader = Reader {
+            chars: input.chars().collect(),
+            index: 0,
+            depth: 0,
+        };
+        let value = reader.read_value()?;
+        reader.skip_whitespace();
+        if reader.index < reader.chars.len() {
+            return Err(reader.error("unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+/// How deep arrays and objects may nest, so that reading a hostile
+/// document fails instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct Reader {
+    chars: Vec<char>,
+    index: usize,
+    /// The arrays and objects open at `index`.
+    depth: usize,
+}
+
+impl Reader {
+    fn error(&self, message: &str) -> String {
+        let before = &self.chars[..self.index.min(self.chars.len())];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        format!("{message} at line {line} column {column}")
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.index += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expect {c}")));
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    fn read_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{' | '[') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("nesting too deep"));
+                }
+                self.depth += 1;
+                let value = match self.peek() {
+                    Some('{') => self.read_object(),
+                    _ => self.read_array(),
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Json::String(self.read_string()?)),
+            Some('-' | '0'..='9') => self.read_number(),
+            Some('t') => self.read_keyword("true", Json::Bool(true)),
+            Some('f') => self.read_keyword("false", Json::Bool(false)),
+            Some('n') => self.read_keyword("null", Json::Null),
+            Some(c) => Err(self.error(&format!("unexpected character {c:?}"))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn read_keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for c in word.chars() {
+            if self.peek() != Some(c) {
+                return Err(self.error(&format!("expect {word}")));
+            }
+            self.index += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.index += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expect string key"));
+            }
+            let key = self.read_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            members.push((key, self.read_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.index += 1,
+                Some('}') => {
+                    self.index += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error("expect , or }")),
+            }
+        }
+    }
+
+    fn read_array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.index += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.read_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.index += 1,
+                Some(']') => {
+                    self.index += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expect , or ]")),
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.index += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = self.peek();
+                    self.index += 1;
+                    match escaped {
+                        Some('"') => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some('/') => s.push('/'),
+                        Some('b') => s.push('\u{8}'),
+                        Some('f') => s.push('\u{c}'),
+                        Some('n') => s.push('\n'),
+                        Some('r') => s.push('\r'),
+                        Some('t') => s.push('\t'),
+                        Some('u') => s.push(self.read_unicode_escape()?),
+                        _ => {
+                            self.index -= 1;
+                            return Err(self.error("invalid escape"));
+                        }
+                    }
+                }
+                c if (c as u32) < 0x20 => {
+                    self.index -= 1;
+                    return Err(self.error("control character in string"));
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Reads the `XXXX` of a `\uXXXX` escape, joining surrogate pairs.
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.read_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("lone surrogate"));
+        }
+        if self.peek() != Some('\\') || self.chars.get(self.index + 1) != Some(&'u') {
+            return Err(self.error("lone surrogate"));
+        }
+        self.index += 2;
+        let low = self.read_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("lone surrogate"));
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.error("invalid surrogate pair"))
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("invalid unicode escape"))?;
+            value = value * 16 + digit;
+            self.index += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_number(&mut self) -> Result<Json, String> {
+        let start = self.index;
+        if self.peek() == Some('-') {
+            self.index += 1;
+        }
+        match self.peek() {
+            Some('0') => self.index += 1,
+            Some('1'..='9') => self.skip_digits(),
+            _ => return Err(self.error("invalid number")),
+        }
+        if self.peek() == Some('.') {
+            self.index += 1;
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.skip_digits();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.index += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.index += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.skip_digits();
+        }
+        let text: String = self.chars[start..self.index].iter().collect();
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn skip_digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.index += 1;
+        }
+    }
+}
+
+fn newline(out: &mut String, depth: Option<usize>) {
+    if let Some(depth) = depth {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats a number the way JavaScript's `Number.prototype.toString` does.
+pub fn format_number(n: f64) -> String {
+    if !n.is_finite() {
+        return "null".to_string();
+    }
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    let abs = n.abs();
+    if !(1e-6..1e21).contains(&abs) {
+        // Rust writes `1e21`, JavaScript writes `1e+21`
+        let s = format!("{n:e}");
+        return if s.contains("e-") {
+            s
+        } else {
+            s.replace('e', "e+")
+        };
+    }
+    format!("{n}")
+}
+
+pub fn string(s: &str) -> Json {
+    Json::String(s.to_string())
+}
+
+pub fn array<T>(items: &[T], f: impl Fn(&T) -> Json) -> Json {
+    Json::Array(items.iter().map(f).collect())
+}
+
+/// `null` for `None`, like the absent children of ESTree nodes.
+pub fn optional<T>(item: Option<&T>, f: impl Fn(&T) -> Json) -> Json {
+    item.map_or(Json::Null, f)
+}
+
+#[cfg(test)]
+mod test_json {
+    use super::*;
+
+    #[test]
+    fn test_pretty() {
+        let value = Json::Object(vec![
+            (
+                "a".to_string(),
+                Json::Array(vec![Json::Null, Json::Bool(true)]),
+            ),
+            ("b".to_string(), Json::Object(vec![])),
+            ("c".to_string(), Json::String("\"x\"\n\u{1}".to_string())),
+        ]);
+        assert_eq!(
+            value.to_string_pretty(),
+            "{\n  \"a\": [\n    null,\n    true\n  ],\n  \"b\": {},\n  \"c\": \"\\\"x\\\"\\n\\u0001\"\n}"
+        );
+        assert_eq!(
+            value.to_string_compact(),
+            "{\"a\":[null,true],\"b\":{},\"c\":\"\\\"x\\\"\\n\\u0001\"}"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(16.0), "16");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(0.1), "0.1");
+        assert_eq!(format_number(1e21), "1e+21");
+        assert_eq!(format_number(1.5e-7), "1.5e-7");
+        assert_eq!(
+            format_number(123456789012345680000.0),
+            "123456789012345680000"
+        );
+        assert_eq!(format_number(f64::INFINITY), "null");
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let value = Json::parse(
+            r#" {"a": [null, true, false], "b": -1.5e3, "c": "\"\u00e9\ud83d\ude00\n", "d": {}} "#,
+        )?;
+        assert_eq!(
+            value,
+            Json::Object(vec![
+                (
+                    "a".to_string(),
+                    Json::Array(vec![Json::Null, Json::Bool(true), Json::Bool(false)])
+                ),
+                ("b".to_string(), Json::Number(-1500.0)),
+                ("c".to_string(), Json::String("\"é😀\n".to_string())),
+                ("d".to_string(), Json::Object(vec![])),
+            ])
+        );
+        assert_eq!(Json::parse(&value.to_string_pretty())?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Json::parse("[1,\n 2,]"),
+            Err("unexpected character ']' at line 2 column 4".to_string())
+        );
+        assert!(Json::parse("01").is_err());
+        assert!(Json::parse("\"\\ud800\"").is_err());
+        assert!(Json::parse("{\"a\" 1}").is_err());
+        assert!(Json::parse("[1] 2").is_err());
+        let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Json::parse(&nested(200_000)),
+            Err(format!(
+                "nesting too deep at line 1 column {}",
+                MAX_DEPTH + 1
+            ))
+        );
+    }
+}
@@ -1,25 +1,44 @@
 use crate::lex::Token::Comment;
-use crate::token::Token;
+use crate::token::{Token, Trivia, TriviaKind};
+use crate::unicode::{is_identifier_part, is_identifier_start, is_line_terminator, is_whitespace};
 use std::fmt::Display;
 
-#[derive(Debug, Clone)]
+/// `line` and `column` are 1-based, `index` is the 0-based char offset.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
+    pub index: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Loc {
     pub start: Position,
     pub end: Position,
 }
 
+const PUNCTUATORS: [&str; 35] = [
+    ">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=", "<=",
+    ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "%=", "&=", "|=", "^=", "**", "<<",
+    ">>", "=", "<", ">", "?",
+];
+
+#[derive(Debug, Clone)]
+pub struct LexState {
+    pos: usize,
+    line: usize,
+    column: usize,
+    token_start: (usize, Position),
+}
+
 pub struct Lex {
     input: String,
     pos: usize,
     line: usize,
     column: usize,
-    pub regex_allowed: bool,
+    token_start: (usize, Position),
+    /// Whether the last identifier read had a `\u{...}` escape.
+    code_point_escape: bool,
 }
 
 impl Lex {
@@ -29,345 +48,506 @@ impl Lex {
             pos: 0,
             line: 1,
             column: 1,
-            regex_allowed: true,
+            token_start: (
+                0,
+                Position {
+                    line: 1,
+                    column: 1,
+                    index: 0,
+                },
+            ),
+            code_point_escape: false,
         }
     }
 
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Whether the last token is an identifier or keyword spelled with a
+    /// `\u{...}` escape, which ES5 does not have.
+    pub fn has_code_point_escape(&self) -> bool {
+        self.code_point_escape
+    }
+
+    /// From the start of the token being read to where reading stopped;
+    /// after an error, where the error is.
+    pub fn error_loc(&self) -> Loc {
+        Loc {
+            start: self.token_start.1.clone(),
+            end: self.position(),
+        }
+    }
+
+    pub fn state(&self) -> LexState {
+        LexState {
+            pos: self.pos,
+            line: self.line,
+            column: self.column,
+            token_start: self.token_start.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, state: LexState) {
+        self.pos = state.pos;
+        self.line = state.line;
+        self.column = state.column;
+        self.token_start = state.token_start;
+    }
+
     pub fn next(&mut self) -> Result<(Token, Loc), String> {
         let str = &self.input;
         if self.pos > str.len() {
             panic!("end of source");
         }
+        if self.pos == 0 && str.starts_with("#!") {
+            self.pos = 1;
+            self.column = 2;
+            let Comment(word) = self.read_comment()? else {
+                unreachable!()
+            };
+            return Ok((
+                Token::Hashbang(word),
+                Loc {
+                    start: Position {
+                        line: 1,
+                        column: 1,
+                        index: 0,
+                    },
+                    end: self.position(),
+                },
+            ));
+        }
         loop {
-            let c = str.chars().nth(self.pos);
-            match c {
-                Some(c) => match c {
-                    ' ' => {
-                        self.column += 1;
-                    }
-                    '\t' => {
-                        println!("\\t find");
-                        self.column += 1;
-                    }
-                    '\r' => {}
-                    '\n' => {
+            match self.peek(0) {
+                Some(c) if is_line_terminator(c) => {
+                    if !(c == '\r' && self.peek(1) == Some('\n')) {
                         self.line += 1;
                         self.column = 1;
                     }
-                    _ => {
-                        break;
-                    }
-                },
-                None => break,
+                }
+                Some(c) if is_whitespace(c) => self.column += 1,
+                _ => break,
             }
             self.pos += 1;
         }
-        let start = Position {
+        let start = self.position();
+        self.token_start = (self.pos, start.clone());
+        self.code_point_escape = false;
+        let c = str.chars().nth(self.pos);
+        let result = match c {
+            Some(c) => match c {
+                '"' | '\'' => self.read_string()?,
+                '/' => self.read_divide_or_comment()?,
+                '=' | '+' | '-' | '*' | '%' | '>' | '<' | '|' | '?' | ':' | '!' | '&' | '~'
+                | '^' => self.read_operation()?,
+                '.' if self.peek(1).is_some_and(|c| c.is_ascii_digit()) => self.read_number()?,
+                ';' | '(' | ')' | '{' | '}' | '.' | ',' | '[' | ']' => {
+                    self.pos += 1;
+                    self.column += 1;
+                    Token::Control(c.to_string())
+                }
+                '0'..='9' => self.read_number()?,
+                '`' => self.read_template_str()?,
+                c if c == '\\' || is_identifier_start(c) => self.read_word()?,
+                _ => return Err(format!("Unrecognized character {}", c)),
+            },
+            None => Token::EOF,
+        };
+        let end = self.position();
+        Ok((result, Loc { start, end }))
+    }
+
+    fn position(&self) -> Position {
+        Position {
             line: self.line,
             column: self.column,
-        };
-        let mut result;
+            index: self.pos,
+        }
+    }
+
+    fn read_word(&mut self) -> Result<Token, String> {
+        let mut word = String::new();
+        let mut escaped = false;
         loop {
-            let c = str.chars().nth(self.pos);
-            match c {
-                Some(c) => match c {
-                    '"' | '\'' => {
-                        result = self.read_string()?;
-                        break;
+            let c = match self.peek(0) {
+                Some('\\') => {
+                    if self.peek(1) != Some('u') {
+                        return Err("Expecting Unicode escape sequence \\uXXXX".to_string());
                     }
-                    '/' => {
-                        result = self.read_divide_regex_comment()?;
-                        break;
-                    }
-                    '=' | '+' | '-' | '*' | '%' | '>' | '<' | '|' | '?' | ':' | '!' | '&' | '~' => {
-                        result = self.read_operation()?;
-                        break;
-                    }
-                    ';' | '(' | ')' | '{' | '}' | '.' | ',' | '[' | ']' => {
-                        self.pos += 1;
-                        self.column += 1;
-                        result = Token::Control(c.to_string());
-                        break;
-                    }
-                    '_' | '$' | 'a'..='z' | 'A'..='Z' => {
-                        result = self.read_word()?;
-                        break;
-                    }
-                    '0' => {
-                        result = self.read_binary_octal_hex_digit()?;
-                        break;
-                    }
-                    '1'..='9' => {
-                        result = self.read_digit()?;
-                        break;
-                    }
-                    '`' => {
-                        result = self.read_template_str()?;
-                        break;
+                    self.pos += 2;
+                    self.column += 2;
+                    escaped = true;
+                    self.code_point_escape |= self.peek(0) == Some('{');
+                    let c = self.read_unicode_escape_body()?;
+                    let valid = if word.is_empty() {
+                        is_identifier_start(c)
+                    } else {
+                        is_identifier_part(c)
+                    };
+                    if !valid {
+                        return Err(format!("Invalid Unicode escape in identifier: {c}"));
                     }
-                    _ => return Err(format!("Unrecognized character {}", c)),
-                },
-                None => {
-                    result = Token::EOF;
-                    break;
+                    c
                 }
-            }
+                Some(c) if is_identifier_part(c) => {
+                    self.pos += 1;
+                    self.column += 1;
+                    c
+                }
+                _ => break,
+            };
+            word.push(c);
         }
-        let end = Position {
-            line: self.line,
-            column: self.column,
-        };
-        Ok((result, Loc { start, end }))
+        // An escaped keyword is still an identifier to the lexer; the parser
+        // rejects it wherever a keyword would be read.
+        if escaped {
+            return Ok(Token::Variable(word));
+        }
+        Ok(keyword(&word).unwrap_or(Token::Variable(word)))
     }
 
-    fn read_word(&mut self) -> Result<Token, String> {
-        let c = self.input.chars().nth(self.pos).unwrap();
+    fn read_string(&mut self) -> Result<Token, String> {
+        let start = self.pos;
+        let quote = self.peek(0).unwrap();
         let mut word = String::new();
-        word.push(c);
+        self.pos += 1;
+        self.column += 1;
         loop {
-            self.pos += 1;
-            self.column += 1;
-            let d = self.input.chars().nth(self.pos);
-            match d {
-                Some(d) => match d {
-                    '_' | '$' | 'a'..='z' | 'A'..='Z' | '0'..='9' => word.push(d),
-                    _ => break,
-                },
-                None => break,
+            match self.peek(0) {
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    self.column += 1;
+                    break;
+                }
+                Some('\\') => self.read_escape(&mut word)?,
+                Some('\n' | '\r') | None => {
+                    return Err(format!("Unterminated string constant, line: {}", self.line));
+                }
+                Some(c) => {
+                    word.push(c);
+                    self.pos += 1;
+                    self.column += 1;
+                    if is_line_terminator(c) {
+                        self.line += 1;
+                        self.column = 1;
+                    }
+                }
             }
         }
-        let d = match word.as_str() {
-            "var" => Token::Var,
-            "let" => Token::Let,
-            "const" => Token::Const,
-            "undefined" => Token::Undefined,
-            "null" => Token::Null,
-            "await" => Token::Await,
-            "async" => Token::Async,
-            "function" => Token::Function,
-            "with" => Token::With,
-            "delete" => Token::Delete,
-            "if" => Token::If,
-            "switch" => Token::Switch,
-            "case" => Token::Case,
-            "default" => Token::Default,
-            "break" => Token::Break,
-            "continue" => Token::Continue,
-            "for" => Token::For,
-            "while" => Token::While,
-            "return" => Token::Return,
-            "true" => Token::True,
-            "false" => Token::False,
-            "else" => Token::Else,
-            "in" => Token::In,
-            "do" => Token::Do,
-            "try" => Token::Try,
-            "catch" => Token::Catch,
-            "finally" => Token::Finally,
-            "throw" => Token::Throw,
-            "new" => Token::New,
-            "this" => Token::This,
-            "instanceof" => Token::Instanceof,
-            "typeof" => Token::Typeof,
-            "class" => Token::Class,
-            "void" => Token::Void,
-            "yield" => Token::Yield,
-            "debugger" => Token::Debugger,
-            _ => Token::Variable(word),
+        let raw = self
+            .input
+            .chars()
+            .skip(start)
+            .take(self.pos - start)
+            .collect();
+        Ok(Token::String(word, raw))
+    }
+
+    fn read_escape(&mut self, word: &mut String) -> Result<(), String> {
+        self.pos += 1;
+        self.column += 1;
+        let c = match self.peek(0) {
+            Some(c) => c,
+            None => return Err("Unterminated string constant".to_string()),
         };
-        Ok(d)
+        self.pos += 1;
+        self.column += 1;
+        match c {
+            'n' => word.push('\n'),
+            'r' => word.push('\r'),
+            't' => word.push('\t'),
+            'b' => word.push('\u{8}'),
+            'f' => word.push('\u{c}'),
+            'v' => word.push('\u{b}'),
+            '0'..='7' => {
+                let mut code = c.to_digit(8).unwrap();
+                let max = if c <= '3' { 3 } else { 2 };
+                for _ in 1..max {
+                    match self.peek(0).and_then(|d| d.to_digit(8)) {
+                        Some(d) => {
+                            code = code * 8 + d;
+                            self.pos += 1;
+                            self.column += 1;
+                        }
+                        None => break,
+                    }
+                }
+                word.push(char::from_u32(code).unwrap());
+            }
+            'x' => {
+                let code = self.read_hex(2)?;
+                word.push(char::from_u32(code).unwrap());
+            }
+            'u' => word.push(self.read_unicode_escape_body()?),
+            '\r' => {
+                if self.peek(0) == Some('\n') {
+                    self.pos += 1;
+                }
+                self.line += 1;
+                self.column = 1;
+            }
+            '\n' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            '\u{2028}' | '\u{2029}' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => word.push(c),
+        }
+        Ok(())
     }
 
-    fn read_string(&mut self) -> Result<Token, String> {
-        let s = self.input.chars().nth(self.pos).unwrap();
-        let mut word = String::new();
-        let mut escaped = false;
-        loop {
+    /// Reads the part of a `\uXXXX` or `\u{...}` escape after the `u`.
+    /// A surrogate pair written as two escapes is combined; a lone surrogate
+    /// can't live in a Rust string and becomes U+FFFD.
+    fn read_unicode_escape_body(&mut self) -> Result<char, String> {
+        let code = if self.peek(0) == Some('{') {
             self.pos += 1;
             self.column += 1;
-            let c = self.input.chars().nth(self.pos);
-            match c {
-                Some(c) => match c {
-                    '"' | '\'' => {
-                        if c == s {
-                            if escaped {
-                                escaped = false;
-                            } else {
-                                self.pos += 1;
-                                self.column += 1;
-                                break;
-                            }
-                        }
-                        word.push(c);
-                    }
-                    'r' | 'n' | 't' => {
-                        if escaped {
-                            escaped = false;
-                            if c == 'r' {
-                                word.push('\r');
-                            }
-                            if c == 'n' {
-                                word.push('\n');
-                            }
-                            if c == 't' {
-                                word.push('\t');
-                            }
-                        } else {
-                            word.push(c);
-                        }
-                    }
-                    '\n' => {
-                        if escaped {
-                            escaped = false;
-                            word.push(c);
-                        } else {
-                            return Err(format!(
-                                "string format error, unsupported \\n, line: {}",
-                                self.line
-                            ));
-                        }
-                    }
-                    '\\' => {
-                        if escaped {
-                            escaped = false;
-                            word.push(c);
-                        } else {
-                            escaped = true;
-                        }
-                    }
-                    _ => {
-                        word.push(c);
-                        escaped = false;
-                    }
-                },
-                None => break,
+            let mut code: u32 = 0;
+            let mut digits = 0;
+            while let Some(d) = self.peek(0).and_then(|d| d.to_digit(16)) {
+                code = code.saturating_mul(16).saturating_add(d);
+                digits += 1;
+                self.pos += 1;
+                self.column += 1;
+            }
+            if digits == 0 || self.peek(0) != Some('}') || code > 0x10FFFF {
+                return Err("Invalid Unicode escape sequence".to_string());
+            }
+            self.pos += 1;
+            self.column += 1;
+            code
+        } else {
+            self.read_hex(4)?
+        };
+        if (0xD800..0xDC00).contains(&code)
+            && self.peek(0) == Some('\\')
+            && self.peek(1) == Some('u')
+        {
+            let pos = self.pos;
+            let column = self.column;
+            self.pos += 2;
+            self.column += 2;
+            match self.read_hex(4) {
+                Ok(low) if (0xDC00..0xE000).contains(&low) => {
+                    let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    return Ok(char::from_u32(code).unwrap());
+                }
+                _ => {
+                    self.pos = pos;
+                    self.column = column;
+                }
             }
         }
-        Ok(Token::String(word))
+        Ok(char::from_u32(code).unwrap_or('\u{FFFD}'))
     }
 
-    fn read_divide_regex_comment(&mut self) -> Result<Token, String> {
-        let c = self.input.chars().nth(self.pos).unwrap();
-        let mut word = String::new();
-        word.push(c);
-        loop {
+    fn read_hex(&mut self, len: usize) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..len {
+            match self.peek(0).and_then(|d| d.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return Err("Invalid hexadecimal escape sequence".to_string()),
+            }
             self.pos += 1;
             self.column += 1;
-            let d = self.input.chars().nth(self.pos);
-            match d {
-                Some(d) => match d {
-                    '/' => {
-                        return self.read_comment();
-                    }
-                    '*' => {
-                        return self.read_multiline_comment();
-                    }
-                    '=' => {
-                        if self.regex_allowed {
-                            return self.read_regex();
-                        }
-                        word.push(d);
-                    }
-                    _ => {
-                        if self.regex_allowed {
-                            return self.read_regex();
-                        }
-                        break;
+        }
+        Ok(code)
+    }
+
+    /// Rewinds to the start of the last token and scans it as a regex literal.
+    /// Reads the whitespace, line terminators and comments before the next
+    /// token. `next` skips them on its own; only the lossless syntax tree
+    /// keeps them.
+    pub fn read_trivia(&mut self) -> Result<Vec<Trivia>, String> {
+        let mut trivia = vec![];
+        loop {
+            let start = self.pos;
+            let kind = match (self.peek(0), self.peek(1)) {
+                (Some('#'), Some('!')) if self.pos == 0 => {
+                    self.pos = 1;
+                    self.column = 2;
+                    self.read_comment()?;
+                    TriviaKind::Hashbang
+                }
+                (Some('/'), Some('/')) => {
+                    self.pos += 1;
+                    self.column += 1;
+                    self.read_comment()?;
+                    TriviaKind::LineComment
+                }
+                (Some('/'), Some('*')) => {
+                    self.pos += 1;
+                    self.column += 1;
+                    self.read_multiline_comment()?;
+                    TriviaKind::BlockComment
+                }
+                (Some(c), next) if is_line_terminator(c) => {
+                    self.pos += if c == '\r' && next == Some('\n') {
+                        2
+                    } else {
+                        1
+                    };
+                    self.line += 1;
+                    self.column = 1;
+                    TriviaKind::Newline
+                }
+                (Some(c), _) if is_whitespace(c) => {
+                    while self.peek(0).is_some_and(is_whitespace) {
+                        self.pos += 1;
+                        self.column += 1;
                     }
-                },
-                None => break,
-            }
+                    TriviaKind::Whitespace
+                }
+                _ => return Ok(trivia),
+            };
+            trivia.push(Trivia {
+                kind,
+                text: self
+                    .input
+                    .chars()
+                    .skip(start)
+                    .take(self.pos - start)
+                    .collect(),
+            });
         }
-        Ok(Token::Control(word))
     }
 
-    fn read_operation(&mut self) -> Result<Token, String> {
-        let c = self.input.chars().nth(self.pos).unwrap();
+    pub fn reread_as_regex(&mut self) -> Result<(Token, Loc), String> {
+        let (pos, start) = self.token_start.clone();
+        self.pos = pos;
+        self.line = start.line;
+        self.column = start.column;
+        let result = self.read_regex()?;
+        let end = self.position();
+        Ok((result, Loc { start, end }))
+    }
+
+    /// Re-scans the current `` ` `` or `}` token as one part of a template:
+    /// up to the closing backtick or through the next `${`. [`Lex::next`]
+    /// reads a whole template as one token, which its users split this way.
+    pub fn reread_as_template_part(&mut self) -> Result<(Token, Loc), String> {
+        let (pos, start) = self.token_start.clone();
+        self.pos = pos;
+        self.line = start.line;
+        self.column = start.column;
         let mut word = String::new();
-        word.push(c);
         loop {
             self.pos += 1;
             self.column += 1;
-            let d = self.input.chars().nth(self.pos);
-            match d {
-                Some(d) => match d {
-                    '=' | '+' | '-' | '*' | '/' | '%' | '>' | '<' | '|' | '?' | ':' | '&' => {
-                        word.push(d);
-                    }
-                    _ => {
-                        break;
+            match self.peek(0) {
+                Some('`') => {
+                    self.pos += 1;
+                    self.column += 1;
+                    break;
+                }
+                Some('$') if self.peek(1) == Some('{') => {
+                    self.pos += 2;
+                    self.column += 2;
+                    break;
+                }
+                Some('\\') => {
+                    word.push('\\');
+                    if let Some(c) = self.peek(1) {
+                        self.pos += 1;
+                        self.column += 1;
+                        self.count_line_break(c);
+                        word.push(c);
                     }
-                },
-                None => break,
+                }
+                Some(c) => {
+                    self.count_line_break(c);
+                    word.push(c);
+                }
+                None => return Err("Unterminated template".to_string()),
+            }
+        }
+        let end = self.position();
+        Ok((Token::TemplateStr(word), Loc { start, end }))
+    }
+
+    fn read_divide_or_comment(&mut self) -> Result<Token, String> {
+        self.pos += 1;
+        self.column += 1;
+        match self.input.chars().nth(self.pos) {
+            Some('/') => self.read_comment(),
+            Some('*') => self.read_multiline_comment(),
+            Some('=') => {
+                self.pos += 1;
+                self.column += 1;
+                Ok(Token::Control("/=".to_string()))
             }
+            _ => Ok(Token::Control("/".to_string())),
         }
+    }
 
+    fn read_operation(&mut self) -> Result<Token, String> {
+        let rest: String = self.input.chars().skip(self.pos).take(4).collect();
+        let mut word = rest.chars().next().unwrap().to_string();
+        for p in PUNCTUATORS {
+            if rest.starts_with(p) {
+                // `a?.5:b` is a conditional, not optional chaining
+                if p == "?." && rest[2..].starts_with(|c: char| c.is_ascii_digit()) {
+                    continue;
+                }
+                word = p.to_string();
+                break;
+            }
+        }
+        let len = word.chars().count();
+        self.pos += len;
+        self.column += len;
         Ok(Token::Control(word))
     }
 
     fn read_regex(&mut self) -> Result<Token, String> {
-        let mut escaped = false;
-        let mut flags_start = false;
-        let c = self.input.chars().nth(self.pos).unwrap();
-        if c == '/' {
-            return Err("expect regex, but find //".to_string());
-        }
         let mut word = String::new();
         let mut flags = String::new();
-        word.push(c);
+        let mut escaped = false;
+        let mut in_class = false;
         loop {
             self.pos += 1;
             self.column += 1;
-            let d = self.input.chars().nth(self.pos);
-            if d.is_none() {
-                break;
-            }
-            let d = d.unwrap();
-            if !escaped && d == '\\' {
-                escaped = true;
-                word.push('\\');
-                continue;
-            }
+            let d = match self.input.chars().nth(self.pos) {
+                Some(d) if is_line_terminator(d) => {
+                    return Err("Unterminated regular expression".to_string());
+                }
+                None => return Err("Unterminated regular expression".to_string()),
+                Some(d) => d,
+            };
             if escaped {
                 escaped = false;
-                word.push(d);
-                continue;
+            } else if d == '\\' {
+                escaped = true;
+            } else if d == '[' {
+                in_class = true;
+            } else if d == ']' {
+                in_class = false;
+            } else if d == '/' && !in_class {
+                break;
             }
-            match d {
-                '/' => {
-                    if flags_start {
-                        return Err("expect regex flags, but found /".to_string());
+            word.push(d);
+        }
+        loop {
+            self.pos += 1;
+            self.column += 1;
+            match self.input.chars().nth(self.pos) {
+                Some(d) if matches!(d, 'd' | 'g' | 'i' | 'm' | 's' | 'u' | 'v' | 'y') => {
+                    if flags.contains(d) {
+                        return Err("repeated regex flags".to_string());
                     }
-                    flags_start = true
+                    flags.push(d);
                 }
-                '_' | 'a'..='z' | '0'..='9' => match d {
-                    'i' | 'g' | 'm' | 's' | 'u' | 'y' => {
-                        if flags_start {
-                            if flags.contains(d) {
-                                return Err("repeated regex flags".to_string());
-                            }
-                            flags.push(d);
-                        }
-                    }
-                    _ => {
-                        if flags_start {
-                            return Err("regex expect newline or semicolon".to_string());
-                        } else {
-                            word.push(d);
-                        }
-                    }
-                },
-                _ => {
-                    if flags_start {
-                        break;
-                    }
-                    word.push(d);
+                Some(d) if d == '_' || d == '$' || d.is_ascii_alphanumeric() => {
+                    return Err(format!("invalid regex flag {d}"));
                 }
+                _ => break,
             }
         }
-        if !flags_start {
-            panic!("regex syntax error");
-        }
         Ok(Token::Regex(word, flags))
     }
 
@@ -379,7 +559,7 @@ impl Lex {
             let c = self.input.chars().nth(self.pos);
             match c {
                 Some(c) => match c {
-                    '\r' | '\n' => {
+                    c if is_line_terminator(c) => {
                         break;
                     }
                     s => {
@@ -418,10 +598,7 @@ impl Lex {
                         word.push('/');
                     }
                     s => {
-                        if s == '\n' {
-                            self.line += 1;
-                            self.column = 0;
-                        }
+                        self.count_line_break(s);
                         if end_star_flag {
                             word.push('*');
                             end_star_flag = false;
@@ -430,127 +607,141 @@ impl Lex {
                     }
                 },
                 None => {
-                    break;
+                    return Err("Unterminated comment".to_string());
                 }
             }
         }
-        Ok(Comment(word))
+        Ok(Token::BlockComment(word))
     }
 
-    fn read_digit(&mut self) -> Result<Token, String> {
-        let c = self.input.chars().nth(self.pos).unwrap();
-        let mut word = String::new();
-        let mut exponential = false;
-        word.push(c);
-        loop {
-            self.pos += 1;
-            self.column += 1;
-            let c = self.input.chars().nth(self.pos);
-            match c {
-                Some(c) => match c {
-                    '_' => {
-                        word.push(c);
-                    }
-                    '0'..='9' => {
-                        word.push(c);
-                        exponential = false;
-                    }
-                    'e' => {
-                        exponential = true;
-                        word.push(c);
-                    }
-                    _ => {
-                        break;
-                    }
-                },
-                None => break,
-            }
-        }
-        if exponential {
-            panic!("digit syntax error");
+    /// Bumps the line for a line terminator inside a token; `\r\n` counts once.
+    /// Callers advance `column` before looking at the next character.
+    fn count_line_break(&mut self, c: char) {
+        if is_line_terminator(c) && !(c == '\r' && self.peek(1) == Some('\n')) {
+            self.line += 1;
+            self.column = 0;
         }
-        Ok(Token::Digit(word))
     }
 
-    fn read_binary_octal_hex_digit(&mut self) -> Result<Token, String> {
-        let c = self.input.chars().nth(self.pos).unwrap();
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.input.chars().nth(self.pos + offset)
+    }
+
+    fn advance(&mut self, word: &mut String) {
+        word.push(self.peek(0).unwrap());
+        self.pos += 1;
+        self.column += 1;
+    }
+
+    fn read_number(&mut self) -> Result<Token, String> {
         let mut word = String::new();
-        let mut flag = "".to_string();
-        let mut underscore_allowed = false;
-        let mut underscore_ended = false;
-        word.push(c);
-        loop {
-            self.pos += 1;
-            self.column += 1;
-            let d = self.input.chars().nth(self.pos);
-            if d.is_none() {
-                break;
-            }
-            let d = d.unwrap();
-            if d.is_ascii_whitespace() {
-                break;
-            }
-            if d == '_' {
-                if underscore_allowed {
-                    word.push('_');
-                    underscore_allowed = false;
-                    underscore_ended = true;
-                    continue;
-                } else {
-                    return Err("digit _ error".to_string());
+        let mut is_decimal = false;
+        if self.peek(0) == Some('0') {
+            match self.peek(1) {
+                Some('x' | 'X' | 'o' | 'O' | 'b' | 'B') => {
+                    return self.read_binary_octal_hex_digit();
                 }
-            }
-            if flag == "b" {
-                if ('0'..='1').contains(&d) {
-                    word.push(d);
-                    underscore_allowed = true;
-                    underscore_ended = false;
-                    continue;
-                } else {
-                    break;
+                Some('0'..='9') => {
+                    self.read_legacy_octal_digit(&mut word)?;
+                    if !word.contains(['8', '9']) {
+                        self.check_number_end()?;
+                        return Ok(Token::Digit(word));
+                    }
                 }
+                Some('_') => return Err("numeric separator after leading 0".to_string()),
+                _ => {}
             }
-            if flag == "o" {
-                if ('0'..='7').contains(&d) {
-                    word.push(d);
-                    underscore_allowed = true;
-                    underscore_ended = false;
-                    continue;
-                } else {
-                    break;
-                }
+        }
+        if word.is_empty() && self.peek(0) != Some('.') {
+            self.read_digits(&mut word, 10)?;
+        }
+        if self.peek(0) == Some('.') {
+            is_decimal = true;
+            self.advance(&mut word);
+            if self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.read_digits(&mut word, 10)?;
             }
-            if flag == "x" {
-                if ('0'..='9').contains(&d) || ('a'..='f').contains(&d) || ('A'..='F').contains(&d)
-                {
-                    word.push(d);
-                    underscore_allowed = true;
-                    underscore_ended = false;
-                    continue;
-                } else {
-                    break;
-                }
+        }
+        if let Some('e' | 'E') = self.peek(0) {
+            is_decimal = true;
+            self.advance(&mut word);
+            if let Some('+' | '-') = self.peek(0) {
+                self.advance(&mut word);
             }
-            match d {
-                'o' | 'b' | 'x' => {
-                    if flag == "" {
-                        word.push(d);
-                        flag = d.to_string();
-                    } else {
-                        return Err("digit syntax error".to_string());
+            self.read_digits(&mut word, 10)?;
+        }
+        if self.peek(0) == Some('n') {
+            if is_decimal || word.starts_with('0') && word.len() > 1 {
+                return Err(format!("invalid BigInt literal {word}n"));
+            }
+            self.advance(&mut word);
+            self.check_number_end()?;
+            return Ok(Token::BigInt(word));
+        }
+        self.check_number_end()?;
+        Ok(Token::Digit(word))
+    }
+
+    fn read_digits(&mut self, word: &mut String, radix: u32) -> Result<(), String> {
+        if !self.peek(0).is_some_and(|c| c.is_digit(radix)) {
+            return Err(format!("digit expected after {word}"));
+        }
+        loop {
+            match self.peek(0) {
+                Some(c) if c.is_digit(radix) => self.advance(word),
+                Some('_') => {
+                    if !self.peek(1).is_some_and(|c| c.is_digit(radix)) {
+                        return Err("digit _ error".to_string());
                     }
+                    self.advance(word);
                 }
-                _ => {
-                    break;
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_legacy_octal_digit(&mut self, word: &mut String) -> Result<(), String> {
+        loop {
+            match self.peek(0) {
+                Some('0'..='9') => self.advance(word),
+                Some('_') => {
+                    return Err("numeric separator in legacy octal literal".to_string());
                 }
+                _ => break,
             }
         }
-        if underscore_ended {
-            return Err("digit _ error".to_string());
+        Ok(())
+    }
+
+    fn read_binary_octal_hex_digit(&mut self) -> Result<Token, String> {
+        let mut word = String::new();
+        self.advance(&mut word);
+        let radix = match self.peek(0) {
+            Some('b' | 'B') => 2,
+            Some('o' | 'O') => 8,
+            _ => 16,
+        };
+        self.advance(&mut word);
+        self.read_digits(&mut word, radix)?;
+        if self.peek(0) == Some('n') {
+            self.advance(&mut word);
+            self.check_number_end()?;
+            return Ok(Token::BigInt(word));
         }
+        self.check_number_end()?;
         Ok(Token::Digit(word))
     }
 
+    fn check_number_end(&self) -> Result<(), String> {
+        match self.peek(0) {
+            Some(c) if c == '\\' || is_identifier_part(c) => Err(format!(
+                "identifier starts immediately after numeric literal: {c}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
     fn read_template_str(&mut self) -> Result<Token, String> {
         let mut word = String::new();
         loop {
@@ -565,19 +756,120 @@ impl Lex {
                         break;
                     }
                     _ => {
+                        self.count_line_break(c);
                         word.push(c);
                     }
                 },
-                None => panic!("template string error"),
+                None => return Err("Unterminated template".to_string()),
             }
         }
         Ok(Token::TemplateStr(word))
     }
 }
 
+pub fn numeric_value(raw: &str) -> f64 {
+    let digits = raw.replace('_', "");
+    let radix = match digits.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ if digits.len() > 1
+            && digits.starts_with('0')
+            && digits.chars().all(|c| ('0'..='7').contains(&c)) =>
+        {
+            return fold_digits(&digits[1..], 8);
+        }
+        _ => return digits.parse().unwrap_or(f64::NAN),
+    };
+    fold_digits(&digits[2..], radix)
+}
+
+fn fold_digits(digits: &str, radix: u32) -> f64 {
+    digits.chars().fold(0.0, |acc, c| {
+        acc * radix as f64 + c.to_digit(radix).unwrap() as f64
+    })
+}
+
+/// The cooked value of the template part whose raw text is `raw`, with its
+/// line breaks already `\n`: escapes are read as in a string. A template
+/// allows no legacy octal escape, and a lone surrogate, which a Rust string
+/// can't hold, is an error rather than U+FFFD.
+pub fn cook_template(raw: &str) -> Result<String, String> {
+    let mut lex = Lex::new(raw.to_string());
+    let mut cooked = String::new();
+    while let Some(c) = lex.peek(0) {
+        if c != '\\' {
+            cooked.push(c);
+            lex.pos += 1;
+            continue;
+        }
+        if let (Some('0'), Some('0'..='9')) | (Some('1'..='9'), _) = (lex.peek(1), lex.peek(2)) {
+            return Err("Octal escape sequences are not allowed in template strings".to_string());
+        }
+        let start = lex.pos + 1;
+        lex.read_escape(&mut cooked)?;
+        if cooked.ends_with('\u{FFFD}') && raw.chars().nth(start) == Some('u') {
+            let escape: String = raw
+                .chars()
+                .skip(start + 1)
+                .take(lex.pos - start - 1)
+                .collect();
+            let code = u32::from_str_radix(escape.trim_matches(['{', '}']), 16);
+            if code.is_ok_and(|code| (0xD800..0xE000).contains(&code)) {
+                return Err("Lone surrogate escapes in templates are not supported".to_string());
+            }
+        }
+    }
+    Ok(cooked)
+}
+
+/// The keyword token spelled by `word`, if any.
+pub fn keyword(word: &str) -> Option<Token> {
+    Some(match word {
+        "var" => Token::Var,
+        "let" => Token::Let,
+        "const" => Token::Const,
+        "undefined" => Token::Undefined,
+        "null" => Token::Null,
+        "await" => Token::Await,
+        "async" => Token::Async,
+        "function" => Token::Function,
+        "with" => Token::With,
+        "delete" => Token::Delete,
+        "if" => Token::If,
+        "switch" => Token::Switch,
+        "case" => Token::Case,
+        "default" => Token::Default,
+        "break" => Token::Break,
+        "continue" => Token::Continue,
+        "for" => Token::For,
+        "while" => Token::While,
+        "return" => Token::Return,
+        "true" => Token::True,
+        "false" => Token::False,
+        "else" => Token::Else,
+        "in" => Token::In,
+        "do" => Token::Do,
+        "try" => Token::Try,
+        "catch" => Token::Catch,
+        "finally" => Token::Finally,
+        "throw" => Token::Throw,
+        "new" => Token::New,
+        "this" => Token::This,
+        "instanceof" => Token::Instanceof,
+        "typeof" => Token::Typeof,
+        "class" => Token::Class,
+        "void" => Token::Void,
+        "yield" => Token::Yield,
+        "debugger" => Token::Debugger,
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::lex::{Lex, Token};
+    use crate::lex::{Lex, Token, cook_template, numeric_value};
+    use crate::token::TriviaKind;
 
     #[test]
     fn test_token_display() {
@@ -593,6 +885,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_unicode_identifier() -> Result<(), String> {
+        let mut lex = Lex::new("const café = π + 変数 + a\u{200D}b".to_string());
+        assert_eq!(lex.next()?.0, Token::Const);
+        assert_eq!(lex.next()?.0, Token::Variable("café".to_string()));
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Variable("π".to_string()));
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Variable("変数".to_string()));
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Variable("a\u{200D}b".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_escaped_identifier() -> Result<(), String> {
+        let mut lex = Lex::new(r"\u0061bc a\u{62}c l\u0065t v\u0061r".to_string());
+        assert_eq!(lex.next()?.0, Token::Variable("abc".to_string()));
+        assert_eq!(lex.next()?.0, Token::Variable("abc".to_string()));
+        assert_eq!(lex.next()?.0, Token::Variable("let".to_string()));
+        assert_eq!(lex.next()?.0, Token::Variable("var".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_escaped_identifier_errors() {
+        for input in [r"\u0031a", r"a\u002Db", r"a\x41", "\u{200D}a"] {
+            let mut lex = Lex::new(input.to_string());
+            assert!(lex.next().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_cook_template() {
+        assert_eq!(
+            cook_template("a\\n\\x41\\u{42}\\uD83D\\uDE00\\0\\\nb\\`"),
+            Ok("a\nAB\u{1F600}\0b`".to_string())
+        );
+        assert_eq!(
+            cook_template(r"\01"),
+            Err("Octal escape sequences are not allowed in template strings".to_string())
+        );
+        assert_eq!(
+            cook_template(r"\uD800"),
+            Err("Lone surrogate escapes in templates are not supported".to_string())
+        );
+        assert_eq!(cook_template(r"\uFFFD"), Ok("\u{FFFD}".to_string()));
+    }
+
     #[test]
     fn test_digit_exponential() -> Result<(), String> {
         let input = "1e3";
@@ -601,11 +942,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_decimal_forms() -> Result<(), String> {
+        let input = "1.5 .5 5. 1e-3 2E+8 0 0.5 08 09.5 1_000_000 1.e3";
+        let mut lex = Lex::new(input.to_string());
+        for raw in input.split(' ') {
+            assert_eq!(lex.next()?.0, Token::Digit(raw.to_string()));
+        }
+        assert_eq!(lex.next()?.0, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_member_after_digit() -> Result<(), String> {
+        let mut lex = Lex::new("5..toString".to_string());
+        assert_eq!(lex.next()?.0, Token::Digit("5.".to_string()));
+        assert_eq!(lex.next()?.0, Token::Control(".".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bigint() -> Result<(), String> {
+        let mut lex = Lex::new("10n 0xFFn 0b1n 1_0n".to_string());
+        assert_eq!(lex.next()?.0, Token::BigInt("10n".to_string()));
+        assert_eq!(lex.next()?.0, Token::BigInt("0xFFn".to_string()));
+        assert_eq!(lex.next()?.0, Token::BigInt("0b1n".to_string()));
+        assert_eq!(lex.next()?.0, Token::BigInt("1_0n".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_digit_errors() {
+        for input in [
+            "1__0", "1_", "0_1", "0x", "0x_1", "1e", "1.5n", "1e3n", "017n", "01_7", "08n", "3in",
+            "0b12", "1._5",
+        ] {
+            let mut lex = Lex::new(input.to_string());
+            assert!(lex.next().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_numeric_value() {
+        assert_eq!(numeric_value("1.5"), 1.5);
+        assert_eq!(numeric_value(".5"), 0.5);
+        assert_eq!(numeric_value("5."), 5.0);
+        assert_eq!(numeric_value("1e-3"), 0.001);
+        assert_eq!(numeric_value("1_000"), 1000.0);
+        assert_eq!(numeric_value("0xff"), 255.0);
+        assert_eq!(numeric_value("0o17"), 15.0);
+        assert_eq!(numeric_value("0b101"), 5.0);
+        assert_eq!(numeric_value("017"), 15.0);
+        assert_eq!(numeric_value("019"), 19.0);
+        assert_eq!(numeric_value("0"), 0.0);
+    }
+
     #[test]
     fn test_string_single() -> Result<(), String> {
         let input = "'abcdefjie'";
         let mut lex = Lex::new(input.to_string());
-        assert_eq!(lex.next()?.0, Token::String("abcdefjie".to_string()));
+        assert_eq!(
+            lex.next()?.0,
+            Token::String("abcdefjie".to_string(), input.to_string())
+        );
         Ok(())
     }
 
@@ -613,15 +1012,66 @@ mod tests {
     fn test_string_single_newline() -> Result<(), String> {
         let input = "'abcdefjie\\nxx'";
         let mut lex = Lex::new(input.to_string());
-        assert_eq!(lex.next()?.0, Token::String("abcdefjie\nxx".to_string()));
+        assert_eq!(
+            lex.next()?.0,
+            Token::String("abcdefjie\nxx".to_string(), input.to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_escapes() -> Result<(), String> {
+        let input = r#"'A\u{1F600}\x41\0\b\f\v\101\8\q😀'"#;
+        let mut lex = Lex::new(input.to_string());
+        assert_eq!(
+            lex.next()?.0,
+            Token::String(
+                "A\u{1F600}A\0\u{8}\u{c}\u{b}A8q\u{1F600}".to_string(),
+                input.to_string()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_line_continuation() -> Result<(), String> {
+        let input = "'a\\\nb\\\r\nc\u{2028}d' x";
+        let mut lex = Lex::new(input.to_string());
+        assert_eq!(
+            lex.next()?.0,
+            Token::String(
+                "abc\u{2028}d".to_string(),
+                input[..input.len() - 2].to_string()
+            )
+        );
+        let (_, loc) = lex.next()?;
+        assert_eq!(loc.start.line, 4);
         Ok(())
     }
 
+    #[test]
+    fn test_string_errors() {
+        for input in [
+            "'abc",
+            "'a\nb'",
+            r"'\x4'",
+            r"'\u12'",
+            r"'\u{110000}'",
+            r"'\u{}'",
+        ] {
+            let mut lex = Lex::new(input.to_string());
+            assert!(lex.next().is_err(), "{input}");
+        }
+    }
+
     #[test]
     fn test_string_double() -> Result<(), String> {
         let input = "\"abcde\\\"fjie\"";
         let mut lex = Lex::new(input.to_string());
-        assert_eq!(lex.next()?.0, Token::String("abcde\"fjie".to_string()));
+        assert_eq!(
+            lex.next()?.0,
+            Token::String("abcde\"fjie".to_string(), input.to_string())
+        );
         Ok(())
     }
 
@@ -638,7 +1088,7 @@ mod tests {
     fn test_multiline_comment() -> Result<(), String> {
         let input = "/*//***\n /**/";
         let mut lex = Lex::new(input.to_string());
-        assert_eq!(lex.next()?.0, Token::Comment("//***\n /*".to_string()));
+        assert_eq!(lex.next()?.0, Token::BlockComment("//***\n /*".to_string()));
         Ok(())
     }
 
@@ -662,8 +1112,9 @@ mod tests {
     fn test_regex() -> Result<(), String> {
         let input = "/abc/";
         let mut lex = Lex::new(input.to_string());
+        assert_eq!(lex.next()?.0, Token::Control("/".to_string()));
         assert_eq!(
-            lex.next()?.0,
+            lex.reread_as_regex()?.0,
             Token::Regex("abc".to_string(), "".to_string())
         );
         Ok(())
@@ -673,8 +1124,9 @@ mod tests {
     fn test_regex_flags() -> Result<(), String> {
         let input = "/abc/ig";
         let mut lex = Lex::new(input.to_string());
+        assert_eq!(lex.next()?.0, Token::Control("/".to_string()));
         assert_eq!(
-            lex.next()?.0,
+            lex.reread_as_regex()?.0,
             Token::Regex("abc".to_string(), "ig".to_string())
         );
         Ok(())
@@ -684,13 +1136,127 @@ mod tests {
     fn test_regex_escape() -> Result<(), String> {
         let input = "/abc\\r\\n/ig";
         let mut lex = Lex::new(input.to_string());
+        assert_eq!(lex.next()?.0, Token::Control("/".to_string()));
         assert_eq!(
-            lex.next()?.0,
+            lex.reread_as_regex()?.0,
             Token::Regex("abc\\r\\n".to_string(), "ig".to_string())
         );
         Ok(())
     }
 
+    #[test]
+    fn test_regex_class_slash() -> Result<(), String> {
+        let input = "/[/]\\//g";
+        let mut lex = Lex::new(input.to_string());
+        lex.next()?;
+        assert_eq!(
+            lex.reread_as_regex()?.0,
+            Token::Regex("[/]\\/".to_string(), "g".to_string())
+        );
+        assert_eq!(lex.next()?.0, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_operator_longest_match() -> Result<(), String> {
+        let input = "a=-1>>>=b?.5:c";
+        let mut lex = Lex::new(input.to_string());
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Control("=".to_string()));
+        assert_eq!(lex.next()?.0, Token::Control("-".to_string()));
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Control(">>>=".to_string()));
+        lex.next()?;
+        assert_eq!(lex.next()?.0, Token::Control("?".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unicode_whitespace() -> Result<(), String> {
+        let input = "\u{FEFF}a\u{B}\u{C}\u{A0}\u{3000}b\u{2028}c\u{2029}d\r\ne\rf";
+        let mut lex = Lex::new(input.to_string());
+        let mut lines = vec![];
+        loop {
+            let (token, loc) = lex.next()?;
+            if token == Token::EOF {
+                break;
+            }
+            lines.push((token.to_string(), loc.start.line, loc.start.column));
+        }
+        let expect = [
+            ("a", 1, 2),
+            ("b", 1, 7),
+            ("c", 2, 1),
+            ("d", 3, 1),
+            ("e", 4, 1),
+            ("f", 5, 1),
+        ];
+        let expect: Vec<_> = expect
+            .iter()
+            .map(|(t, l, c)| (t.to_string(), *l, *c))
+            .collect();
+        assert_eq!(lines, expect);
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_line_terminators() -> Result<(), String> {
+        let mut lex = Lex::new("//a\u{2028}b /*\r\n\u{2029}*/c".to_string());
+        assert_eq!(lex.next()?.0, Token::Comment("a".to_string()));
+        assert_eq!(lex.next()?.0, Token::Variable("b".to_string()));
+        lex.next()?;
+        let (token, loc) = lex.next()?;
+        assert_eq!(token, Token::Variable("c".to_string()));
+        assert_eq!((loc.start.line, loc.start.column), (4, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_trivia() -> Result<(), String> {
+        let mut lex = Lex::new("#!node\r\n\t// a\n/* b\n */ c".to_string());
+        let kinds: Vec<(TriviaKind, String)> = lex
+            .read_trivia()?
+            .into_iter()
+            .map(|trivia| (trivia.kind, trivia.text))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (TriviaKind::Hashbang, "#!node".to_string()),
+                (TriviaKind::Newline, "\r\n".to_string()),
+                (TriviaKind::Whitespace, "\t".to_string()),
+                (TriviaKind::LineComment, "// a".to_string()),
+                (TriviaKind::Newline, "\n".to_string()),
+                (TriviaKind::BlockComment, "/* b\n */".to_string()),
+                (TriviaKind::Whitespace, " ".to_string()),
+            ]
+        );
+        let (token, loc) = lex.next()?;
+        assert_eq!(token, Token::Variable("c".to_string()));
+        assert_eq!(
+            (loc.start.line, loc.start.column, loc.start.index),
+            (4, 5, 23)
+        );
+        assert!(lex.read_trivia()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashbang() -> Result<(), String> {
+        let mut lex = Lex::new("#!/usr/bin/env node\na".to_string());
+        assert_eq!(
+            lex.next()?.0,
+            Token::Hashbang("/usr/bin/env node".to_string())
+        );
+        let (token, loc) = lex.next()?;
+        assert_eq!(token, Token::Variable("a".to_string()));
+        assert_eq!(loc.start.line, 2);
+        let mut lex = Lex::new("a #!b".to_string());
+        lex.next()?;
+        assert!(lex.next().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_lex() -> Result<(), String> {
         let input = " \n\n\nlet\n\n\n a\n\n\n =\n\n\n 1\n\n\n + \n\n\n2\n\n\n";
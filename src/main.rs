@@ -4,12 +4,28 @@ use crate::parser::Parser;
 use std::fs::File;
 use std::io::Read;
 
+mod babel;
+mod codegen;
+mod comments;
+mod cst;
+mod diagnostic;
+mod estree;
+mod estree_builder;
 mod exp;
 mod express;
 mod express_test;
+#[cfg(test)]
+mod fixtures;
+mod highlight;
+mod jsdoc;
+mod json;
 mod lex;
 mod node;
 mod parser;
+mod sourcemap;
+mod token;
+mod tokenize;
+mod unicode;
 
 fn main() -> Result<(), String> {
     let mut str = String::new();
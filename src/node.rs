@@ -1,192 +1,627 @@
-use crate::token::Token;
+use crate::lex::Loc;
+use std::collections::BTreeMap;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Extra {
     None,
-    Parenthesized,
+    /// Wrapped in parentheses; `paren_start` is the char offset of the `(`.
+    Parenthesized {
+        paren_start: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    pub directives: Vec<Directive>,
+    pub body: Vec<ModuleItem>,
+    /// Every comment in the source, in order.
+    pub comments: Vec<Comment>,
+    pub attached_comments: AttachedComments,
+    pub source_type: SourceType,
+    pub loc: Loc,
+}
+
+/// A string literal statement opening a program or function body, like
+/// `"use strict"`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Directive {
+    pub value: StringLiteral,
+    pub loc: Loc,
+}
+
+impl Directive {
+    /// The text between the quotes, escapes left as written.
+    pub fn raw_value(&self) -> &str {
+        let raw = &self.value.raw;
+        &raw[1..raw.len() - 1]
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SourceType {
+    #[default]
+    Script,
+    /// Strict mode code, where `await` works at the top level from ES2022
+    /// on. `import` and `export` declarations are not parsed yet.
+    Module,
+    /// A script the loader wraps in a function, so `return` works at the top.
+    CommonJs,
+}
+
+impl SourceType {
+    pub fn name(self) -> &'static str {
+        match self {
+            SourceType::Script => "script",
+            SourceType::Module => "module",
+            SourceType::CommonJs => "commonjs",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CommentKind {
+    /// `// value`
+    Line,
+    /// `/* value */`
+    Block,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    pub kind: CommentKind,
+    pub value: String,
+    pub loc: Loc,
+}
+
+/// The comments of each node that has any, keyed by its `(start, end)` char
+/// offsets. Where nodes share a span, like the statement `a` and its
+/// expression, the leading and trailing comments belong to the outermost of
+/// them and the inner comments to the innermost.
+pub type AttachedComments = BTreeMap<(usize, usize), NodeComments>;
+
+/// The comments attached to a node, following Babel's heuristics.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NodeComments {
+    pub leading_comments: Vec<Comment>,
+    pub trailing_comments: Vec<Comment>,
+    /// Comments inside a node with no child to attach to, like `{ /* */ }`.
+    pub inner_comments: Vec<Comment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ModuleItem {
+    Statement(Statement),
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Node {
-    EmptyStatement {},
-    Identity {
-        name: String,
+pub enum Statement {
+    EmptyStatement {
+        loc: Loc,
     },
-    NumericLiteral {
-        value: String,
+    BlockStatement(BlockStatement),
+    ExpressionStatement {
+        expression: Box<Expression>,
+        loc: Loc,
     },
-    StringLiteral {
-        value: String,
+    Declaration(Declaration),
+    IfStatement {
+        test: Box<Expression>,
+        consequent: Box<Statement>,
+        alternate: Option<Box<Statement>>,
+        loc: Loc,
+    },
+    ForStatement {
+        init: Option<ForInit>,
+        test: Option<Box<Expression>>,
+        update: Option<Box<Expression>>,
+        body: Box<Statement>,
+        loc: Loc,
+    },
+    ForInStatement {
+        left: ForInLeft,
+        right: Box<Expression>,
+        body: Box<Statement>,
+        loc: Loc,
+    },
+    WhileStatement {
+        test: Box<Expression>,
+        body: Box<Statement>,
+        loc: Loc,
+    },
+    DoWhileStatement {
+        body: Box<Statement>,
+        test: Box<Expression>,
+        loc: Loc,
+    },
+    WithStatement {
+        object: Box<Expression>,
+        body: Box<Statement>,
+        loc: Loc,
+    },
+    TryStatement {
+        block: BlockStatement,
+        handler: Option<CatchClause>,
+        finalizer: Option<BlockStatement>,
+        loc: Loc,
+    },
+    ReturnStatement {
+        argument: Option<Box<Expression>>,
+        loc: Loc,
+    },
+    SwitchStatement {
+        discriminant: Box<Expression>,
+        cases: Vec<SwitchCase>,
+        loc: Loc,
+    },
+    LabeledStatement {
+        label: Identifier,
+        body: Box<Statement>,
+        loc: Loc,
+    },
+    BreakStatement {
+        label: Option<Identifier>,
+        loc: Loc,
+    },
+    ContinueStatement {
+        label: Option<Identifier>,
+        loc: Loc,
+    },
+    ThrowStatement {
+        argument: Box<Expression>,
+        loc: Loc,
+    },
+    /// The tokens skipped by error recovery in place of a statement.
+    Invalid {
+        loc: Loc,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Declaration {
+    FunctionDeclaration(Function),
+    VariableDeclaration(VariableDeclaration),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum VariableKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct VariableDeclaration {
+    pub kind: VariableKind,
+    pub declarations: Vec<VariableDeclarator>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct VariableDeclarator {
+    pub id: Pattern,
+    pub init: Option<Box<Expression>>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ForInit {
+    VariableDeclaration(VariableDeclaration),
+    Expression(Box<Expression>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ForInLeft {
+    VariableDeclaration(VariableDeclaration),
+    Pattern(Pattern),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockStatement {
+    /// Only a function body has any.
+    pub directives: Vec<Directive>,
+    pub body: Vec<Statement>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CatchClause {
+    pub param: Option<Pattern>,
+    pub body: BlockStatement,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SwitchCase {
+    pub test: Option<Box<Expression>>,
+    pub consequent: Vec<Statement>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Identifier(Identifier),
+    ThisExpression {
+        extra: Extra,
+        loc: Loc,
+    },
+    NullLiteral {
+        extra: Extra,
+        loc: Loc,
     },
     BooleanLiteral {
         value: bool,
+        extra: Extra,
+        loc: Loc,
+    },
+    NumericLiteral(NumericLiteral),
+    BigIntLiteral {
+        value: String,
+        raw: String,
+        extra: Extra,
+        loc: Loc,
     },
-    NullLiteral {},
+    StringLiteral(StringLiteral),
     RegExpLiteral {
         pattern: String,
         flags: String,
+        extra: Extra,
+        loc: Loc,
     },
     TemplateLiteral {
-        expressions: Vec<Node>,
-        quasis: Vec<Node>,
-    },
-    TemplateElement {
-        value: String,
+        expressions: Vec<Expression>,
+        quasis: Vec<TemplateElement>,
+        extra: Extra,
+        loc: Loc,
     },
     ArrayExpression {
-        elements: Vec<Node>,
+        elements: Vec<Option<Expression>>,
+        trailing_comma: bool,
+        extra: Extra,
+        loc: Loc,
     },
     ObjectExpression {
-        properties: Vec<Node>,
-    },
-    ObjectProperty {
-        key: Box<Node>,
-        value: Box<Node>,
-    },
-    ObjectMethod {
-        key: Box<Node>,
-        params: Vec<Node>,
-        body: Box<Node>,
-    },
-    ObjectPattern {
-        properties: Vec<Node>,
+        properties: Vec<ObjectMember>,
+        trailing_comma: bool,
+        extra: Extra,
+        loc: Loc,
     },
-    ArrayPattern {
-        elements: Vec<Node>,
+    FunctionExpression(Function),
+    ArrowFunctionExpression {
+        params: Vec<Pattern>,
+        body: ArrowFunctionBody,
+        trailing_comma: bool,
+        extra: Extra,
+        loc: Loc,
     },
     SequenceExpression {
-        expressions: Vec<Node>,
+        expressions: Vec<Expression>,
         extra: Extra,
-    },
-    VariableDeclaration {
-        kind: Token,
-        declarations: Vec<Node>,
-    },
-    VariableDeclarator {
-        id: Box<Node>,
-        init: Option<Box<Node>>,
+        loc: Loc,
     },
     AssignmentExpression {
-        left: Box<Node>,
+        left: Box<Pattern>,
         operator: String,
-        right: Box<Node>,
+        right: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
     BinaryExpression {
-        left: Box<Node>,
+        left: Box<Expression>,
         operator: String,
-        right: Box<Node>,
+        right: Box<Expression>,
         extra: Extra,
+        loc: Loc,
     },
     LogicalExpression {
-        left: Box<Node>,
+        left: Box<Expression>,
         operator: String,
-        right: Box<Node>,
+        right: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
     UnaryExpression {
         operator: String,
         prefix: bool,
-        argument: Box<Node>,
+        argument: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
     UpdateExpression {
         operator: String,
         prefix: bool,
-        argument: Box<Node>,
+        argument: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
-    MemberExpression {
-        object: Box<Node>,
-        property: Box<Node>,
-        computed: bool,
+    AwaitExpression {
+        argument: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
+    MemberExpression(MemberExpression),
     ConditionalExpression {
-        test: Box<Node>,
-        consequent: Box<Node>,
-        alternate: Box<Node>,
+        test: Box<Expression>,
+        consequent: Box<Expression>,
+        alternate: Box<Expression>,
+        extra: Extra,
+        loc: Loc,
     },
     CallExpression {
-        callee: Box<Node>,
-        arguments: Vec<Node>,
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+        trailing_comma: bool,
+        extra: Extra,
+        loc: Loc,
     },
     NewExpression {
-        callee: Box<Node>,
-        arguments: Vec<Node>,
-    },
-    ForStatement {
-        init: Box<Node>,
-        test: Box<Node>,
-        update: Box<Node>,
-        body: Box<Node>,
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+        trailing_comma: bool,
+        extra: Extra,
+        loc: Loc,
     },
-    ForInStatement {
-        left: Box<Node>,
-        right: Box<Node>,
-        body: Box<Node>,
+    /// Only with `preserve_parens`; otherwise parentheses leave no node.
+    ParenthesizedExpression {
+        expression: Box<Expression>,
+        loc: Loc,
     },
-    WhileStatement {
-        test: Box<Node>,
-        body: Box<Node>,
-    },
-    DoWhileStatement {
-        body: Box<Node>,
-        test: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArrowFunctionBody {
+    BlockStatement(BlockStatement),
+    Expression(Box<Expression>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ObjectMember {
+    ObjectProperty {
+        key: PropertyKey,
+        value: Box<Expression>,
+        shorthand: bool,
+        loc: Loc,
     },
-    FunctionDeclaration {
-        id: Box<Node>,
-        params: Vec<Node>,
-        body: Box<Node>,
+    ObjectMethod {
+        key: PropertyKey,
+        function: Function,
+        loc: Loc,
     },
-    FunctionExpression {
-        id: Option<Box<Node>>,
-        params: Vec<Node>,
-        body: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PropertyKey {
+    Identifier(Identifier),
+    StringLiteral(StringLiteral),
+    NumericLiteral(NumericLiteral),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    Identifier(Identifier),
+    MemberExpression(MemberExpression),
+    ObjectPattern {
+        properties: Vec<ObjectPatternProperty>,
+        trailing_comma: bool,
+        loc: Loc,
     },
-    ArrowFunctionExpression {
-        params: Vec<Node>,
-        body: Box<Node>,
+    ArrayPattern {
+        elements: Vec<Option<Pattern>>,
+        trailing_comma: bool,
+        loc: Loc,
     },
-    ThisExpression {},
     AssignmentPattern {
-        left: Box<Node>,
-        right: Box<Node>,
-    },
-    BlockStatement {
-        body: Vec<Node>,
-    },
-    IfStatement {
-        test: Box<Node>,
-        consequent: Box<Node>,
-        alternate: Option<Box<Node>>,
-    },
-    TryStatement {
-        block: Box<Node>,
-        handle: Option<Box<Node>>,
-        finalizer: Option<Box<Node>>,
-    },
-    CatchClause {
-        param: Option<Box<Node>>,
-        body: Box<Node>,
-    },
-    ReturnStatement {
-        argument: Option<Box<Node>>,
-    },
-    SwitchStatement {
-        discriminant: Box<Node>,
-        cases: Vec<Node>,
-    },
-    SwitchCase {
-        test: Option<Box<Node>>,
-        consequent: Vec<Node>,
-    },
-    LabeledStatement {
-        label: Box<Node>,
-        body: Box<Node>,
-    },
-    BreakStatement {
-        label: Option<Box<Node>>,
-    },
-    ContinueStatement {
-        label: Option<Box<Node>>,
-    },
-    ThrowStatement {
-        argument: Box<Node>,
+        left: Box<Pattern>,
+        right: Box<Expression>,
+        loc: Loc,
     },
 }
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ObjectPatternProperty {
+    pub key: PropertyKey,
+    pub value: Pattern,
+    pub shorthand: bool,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Identifier {
+    pub name: String,
+    pub extra: Extra,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringLiteral {
+    pub value: String,
+    pub raw: String,
+    pub extra: Extra,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct NumericLiteral {
+    pub value: f64,
+    pub raw: String,
+    pub extra: Extra,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TemplateElement {
+    /// The source text, with line breaks as `\n`.
+    pub value: String,
+    /// `value` with its escapes read.
+    pub cooked: String,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemberExpression {
+    pub object: Box<Expression>,
+    pub property: Box<Expression>,
+    pub computed: bool,
+    pub extra: Extra,
+    pub loc: Loc,
+}
+
+/// Shared by function declarations, function expressions and object methods.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    pub id: Option<Identifier>,
+    pub params: Vec<Pattern>,
+    pub body: BlockStatement,
+    pub trailing_comma: bool,
+    /// Only ever set on a function expression.
+    pub extra: Extra,
+    pub loc: Loc,
+}
+
+impl Statement {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Statement::BlockStatement(BlockStatement { loc, .. })
+            | Statement::Declaration(Declaration::FunctionDeclaration(Function { loc, .. }))
+            | Statement::Declaration(Declaration::VariableDeclaration(VariableDeclaration {
+                loc,
+                ..
+            }))
+            | Statement::EmptyStatement { loc }
+            | Statement::ExpressionStatement { loc, .. }
+            | Statement::IfStatement { loc, .. }
+            | Statement::ForStatement { loc, .. }
+            | Statement::ForInStatement { loc, .. }
+            | Statement::WhileStatement { loc, .. }
+            | Statement::DoWhileStatement { loc, .. }
+            | Statement::WithStatement { loc, .. }
+            | Statement::TryStatement { loc, .. }
+            | Statement::ReturnStatement { loc, .. }
+            | Statement::SwitchStatement { loc, .. }
+            | Statement::LabeledStatement { loc, .. }
+            | Statement::BreakStatement { loc, .. }
+            | Statement::ContinueStatement { loc, .. }
+            | Statement::ThrowStatement { loc, .. }
+            | Statement::Invalid { loc } => loc,
+        }
+    }
+}
+
+impl Expression {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Expression::Identifier(Identifier { loc, .. })
+            | Expression::NumericLiteral(NumericLiteral { loc, .. })
+            | Expression::StringLiteral(StringLiteral { loc, .. })
+            | Expression::FunctionExpression(Function { loc, .. })
+            | Expression::MemberExpression(MemberExpression { loc, .. })
+            | Expression::ThisExpression { loc, .. }
+            | Expression::NullLiteral { loc, .. }
+            | Expression::BooleanLiteral { loc, .. }
+            | Expression::BigIntLiteral { loc, .. }
+            | Expression::RegExpLiteral { loc, .. }
+            | Expression::TemplateLiteral { loc, .. }
+            | Expression::ArrayExpression { loc, .. }
+            | Expression::ObjectExpression { loc, .. }
+            | Expression::ArrowFunctionExpression { loc, .. }
+            | Expression::SequenceExpression { loc, .. }
+            | Expression::AssignmentExpression { loc, .. }
+            | Expression::BinaryExpression { loc, .. }
+            | Expression::LogicalExpression { loc, .. }
+            | Expression::UnaryExpression { loc, .. }
+            | Expression::UpdateExpression { loc, .. }
+            | Expression::AwaitExpression { loc, .. }
+            | Expression::ConditionalExpression { loc, .. }
+            | Expression::CallExpression { loc, .. }
+            | Expression::NewExpression { loc, .. }
+            | Expression::ParenthesizedExpression { loc, .. } => loc,
+        }
+    }
+
+    /// `None` for a `ParenthesizedExpression`, which has no parentheses of
+    /// its own to record.
+    pub fn extra_mut(&mut self) -> Option<&mut Extra> {
+        match self {
+            Expression::Identifier(Identifier { extra, .. })
+            | Expression::NumericLiteral(NumericLiteral { extra, .. })
+            | Expression::StringLiteral(StringLiteral { extra, .. })
+            | Expression::FunctionExpression(Function { extra, .. })
+            | Expression::MemberExpression(MemberExpression { extra, .. })
+            | Expression::ThisExpression { extra, .. }
+            | Expression::NullLiteral { extra, .. }
+            | Expression::BooleanLiteral { extra, .. }
+            | Expression::BigIntLiteral { extra, .. }
+            | Expression::RegExpLiteral { extra, .. }
+            | Expression::TemplateLiteral { extra, .. }
+            | Expression::ArrayExpression { extra, .. }
+            | Expression::ObjectExpression { extra, .. }
+            | Expression::ArrowFunctionExpression { extra, .. }
+            | Expression::SequenceExpression { extra, .. }
+            | Expression::AssignmentExpression { extra, .. }
+            | Expression::BinaryExpression { extra, .. }
+            | Expression::LogicalExpression { extra, .. }
+            | Expression::UnaryExpression { extra, .. }
+            | Expression::UpdateExpression { extra, .. }
+            | Expression::AwaitExpression { extra, .. }
+            | Expression::ConditionalExpression { extra, .. }
+            | Expression::CallExpression { extra, .. }
+            | Expression::NewExpression { extra, .. } => Some(extra),
+            Expression::ParenthesizedExpression { .. } => None,
+        }
+    }
+
+    pub fn is_parenthesized(&self) -> bool {
+        matches!(self.extra(), Extra::Parenthesized { .. })
+    }
+
+    pub fn extra(&self) -> &Extra {
+        match self {
+            Expression::Identifier(Identifier { extra, .. })
+            | Expression::NumericLiteral(NumericLiteral { extra, .. })
+            | Expression::StringLiteral(StringLiteral { extra, .. })
+            | Expression::FunctionExpression(Function { extra, .. })
+            | Expression::MemberExpression(MemberExpression { extra, .. })
+            | Expression::ThisExpression { extra, .. }
+            | Expression::NullLiteral { extra, .. }
+            | Expression::BooleanLiteral { extra, .. }
+            | Expression::BigIntLiteral { extra, .. }
+            | Expression::RegExpLiteral { extra, .. }
+            | Expression::TemplateLiteral { extra, .. }
+            | Expression::ArrayExpression { extra, .. }
+            | Expression::ObjectExpression { extra, .. }
+            | Expression::ArrowFunctionExpression { extra, .. }
+            | Expression::SequenceExpression { extra, .. }
+            | Expression::AssignmentExpression { extra, .. }
+            | Expression::BinaryExpression { extra, .. }
+            | Expression::LogicalExpression { extra, .. }
+            | Expression::UnaryExpression { extra, .. }
+            | Expression::UpdateExpression { extra, .. }
+            | Expression::AwaitExpression { extra, .. }
+            | Expression::ConditionalExpression { extra, .. }
+            | Expression::CallExpression { extra, .. }
+            | Expression::NewExpression { extra, .. } => extra,
+            Expression::ParenthesizedExpression { .. } => &Extra::None,
+        }
+    }
+}
+
+impl Pattern {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Pattern::Identifier(Identifier { loc, .. })
+            | Pattern::MemberExpression(MemberExpression { loc, .. })
+            | Pattern::ObjectPattern { loc, .. }
+            | Pattern::ArrayPattern { loc, .. }
+            | Pattern::AssignmentPattern { loc, .. } => loc,
+        }
+    }
+}
+
+impl PropertyKey {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            PropertyKey::Identifier(Identifier { loc, .. })
+            | PropertyKey::StringLiteral(StringLiteral { loc, .. })
+            | PropertyKey::NumericLiteral(NumericLiteral { loc, .. }) => loc,
+        }
+    }
+}
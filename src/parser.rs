@@ -1,3 +1,5 @@
+use crate::comments;
+use crate::diagnostic::Diagnostic;
 use crate::exp::declaration_exp::build_let;
 use crate::exp::for_exp::build_for;
 use crate::exp::function_exp::build_function;
@@ -6,218 +8,1059 @@ use crate::exp::switch_exp::build_switch;
 use crate::exp::try_exp::build_try;
 use crate::exp::while_exp::{build_do_while, build_while};
 use crate::express::{expect, is_ctrl_word, parse_expression};
-use crate::lex::{Lex, Loc};
-use crate::node::Node;
-use crate::node::Node::{
-    BlockStatement, BreakStatement, ContinueStatement, EmptyStatement, ReturnStatement,
-    ThrowStatement,
+use crate::lex::{self, Lex, LexState, Loc, Position};
+use crate::node::Statement::{
+    BreakStatement, ContinueStatement, EmptyStatement, ExpressionStatement, LabeledStatement,
+    ReturnStatement, ThrowStatement, WithStatement,
 };
+use crate::node::{
+    AttachedComments, BlockStatement, Comment, CommentKind, Declaration, Directive, Expression,
+    Extra, Identifier, ModuleItem, Pattern, Program, SourceType, Statement, StringLiteral,
+};
+use crate::sourcemap;
 use crate::token::Token;
+use std::collections::VecDeque;
+use std::fmt;
 
-#[derive(PartialEq, Debug)]
-pub enum IsArrowFunction {
+#[derive(PartialEq, Debug, Clone)]
+pub enum IsForIn {
     Impossible,
     Maybe,
     Must,
 }
 
-#[derive(PartialEq, Debug)]
-pub enum IsForIn {
-    Impossible,
-    Maybe,
-    Must,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum EcmaVersion {
+    Es5,
+    Es2015,
+    Es2016,
+    Es2017,
+    Es2018,
+    Es2019,
+    Es2020,
+    Es2021,
+    Es2022,
+    Es2023,
+    Es2024,
+    Es2025,
+    #[default]
+    Latest,
+}
+
+impl fmt::Display for EcmaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcmaVersion::Es5 => write!(f, "ES5"),
+            EcmaVersion::Latest => write!(f, "latest"),
+            version => write!(f, "ES{}", 2015 + *version as usize - 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub source_type: SourceType,
+    /// Syntax newer than this is an error.
+    pub ecma_version: EcmaVersion,
+    /// Allow `return` at the top level, as CommonJS always does.
+    pub allow_return_outside_function: bool,
+    /// Allow a `#!` line at the start. It is ES2023 syntax, but engines
+    /// accepted it long before, so `ecma_version` does not apply.
+    pub allow_hashbang: bool,
+    /// Allow `await` outside of any function, as a module allows it from
+    /// ES2022 on. In a script `await` is otherwise an identifier.
+    pub allow_await_outside_function: bool,
+    /// Keep parentheses as `ParenthesizedExpression` nodes.
+    pub preserve_parens: bool,
+    pub proposals: Proposals,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            source_type: SourceType::Script,
+            ecma_version: EcmaVersion::Latest,
+            allow_return_outside_function: false,
+            allow_hashbang: true,
+            allow_await_outside_function: false,
+            preserve_parens: false,
+            proposals: Proposals::default(),
+        }
+    }
+}
+
+/// Syntax from proposals not yet in the standard, each off by default.
+/// `ecma_version` does not apply to them.
+#[derive(Debug, Clone, Default)]
+pub struct Proposals {
+    /// `throw` as an expression, as in `f(() => throw e)`; it is written as
+    /// a `UnaryExpression` the way Babel does.
+    pub throw_expressions: bool,
+}
+
+/// A snapshot of the parser taken by [`Parser::checkpoint`].
+#[derive(Clone)]
+pub struct Checkpoint {
+    lex: LexState,
+    current: Token,
+    current_state: LexState,
+    loc: Loc,
+    last_loc: Loc,
+    lookahead: VecDeque<(Token, Loc, LexState)>,
+    comments: usize,
+    is_for_in: IsForIn,
+    in_for_init: bool,
+    is_identity_keyword: bool,
+    maybe_pattern: bool,
+    brackets: Vec<(String, Loc)>,
+    strict: bool,
+    return_allowed: bool,
+    in_function: bool,
+    is_identity_finally: bool,
 }
 
 pub struct Parser {
     pub current: Token,
-    pub is_arrow_function: IsArrowFunction,
     pub is_for_in: IsForIn,
     pub in_for_init: bool,
     pub list: Vec<Token>,
     pub loc: Loc,
-    pub last_loc_line: usize,
-    comment: Option<Token>,
-    pub regex_allowed: bool,
+    pub last_loc: Loc,
     pub is_identity_keyword: bool,
+    pub maybe_pattern: bool,
     pub is_identity_finally: bool,
+    pub hashbang: Option<String>,
+    /// The URL of the last `//# sourceMappingURL=` comment seen.
+    pub source_mapping_url: Option<String>,
+    /// Every comment lexed so far, in source order.
+    pub comments: Vec<Comment>,
+    pub options: ParseOptions,
+    /// Whether `return` may appear here: inside a function, or anywhere if
+    /// the options allow it.
+    pub return_allowed: bool,
+    /// Whether the code here is strict mode code: a module, or inside a
+    /// `"use strict"` program or function.
+    pub strict: bool,
+    /// Whether the code here is inside a function, where `await` is not
+    /// the one of the top level.
+    pub in_function: bool,
+    /// Set by [`Parser::parse_tolerant`].
+    tolerant: bool,
+    /// Set once a lexer error ended the input.
+    halted: bool,
+    diagnostics: Vec<Diagnostic>,
+    /// The located form of the last error, when it has more to say than
+    /// the current token; see [`Parser::fail`].
+    error: Option<Diagnostic>,
+    /// The brackets opened up to `current` and not yet closed.
+    brackets: Vec<(String, Loc)>,
     lex: Lex,
+    current_state: LexState,
+    lookahead: VecDeque<(Token, Loc, LexState)>,
 }
 
 impl Parser {
     pub fn new(input: String) -> Result<Parser, String> {
+        Parser::new_with_options(input, ParseOptions::default())
+    }
+
+    pub fn new_with_options(input: String, options: ParseOptions) -> Result<Parser, String> {
         let mut lex = Lex::new(input.to_string());
         let mut current;
         let mut loc;
+        let mut hashbang = None;
+        let mut source_mapping_url = None;
+        let mut comments = vec![];
+        // A lexer error here ends the input as it would later on.
+        let mut lex_error = None;
         loop {
-            (current, loc) = lex.next()?;
+            (current, loc) = match lex.next() {
+                Ok(token) => token,
+                Err(message) => {
+                    let loc = lex.error_loc();
+                    lex_error = Some(Diagnostic::new(message, loc.clone()));
+                    let end = Loc {
+                        start: loc.start.clone(),
+                        end: loc.start,
+                    };
+                    (Token::EOF, end)
+                }
+            };
+            if let Token::Hashbang(s) = current {
+                if !options.allow_hashbang {
+                    return Err("Unexpected character '#'".to_string());
+                }
+                hashbang = Some(s);
+                continue;
+            }
+            if let Some(comment) = to_comment(&current, &loc) {
+                source_mapping_url =
+                    sourcemap::source_mapping_url(&comment.value).or(source_mapping_url);
+                comments.push(comment);
+                continue;
+            }
             if current != Token::LF || current == Token::EOF {
                 break;
             }
         }
 
-        let parser = Parser {
-            comment: None,
+        let mut parser = Parser {
             current: current.clone(),
             loc: loc.clone(),
-            last_loc_line: 0,
-            is_arrow_function: IsArrowFunction::Maybe,
+            last_loc: Loc::default(),
             in_for_init: false,
             is_for_in: IsForIn::Maybe,
             list: vec![current],
-            regex_allowed: true,
             is_identity_keyword: false,
+            maybe_pattern: false,
             is_identity_finally: false,
+            hashbang,
+            source_mapping_url,
+            comments,
+            return_allowed: options.allow_return_outside_function
+                || options.source_type == SourceType::CommonJs,
+            strict: options.source_type == SourceType::Module,
+            in_function: false,
+            options,
+            tolerant: false,
+            halted: lex_error.is_some(),
+            diagnostics: lex_error.into_iter().collect(),
+            error: None,
+            brackets: vec![],
+            current_state: lex.state(),
             lex,
+            lookahead: VecDeque::new(),
         };
+        parser.require_escapes()?;
+        parser.track_bracket();
 
         Ok(parser)
     }
 
     pub fn next(&mut self) -> Result<(), String> {
         println!("line: {}", self.loc.end.line);
-        self.lex.regex_allowed = self.regex_allowed;
-        self.last_loc_line = self.loc.end.line;
+        self.last_loc = self.loc.clone();
+        (self.current, self.loc, self.current_state) = match self.lookahead.pop_front() {
+            Some(token) => token,
+            None => self.lex_token()?,
+        };
+        self.track_bracket();
+        Ok(())
+    }
+
+    /// Keeps `brackets` up to date with `current`. A closing bracket of the
+    /// wrong kind leaves the open one for the error to point at.
+    fn track_bracket(&mut self) {
+        let Token::Control(s) = &self.current else {
+            return;
+        };
+        match s.as_str() {
+            "(" | "[" | "{" => self.brackets.push((s.clone(), self.loc.clone())),
+            ")" | "]" | "}" => {
+                if self
+                    .brackets
+                    .last()
+                    .is_some_and(|(open, _)| closing(open) == s)
+                {
+                    self.brackets.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Where the innermost bracket that `close` would close was opened.
+    pub fn opening(&self, close: &str) -> Option<&(String, Loc)> {
+        self.brackets
+            .last()
+            .filter(|(open, _)| closing(open) == close)
+    }
+
+    /// Keeps `diagnostic` for [`Parser::parse`] to report and returns its
+    /// message, to travel up as the error.
+    pub fn fail(&mut self, diagnostic: Diagnostic) -> String {
+        let message = diagnostic.message.clone();
+        self.error = Some(diagnostic);
+        message
+    }
+
+    /// The error `message` at the current token, or as [`Parser::fail`]
+    /// reported it.
+    fn locate(&mut self, message: String) -> Diagnostic {
+        match self.error.take() {
+            Some(diagnostic) if diagnostic.message == message => diagnostic,
+            _ => Diagnostic::new(message, self.loc.clone()),
+        }
+    }
+
+    /// Reports a lexer error and ends the input there: the lexer cannot
+    /// skip past it.
+    fn halt(&mut self, message: String) -> (Token, Loc, LexState) {
+        let loc = self.lex.error_loc();
+        if !self.halted {
+            self.diagnostics.push(Diagnostic::new(message, loc.clone()));
+            self.halted = true;
+        }
+        let end = Loc {
+            start: loc.start.clone(),
+            end: loc.start,
+        };
+        (Token::EOF, end, self.lex.state())
+    }
+
+    fn lex_token(&mut self) -> Result<(Token, Loc, LexState), String> {
         loop {
-            (self.current, self.loc) = self.lex.next()?;
-            /*if self.last_loc_line> 9320 {
-                println!("token: {}", self.current);
-            }*/
-            // self.list.insert(0, self.current.clone());
-            if !matches!(self.current, Token::Comment(_)) {
-                break;
+            let (token, loc) = match self.lex.next() {
+                Ok(token) => token,
+                Err(message) if self.tolerant => return Ok(self.halt(message)),
+                Err(message) => return Err(message),
+            };
+            let Some(comment) = to_comment(&token, &loc) else {
+                self.require_escapes()?;
+                return Ok((token, loc, self.lex.state()));
+            };
+            if let Some(url) = sourcemap::source_mapping_url(&comment.value) {
+                self.source_mapping_url = Some(url);
+            }
+            self.comments.push(comment);
+        }
+    }
+
+    /// Looks `n` tokens past `current` without consuming anything;
+    /// `peek_token(0)` is `current` itself. A `/` is always peeked as divide.
+    pub fn peek_token(&mut self, n: usize) -> Result<&Token, String> {
+        if n == 0 {
+            return Ok(&self.current);
+        }
+        while self.lookahead.len() < n {
+            let token = self.lex_token()?;
+            self.lookahead.push_back(token);
+        }
+        Ok(&self.lookahead[n - 1].0)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            lex: self.lex.state(),
+            current: self.current.clone(),
+            current_state: self.current_state.clone(),
+            loc: self.loc.clone(),
+            last_loc: self.last_loc.clone(),
+            lookahead: self.lookahead.clone(),
+            comments: self.comments.len(),
+            is_for_in: self.is_for_in.clone(),
+            in_for_init: self.in_for_init,
+            is_identity_keyword: self.is_identity_keyword,
+            maybe_pattern: self.maybe_pattern,
+            brackets: self.brackets.clone(),
+            strict: self.strict,
+            return_allowed: self.return_allowed,
+            in_function: self.in_function,
+            is_identity_finally: self.is_identity_finally,
+        }
+    }
+
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.lex.restore(checkpoint.lex);
+        self.current = checkpoint.current;
+        self.current_state = checkpoint.current_state;
+        self.loc = checkpoint.loc;
+        self.last_loc = checkpoint.last_loc;
+        self.lookahead = checkpoint.lookahead;
+        self.comments.truncate(checkpoint.comments);
+        self.is_for_in = checkpoint.is_for_in;
+        self.in_for_init = checkpoint.in_for_init;
+        self.is_identity_keyword = checkpoint.is_identity_keyword;
+        self.maybe_pattern = checkpoint.maybe_pattern;
+        self.brackets = checkpoint.brackets;
+        self.strict = checkpoint.strict;
+        self.return_allowed = checkpoint.return_allowed;
+        self.in_function = checkpoint.in_function;
+        self.is_identity_finally = checkpoint.is_identity_finally;
+        self.error = None;
+    }
+
+    /// Runs `f` speculatively, rewinding to where it started if it fails.
+    pub fn try_parse<T>(&mut self, f: impl FnOnce(&mut Parser) -> Result<T, String>) -> Option<T> {
+        let checkpoint = self.checkpoint();
+        // Recovering inside `f` would hide that it failed.
+        let tolerant = std::mem::replace(&mut self.tolerant, false);
+        let result = f(self);
+        self.tolerant = tolerant;
+        match result {
+            Ok(result) => Some(result),
+            Err(_) => {
+                self.rewind(checkpoint);
+                None
+            }
+        }
+    }
+
+    /// Re-scans the current `/` or `/=` token as a regex literal.
+    pub fn reread_as_regex(&mut self) -> Result<(), String> {
+        self.lex.restore(self.current_state.clone());
+        self.lookahead.clear();
+        let start = self.loc.start.index;
+        self.comments
+            .retain(|comment| comment.loc.start.index < start);
+        (self.current, self.loc) = self.lex.reread_as_regex()?;
+        self.current_state = self.lex.state();
+        Ok(())
+    }
+
+    /// Re-scans the current `` ` `` or `}` token as one part of a template,
+    /// and tells whether a `${` ends it. That `${` stays open in `brackets`
+    /// until its `}`.
+    pub fn reread_as_template_part(&mut self) -> Result<bool, String> {
+        self.lex.restore(self.current_state.clone());
+        self.lookahead.clear();
+        let start = self.loc.start.index;
+        self.comments
+            .retain(|comment| comment.loc.start.index < start);
+        (self.current, self.loc) = self.lex.reread_as_template_part()?;
+        self.current_state = self.lex.state();
+        let substitution = self.lex.input().chars().nth(self.loc.end.index - 1) == Some('{');
+        if substitution {
+            self.brackets.push(("{".to_string(), self.loc.clone()));
+        }
+        Ok(substitution)
+    }
+
+    /// Fails unless the target version has `feature`, which came in `version`.
+    pub fn require(&self, version: EcmaVersion, feature: &str) -> Result<(), String> {
+        if self.options.ecma_version < version {
+            return Err(format!("{feature} requires {version}"));
+        }
+        Ok(())
+    }
+
+    /// Fails if `name` is reserved or an escaped keyword, or in strict mode
+    /// if `name` is reserved there.
+    pub fn check_identifier(&self, name: &str) -> Result<(), String> {
+        if matches!(name, "import" | "export" | "enum") {
+            return Err(format!("Unexpected reserved word '{name}'"));
+        }
+        // The lexer only reads a keyword as a name when it was escaped.
+        let escaped = match lex::keyword(name) {
+            Some(Token::Let | Token::Yield) => self.strict,
+            Some(Token::Await | Token::Async | Token::Undefined) | None => false,
+            Some(_) => true,
+        };
+        if escaped {
+            return Err(format!(
+                "Keyword must not contain escaped characters: {name}"
+            ));
+        }
+        if self.strict && is_strict_reserved_word(name) {
+            return Err(format!("Unexpected strict mode reserved word '{name}'"));
+        }
+        Ok(())
+    }
+
+    /// Fails in strict mode if `pattern` declares `eval` or `arguments`.
+    pub fn check_binding(&self, pattern: &Pattern) -> Result<(), String> {
+        self.check_target(pattern, "Binding")
+    }
+
+    /// Fails in strict mode if `pattern` assigns to `eval` or `arguments`.
+    pub fn check_assignment(&self, pattern: &Pattern) -> Result<(), String> {
+        self.check_target(pattern, "Assigning to")
+    }
+
+    fn check_target(&self, pattern: &Pattern, verb: &str) -> Result<(), String> {
+        if !self.strict {
+            return Ok(());
+        }
+        bound_names(pattern)
+            .into_iter()
+            .try_for_each(|identifier| check_eval(identifier, verb))
+    }
+
+    /// Fails in strict mode on a legacy octal number or string escape.
+    pub fn check_literal(&self, token: &Token) -> Result<(), String> {
+        if !self.strict {
+            return Ok(());
+        }
+        match token {
+            Token::Digit(raw) if is_legacy_octal(raw) => {
+                Err("Legacy octal literals are not allowed in strict mode".to_string())
+            }
+            Token::String(_, raw) if has_octal_escape(raw) => {
+                Err("Octal escape sequences are not allowed in strict mode".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The checks strict mode makes on a function's name and parameters,
+    /// which a `"use strict"` in the body applies too.
+    pub fn check_params(&self, id: Option<&Identifier>, params: &[Pattern]) -> Result<(), String> {
+        let names: Vec<&Identifier> = params.iter().flat_map(bound_names).collect();
+        for identifier in id.into_iter().chain(names.iter().copied()) {
+            if is_strict_reserved_word(&identifier.name) {
+                return Err(format!(
+                    "Unexpected strict mode reserved word '{}'",
+                    identifier.name
+                ));
             }
+            check_eval(identifier, "Binding")?;
+        }
+        match duplicate_param(params) {
+            Some(name) => Err(format!("Duplicate parameter name '{name}' in strict mode")),
+            None => Ok(()),
+        }
+    }
+
+    /// An arrow function may not repeat a parameter name in any mode.
+    pub fn check_arrow_params(&self, params: &[Pattern]) -> Result<(), String> {
+        match duplicate_param(params) {
+            Some(name) => Err(format!(
+                "Duplicate parameter name '{name}' in arrow function"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if the identifier just lexed uses a `\u{...}` escape the
+    /// target version does not have.
+    fn require_escapes(&self) -> Result<(), String> {
+        if self.lex.has_code_point_escape() {
+            self.require(EcmaVersion::Es2015, "Unicode code point escape")?;
         }
-        self.regex_allowed = false;
         Ok(())
     }
 
     pub fn is_same_line(&self) -> bool {
-        self.last_loc_line == self.loc.start.line
+        self.last_loc.end.line == self.loc.start.line
+    }
+
+    pub fn start_position(&self) -> Position {
+        self.loc.start.clone()
+    }
+
+    /// The loc from `start` to the end of the last consumed token.
+    pub fn loc_from(&self, start: Position) -> Loc {
+        Loc {
+            start,
+            end: self.last_loc.end.clone(),
+        }
+    }
+
+    /// A program or function body: the directive prologue, which may turn
+    /// on strict mode, then the statements.
+    fn parse_body(parser: &mut Parser) -> Result<(Vec<Directive>, Vec<Statement>), String> {
+        let mut directives: Vec<Directive> = vec![];
+        let mut body = vec![];
+        while let Token::String(..) = parser.current {
+            let statement = parser.parse_list_item()?;
+            let Some(directive) = to_directive(&statement) else {
+                body.push(statement);
+                break;
+            };
+            if directive.raw_value() == "use strict" {
+                parser.strict = true;
+                // The directives before it are strict code too.
+                for directive in &directives {
+                    let StringLiteral { value, raw, .. } = &directive.value;
+                    parser.check_literal(&Token::String(value.clone(), raw.clone()))?;
+                }
+            }
+            directives.push(directive);
+        }
+        body.extend(Parser::parse_statement_list(parser)?);
+        Ok((directives, body))
     }
 
-    pub fn parse_statement_list(parser: &mut Parser) -> Result<Vec<Node>, String> {
+    pub fn parse_statement_list(parser: &mut Parser) -> Result<Vec<Statement>, String> {
         let mut ast = vec![];
         loop {
             match &parser.current {
                 Token::EOF => break,
-                Token::Comment(_) => {
-                    parser.comment = Some(parser.current.clone());
+                Token::Control(s) if s == ";" => {
                     parser.next()?;
                 }
-                Token::Control(s) => match s.as_str() {
-                    ";" => {
-                        parser.regex_allowed = true;
-                        parser.next()?;
-                    }
-                    "}" => break,
-                    _ => ast.push(*parse_expression(parser, 0)?),
-                },
+                Token::Control(s) if s == "}" => break,
                 Token::Case | Token::Default => {
                     break;
                 }
-                Token::Var | Token::Let | Token::Const => {
-                    ast.push(*build_let(parser)?);
-                }
-                Token::For => {
-                    ast.push(*build_for(parser)?);
-                }
-                Token::Function => {
-                    ast.push(*build_function(parser, true)?);
-                }
-                Token::If => {
-                    ast.push(*build_if(parser)?);
-                }
-                Token::While => {
-                    ast.push(*build_while(parser)?);
-                }
-                Token::Do => {
-                    ast.push(*build_do_while(parser)?);
-                }
-                Token::Try => {
-                    ast.push(*build_try(parser)?);
+                _ => ast.push(parser.parse_list_item()?),
+            }
+        }
+        Ok(ast)
+    }
+
+    /// A statement, or in tolerant mode the [`Statement::Invalid`] that
+    /// replaces it.
+    fn parse_list_item(&mut self) -> Result<Statement, String> {
+        let start = self.start_position();
+        match Parser::parse_statement(self) {
+            Ok(statement) => Ok(*statement),
+            Err(message) if self.tolerant => Ok(self.recover(message, start)),
+            Err(message) => Err(message),
+        }
+    }
+
+    pub fn parse_statement(parser: &mut Parser) -> Result<Box<Statement>, String> {
+        let is_label = matches!(parser.current, Token::Variable(_))
+            && is_ctrl_word(parser.peek_token(1)?, ":");
+        if let Token::Variable(s) = &parser.current
+            && (s == "import" || s == "export")
+        {
+            // `import(...)` and `import.meta` are expressions.
+            let keyword = s.clone();
+            let next = parser.peek_token(1)?;
+            if !is_ctrl_word(next, "(") && !is_ctrl_word(next, ".") {
+                return Err(match parser.options.source_type {
+                    SourceType::Module => format!("'{keyword}' declarations are not supported"),
+                    _ => "'import' and 'export' may appear only with 'sourceType: module'"
+                        .to_string(),
+                });
+            }
+        }
+        let start = parser.start_position();
+        let statement = match &parser.current {
+            Token::Var | Token::Let | Token::Const => {
+                let mut declaration = build_let(parser)?;
+                parser.eat_semicolon()?;
+                declaration.loc = parser.loc_from(start);
+                Box::new(Statement::Declaration(Declaration::VariableDeclaration(
+                    declaration,
+                )))
+            }
+            Token::For => build_for(parser)?,
+            Token::Function => Box::new(Statement::Declaration(Declaration::FunctionDeclaration(
+                build_function(parser, true)?,
+            ))),
+            Token::If => build_if(parser)?,
+            Token::While => build_while(parser)?,
+            Token::Do => build_do_while(parser)?,
+            Token::Try => build_try(parser)?,
+            Token::Switch => build_switch(parser)?,
+            Token::With => {
+                if parser.strict {
+                    return Err("'with' in strict mode".to_string());
                 }
-                Token::Switch => {
-                    ast.push(*build_switch(parser)?);
+                parser.next()?;
+                expect(parser, "(")?;
+                let object = parse_expression(parser, 0)?;
+                expect(parser, ")")?;
+                let body = Parser::parse_statement(parser)?;
+                Box::new(WithStatement {
+                    object,
+                    body,
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Return => {
+                if !parser.return_allowed {
+                    return Err("'return' outside of function".to_string());
                 }
-                Token::Return => {
-                    parser.regex_allowed = true;
-                    parser.next()?;
-                    if !parser.is_same_line() || parser.current == Token::EOF {
-                        ast.push(ReturnStatement { argument: None })
-                    } else if is_ctrl_word(&parser.current, "}")
-                        || is_ctrl_word(&parser.current, ";")
-                    {
-                        ast.push(ReturnStatement { argument: None })
-                    } else {
-                        ast.push(ReturnStatement {
-                            argument: Some(parse_expression(parser, 0)?),
-                        })
-                    }
-                }
-                Token::Break => {
-                    parser.next()?;
-                    ast.push(BreakStatement { label: None })
+                parser.next()?;
+                let argument = if !parser.is_same_line()
+                    || parser.current == Token::EOF
+                    || is_ctrl_word(&parser.current, "}")
+                    || is_ctrl_word(&parser.current, ";")
+                {
+                    None
+                } else {
+                    Some(parse_expression(parser, 0)?)
+                };
+                parser.eat_semicolon()?;
+                Box::new(ReturnStatement {
+                    argument,
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Break => {
+                parser.next()?;
+                let label = parser.parse_jump_label()?;
+                parser.eat_semicolon()?;
+                Box::new(BreakStatement {
+                    label,
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Continue => {
+                parser.next()?;
+                let label = parser.parse_jump_label()?;
+                parser.eat_semicolon()?;
+                Box::new(ContinueStatement {
+                    label,
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Control(s) if s == "{" => {
+                Box::new(Statement::BlockStatement(Parser::parse_block(parser)?))
+            }
+            Token::Control(s) if s == ";" => {
+                parser.next()?;
+                Box::new(EmptyStatement {
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Variable(s) if is_label => {
+                parser.check_identifier(s)?;
+                let label = Identifier {
+                    name: s.to_string(),
+                    extra: Extra::None,
+                    loc: parser.loc.clone(),
+                };
+                parser.next()?;
+                parser.next()?;
+                let body = Parser::parse_statement(parser)?;
+                Box::new(LabeledStatement {
+                    label,
+                    body,
+                    loc: parser.loc_from(start),
+                })
+            }
+            Token::Throw => {
+                parser.next()?;
+                if !parser.is_same_line() || parser.current == Token::EOF {
+                    return Err("expression expected".to_string());
                 }
-                Token::Continue => {
-                    parser.next()?;
-                    ast.push(ContinueStatement { label: None })
+                if is_ctrl_word(&parser.current, "}") || is_ctrl_word(&parser.current, ";") {
+                    return Err("Unexpected token".to_string());
                 }
-                Token::Throw => {
-                    parser.regex_allowed = true;
-                    parser.next()?;
-                    if !parser.is_same_line() || parser.current == Token::EOF {
-                        return Err("expression expected".to_string());
-                    }
-                    if is_ctrl_word(&parser.current, "}") || is_ctrl_word(&parser.current, ";") {
-                        return Err("Unexpected token".to_string());
-                    }
-                    ast.push(ThrowStatement {
-                        argument: parse_expression(parser, 0)?,
-                    })
+                let argument = parse_expression(parser, 0)?;
+                parser.eat_semicolon()?;
+                Box::new(ThrowStatement {
+                    argument,
+                    loc: parser.loc_from(start),
+                })
+            }
+            _ => {
+                let expression = parse_expression(parser, 0)?;
+                parser.eat_semicolon()?;
+                Box::new(ExpressionStatement {
+                    expression,
+                    loc: parser.loc_from(start),
+                })
+            }
+        };
+        Ok(statement)
+    }
+
+    /// Reports `message` and skips the rest of the statement from `start`:
+    /// up to a line break, or a `;`, the `}` of the enclosing block or a
+    /// keyword that starts a statement outside any brackets opened on the
+    /// way.
+    fn recover(&mut self, message: String, start: Position) -> Statement {
+        if !self.halted {
+            let diagnostic = self.locate(message);
+            self.diagnostics.push(diagnostic);
+        }
+        // Skip at least one token, or the same error comes back. A line
+        // break ends the statement too, since most statements end there.
+        let mut progressed = self.loc.start.index > start.index;
+        let mut depth: usize = 0;
+        loop {
+            let stop = match &self.current {
+                Token::EOF => break,
+                _ if progressed && !self.is_same_line() => break,
+                Token::Control(s) if depth == 0 && s == ";" => true,
+                Token::Control(s) if depth == 0 && s == "}" && progressed => break,
+                Token::Control(s) if s == "(" || s == "[" || s == "{" => {
+                    depth += 1;
+                    false
                 }
-                _ => {
-                    ast.push(*parse_expression(parser, 0)?);
+                Token::Control(s) if s == ")" || s == "]" || s == "}" => {
+                    depth = depth.saturating_sub(1);
+                    false
                 }
+                token if depth == 0 && progressed && starts_statement(token) => break,
+                _ => false,
+            };
+            if self.next().is_err() || stop {
+                break;
             }
+            progressed = true;
         }
-        Ok(ast)
+        let end = if self.last_loc.end.index > start.index {
+            self.last_loc.end.clone()
+        } else {
+            start.clone()
+        };
+        Statement::Invalid {
+            loc: Loc { start, end },
+        }
+    }
+
+    fn eat_semicolon(&mut self) -> Result<(), String> {
+        if is_ctrl_word(&self.current, ";") {
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    fn parse_jump_label(&mut self) -> Result<Option<Identifier>, String> {
+        if let Token::Variable(s) = &self.current
+            && self.is_same_line()
+        {
+            self.check_identifier(s)?;
+            let label = Identifier {
+                name: s.to_string(),
+                extra: Extra::None,
+                loc: self.loc.clone(),
+            };
+            self.next()?;
+            return Ok(Some(label));
+        }
+        Ok(None)
     }
 
-    pub fn parse_block(parser: &mut Parser) -> Result<Box<Node>, String> {
-        let consequent: Box<Node>;
+    pub fn parse_block(parser: &mut Parser) -> Result<BlockStatement, String> {
         if !is_ctrl_word(&parser.current, "{") {
             return Err("handle_block expect {".to_string());
         }
-        parser.regex_allowed = true;
+        let start = parser.start_position();
         parser.next()?;
-        consequent = Box::new(BlockStatement {
-            body: Parser::parse_statement_list(parser)?,
-        });
+        let body = Parser::parse_statement_list(parser)?;
         expect(parser, "}")?;
-        Ok(consequent)
+        Ok(BlockStatement {
+            directives: vec![],
+            body,
+            loc: parser.loc_from(start),
+        })
     }
 
-    pub fn build_maybe_empty_body(&mut self) -> Result<Box<Node>, String> {
-        let body: Box<Node>;
-        if is_ctrl_word(&self.current, "{") {
-            body = Parser::parse_block(self)?;
-        } else if is_ctrl_word(&self.current, ";") {
-            body = Box::new(EmptyStatement {});
-            self.next()?;
-        } else {
-            return Err("for body error".to_string());
+    /// A function body, where `return` is allowed. It is strict if the code
+    /// around is, or if it says `"use strict"`; then the name and parameters
+    /// of the function get the checks of strict mode too.
+    pub fn parse_function_body(
+        parser: &mut Parser,
+        id: Option<&Identifier>,
+        params: &[Pattern],
+    ) -> Result<BlockStatement, String> {
+        let start = parser.start_position();
+        expect(parser, "{")?;
+        let return_allowed = std::mem::replace(&mut parser.return_allowed, true);
+        let in_function = std::mem::replace(&mut parser.in_function, true);
+        let strict = parser.strict;
+        let body = Parser::parse_body(parser);
+        parser.return_allowed = return_allowed;
+        parser.in_function = in_function;
+        let function_strict = std::mem::replace(&mut parser.strict, strict);
+        let (directives, body) = body?;
+        expect(parser, "}")?;
+        let use_strict = directives
+            .iter()
+            .any(|directive| directive.raw_value() == "use strict");
+        if use_strict
+            && !params
+                .iter()
+                .all(|param| matches!(param, Pattern::Identifier(_)))
+        {
+            return Err(
+                "Illegal 'use strict' directive in function with non-simple parameter list"
+                    .to_string(),
+            );
+        }
+        if function_strict {
+            parser.check_params(id, params)?;
+        }
+        Ok(BlockStatement {
+            directives,
+            body,
+            loc: parser.loc_from(start),
+        })
+    }
+
+    /// The expression body of an arrow function, which like a block body is
+    /// inside a function.
+    pub fn parse_arrow_body(parser: &mut Parser) -> Result<Box<Expression>, String> {
+        let in_function = std::mem::replace(&mut parser.in_function, true);
+        let body = parse_expression(parser, 2);
+        parser.in_function = in_function;
+        body
+    }
+
+    /// Parses the whole input. The error is located at the token it was
+    /// found at.
+    pub fn parse(&mut self) -> Result<Program, Diagnostic> {
+        if self.halted {
+            return Err(self.diagnostics.remove(0));
+        }
+        let (directives, body) =
+            Parser::parse_body(self).map_err(|message| self.locate(message))?;
+        Ok(self.program(directives, body))
+    }
+
+    /// Like [`Parser::parse`], but goes on after a syntax error: the
+    /// statement becomes [`Statement::Invalid`] and the error a
+    /// [`Diagnostic`]. A lexer error ends the input.
+    pub fn parse_tolerant(&mut self) -> (Program, Vec<Diagnostic>) {
+        self.tolerant = true;
+        let (directives, mut body) = Parser::parse_body(self).unwrap_or_default();
+        while self.current != Token::EOF {
+            // A `}`, `case` or `default` no statement list takes.
+            let message = format!("Unexpected token {}", self.current);
+            let start = self.start_position();
+            body.push(self.recover(message, start));
+            if let Ok(statements) = Parser::parse_statement_list(self) {
+                body.extend(statements);
+            }
         }
-        Ok(body)
+        self.tolerant = false;
+        let program = self.program(directives, body);
+        (program, std::mem::take(&mut self.diagnostics))
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Node>, String> {
-        Parser::parse_statement_list(self)
+    fn program(&mut self, directives: Vec<Directive>, body: Vec<Statement>) -> Program {
+        let mut program = Program {
+            directives,
+            body: body.into_iter().map(ModuleItem::Statement).collect(),
+            comments: self.comments.clone(),
+            attached_comments: AttachedComments::new(),
+            source_type: self.options.source_type,
+            loc: Loc {
+                start: Position {
+                    line: 1,
+                    column: 1,
+                    index: 0,
+                },
+                end: self.loc.end.clone(),
+            },
+        };
+        program.attached_comments = comments::attach(&program, self.lex.input());
+        program
+    }
+}
+
+/// The bracket that closes `open`.
+fn closing(open: &str) -> &str {
+    match open {
+        "(" => ")",
+        "[" => "]",
+        _ => "}",
+    }
+}
+
+/// The identifiers `pattern` binds, or assigns to, in order.
+fn bound_names(pattern: &Pattern) -> Vec<&Identifier> {
+    match pattern {
+        Pattern::Identifier(identifier) => vec![identifier],
+        Pattern::MemberExpression(_) => vec![],
+        Pattern::ObjectPattern { properties, .. } => properties
+            .iter()
+            .flat_map(|property| bound_names(&property.value))
+            .collect(),
+        Pattern::ArrayPattern { elements, .. } => {
+            elements.iter().flatten().flat_map(bound_names).collect()
+        }
+        Pattern::AssignmentPattern { left, .. } => bound_names(left),
+    }
+}
+
+/// The first name `params` bind twice.
+fn duplicate_param(params: &[Pattern]) -> Option<&str> {
+    let names: Vec<&Identifier> = params.iter().flat_map(bound_names).collect();
+    names.iter().enumerate().find_map(|(i, identifier)| {
+        names[..i]
+            .iter()
+            .any(|name| name.name == identifier.name)
+            .then_some(identifier.name.as_str())
+    })
+}
+
+fn check_eval(identifier: &Identifier, verb: &str) -> Result<(), String> {
+    if identifier.name == "eval" || identifier.name == "arguments" {
+        return Err(format!("{verb} '{}' in strict mode", identifier.name));
     }
+    Ok(())
+}
+
+/// Reserved only in strict mode; `let` and `yield` are keywords to the lexer.
+fn is_strict_reserved_word(name: &str) -> bool {
+    matches!(
+        name,
+        "implements" | "interface" | "package" | "private" | "protected" | "public" | "static"
+    )
+}
+
+/// `010`, or `08` with a digit no octal has.
+fn is_legacy_octal(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    chars.next() == Some('0') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Whether the raw string has an escape like `\07` or `\8`; `\0` alone is
+/// the null character.
+fn has_octal_escape(raw: &str) -> bool {
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some('0') => {
+                if chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    return true;
+                }
+            }
+            Some('1'..='9') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn starts_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Var
+            | Token::Let
+            | Token::Const
+            | Token::Function
+            | Token::If
+            | Token::For
+            | Token::While
+            | Token::Do
+            | Token::Try
+            | Token::Switch
+            | Token::Return
+            | Token::Break
+            | Token::Continue
+            | Token::Throw
+            | Token::With
+    )
+}
+
+/// An expression statement of just a string literal; `("a")` is not one.
+fn to_directive(statement: &Statement) -> Option<Directive> {
+    let Statement::ExpressionStatement { expression, loc } = statement else {
+        return None;
+    };
+    let Expression::StringLiteral(value) = expression.as_ref() else {
+        return None;
+    };
+    (value.loc.start.index == loc.start.index).then(|| Directive {
+        value: value.clone(),
+        loc: loc.clone(),
+    })
+}
+
+fn to_comment(token: &Token, loc: &Loc) -> Option<Comment> {
+    let (kind, value) = match token {
+        Token::Comment(value) => (CommentKind::Line, value),
+        Token::BlockComment(value) => (CommentKind::Block, value),
+        _ => return None,
+    };
+    Some(Comment {
+        kind,
+        value: value.clone(),
+        loc: loc.clone(),
+    })
 }
 
 #[cfg(test)]
 mod parser_test {
-    use crate::parser::Parser;
+    use crate::express::{expect, parse_expression};
+    use crate::node::Statement::{BlockStatement, LabeledStatement};
+    use crate::node::{
+        ArrowFunctionBody, Declaration, Directive, Expression, ModuleItem, SourceType, Statement,
+    };
+    use crate::parser::{EcmaVersion, ParseOptions, Parser};
     use crate::token::Token;
 
     #[test]
@@ -229,6 +1072,663 @@ mod parser_test {
         assert_eq!(Token::Variable("a".to_string()), parser.current);
     }
 
+    #[test]
+    fn test_hashbang() -> Result<(), String> {
+        let mut parser = Parser::new("#!/usr/bin/env node\r\nlet a\u{2028}a".to_string())?;
+        assert_eq!(parser.hashbang, Some("/usr/bin/env node".to_string()));
+        assert_eq!(parser.parse()?.body.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_peek_token() -> Result<(), String> {
+        let mut parser = Parser::new("a = b / c".to_string())?;
+        assert_eq!(parser.peek_token(2)?, &Token::Variable("b".to_string()));
+        assert_eq!(parser.peek_token(1)?, &Token::Control("=".to_string()));
+        parser.next()?;
+        assert_eq!(parser.current, Token::Control("=".to_string()));
+        assert_eq!(parser.peek_token(2)?, &Token::Control("/".to_string()));
+        parser.next()?;
+        parser.next()?;
+        parser.next()?;
+        assert_eq!(parser.current, Token::Variable("c".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_after_peek() -> Result<(), String> {
+        let mut parser = Parser::new("/a/g.b".to_string())?;
+        assert_eq!(parser.peek_token(1)?, &Token::Variable("a".to_string()));
+        parser.reread_as_regex()?;
+        assert_eq!(
+            parser.current,
+            Token::Regex("a".to_string(), "g".to_string())
+        );
+        assert_eq!(parser.peek_token(1)?, &Token::Control(".".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkpoint_rewind() -> Result<(), String> {
+        let mut parser = Parser::new("(a, b) c".to_string())?;
+        let checkpoint = parser.checkpoint();
+        parser.next()?;
+        parser.next()?;
+        parser.in_for_init = true;
+        parser.rewind(checkpoint);
+        assert_eq!(parser.current, Token::Control("(".to_string()));
+        assert!(!parser.in_for_init);
+        let parsed = parser.try_parse(|p| {
+            parse_expression(p, 0)?;
+            expect(p, ";")
+        });
+        assert_eq!(parsed, None);
+        assert_eq!(parser.current, Token::Control("(".to_string()));
+        assert_eq!(parser.loc.start.column, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewind_directive() -> Result<(), String> {
+        let mut parser = Parser::new("'use strict'; a b".to_string())?;
+        let parsed = parser.try_parse(|p| {
+            p.return_allowed = true;
+            p.in_function = true;
+            Parser::parse_body(p)
+        });
+        assert!(parsed.is_none());
+        assert!(!parser.strict);
+        assert!(!parser.return_allowed);
+        assert!(!parser.in_function);
+        assert!(matches!(parser.current, Token::String(..)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_labeled_statement() -> Result<(), String> {
+        let mut parser = Parser::new(
+            "outer: for (;;) { inner: while (a) { break outer; continue\ninner } }".to_string(),
+        )?;
+        let ast = parser.parse()?;
+        assert!(matches!(
+            &ast.body[0],
+            ModuleItem::Statement(LabeledStatement { .. })
+        ));
+        assert_eq!(parser.current, Token::EOF);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_statement() -> Result<(), String> {
+        let mut parser = Parser::new("{ a: 1 }".to_string())?;
+        let ast = parser.parse()?;
+        let ModuleItem::Statement(BlockStatement(block)) = &ast.body[0] else {
+            panic!("expect block");
+        };
+        assert!(matches!(block.body[0], Statement::LabeledStatement { .. }));
+        Ok(())
+    }
+
+    fn parse_with(source: &str, options: ParseOptions) -> Result<Vec<ModuleItem>, String> {
+        Ok(Parser::new_with_options(source.to_string(), options)?
+            .parse()?
+            .body)
+    }
+
+    #[test]
+    fn test_ecma_version() {
+        for (source, version, message) in [
+            ("let a = 1", EcmaVersion::Es2015, "'let' declaration"),
+            ("a = `t`", EcmaVersion::Es2015, "Template literal"),
+            ("f = a => a", EcmaVersion::Es2015, "Arrow function"),
+            ("f = (a, b) => a", EcmaVersion::Es2015, "Arrow function"),
+            ("a = {b}", EcmaVersion::Es2015, "Shorthand property"),
+            ("a = {b() {}}", EcmaVersion::Es2015, "Method definition"),
+            (
+                "function f(a = 1) {}",
+                EcmaVersion::Es2015,
+                "Default parameter",
+            ),
+            (
+                "function f({a}) {}",
+                EcmaVersion::Es2015,
+                "Destructuring pattern",
+            ),
+            ("[a] = b", EcmaVersion::Es2015, "Destructuring assignment"),
+            ("a = 0b1", EcmaVersion::Es2015, "Binary literal"),
+            (
+                "a = '\\u{61}'",
+                EcmaVersion::Es2015,
+                "Unicode code point escape",
+            ),
+            (
+                "var \\u{61}",
+                EcmaVersion::Es2015,
+                "Unicode code point escape",
+            ),
+            (
+                "a = /x/y",
+                EcmaVersion::Es2015,
+                "Regular expression flag 'y'",
+            ),
+            (
+                "f(a,)",
+                EcmaVersion::Es2017,
+                "Trailing comma after arguments",
+            ),
+            (
+                "function f(a,) {}",
+                EcmaVersion::Es2017,
+                "Trailing comma after parameters",
+            ),
+            (
+                "a = /x/s",
+                EcmaVersion::Es2018,
+                "Regular expression flag 's'",
+            ),
+            (
+                "try {} catch {}",
+                EcmaVersion::Es2019,
+                "Optional catch binding",
+            ),
+            ("a = 1n", EcmaVersion::Es2020, "BigInt literal"),
+            ("a = 1_000", EcmaVersion::Es2021, "Numeric separator"),
+            (
+                "a = /x/d",
+                EcmaVersion::Es2022,
+                "Regular expression flag 'd'",
+            ),
+        ] {
+            let options = |ecma_version| ParseOptions {
+                ecma_version,
+                ..ParseOptions::default()
+            };
+            assert!(parse_with(source, options(version)).is_ok(), "{source}");
+            let previous = match version {
+                EcmaVersion::Es2015 => EcmaVersion::Es5,
+                EcmaVersion::Es2017 => EcmaVersion::Es2016,
+                EcmaVersion::Es2018 => EcmaVersion::Es2017,
+                EcmaVersion::Es2019 => EcmaVersion::Es2018,
+                EcmaVersion::Es2020 => EcmaVersion::Es2019,
+                EcmaVersion::Es2021 => EcmaVersion::Es2020,
+                _ => EcmaVersion::Es2021,
+            };
+            assert_eq!(
+                parse_with(source, options(previous)),
+                Err(format!("{message} requires {version}")),
+            );
+        }
+        assert_eq!(EcmaVersion::Es2020.to_string(), "ES2020");
+        assert!(
+            parse_with(
+                "var a = [1, 2]; a = {b: 1}; var \\u0062",
+                ParseOptions {
+                    ecma_version: EcmaVersion::Es5,
+                    ..ParseOptions::default()
+                }
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_options() -> Result<(), String> {
+        let source = "return 1";
+        assert_eq!(
+            parse_with(source, ParseOptions::default()),
+            Err("'return' outside of function".to_string())
+        );
+        assert!(parse_with("function f() { return 1 }", ParseOptions::default()).is_ok());
+        assert!(parse_with("f = () => { return 1 }", ParseOptions::default()).is_ok());
+        assert!(parse_with("f = function () {}; return", ParseOptions::default()).is_err());
+        parse_with(
+            source,
+            ParseOptions {
+                allow_return_outside_function: true,
+                ..ParseOptions::default()
+            },
+        )?;
+        let options = ParseOptions {
+            source_type: SourceType::CommonJs,
+            ..ParseOptions::default()
+        };
+        let program = Parser::new_with_options(source.to_string(), options)?.parse()?;
+        assert_eq!(program.source_type, SourceType::CommonJs);
+
+        let hashbang = "#!/usr/bin/env node\na";
+        parse_with(hashbang, ParseOptions::default())?;
+        assert!(
+            parse_with(
+                hashbang,
+                ParseOptions {
+                    allow_hashbang: false,
+                    ..ParseOptions::default()
+                }
+            )
+            .is_err()
+        );
+
+        let options = ParseOptions {
+            preserve_parens: true,
+            ..ParseOptions::default()
+        };
+        let body = parse_with("((a)) = 1; (b, c)", options)?;
+        let [
+            ModuleItem::Statement(Statement::ExpressionStatement {
+                expression: first, ..
+            }),
+            ModuleItem::Statement(Statement::ExpressionStatement {
+                expression: second, ..
+            }),
+        ] = &body[..]
+        else {
+            panic!("expect two expression statements");
+        };
+        assert!(matches!(**first, Expression::AssignmentExpression { .. }));
+        let Expression::ParenthesizedExpression { expression, loc } = second.as_ref() else {
+            panic!("expect parentheses");
+        };
+        assert!(matches!(
+            **expression,
+            Expression::SequenceExpression { .. }
+        ));
+        assert_eq!((loc.start.index, loc.end.index), (11, 17));
+        Ok(())
+    }
+
+    fn first_expression(body: &[ModuleItem]) -> &Expression {
+        match body {
+            [
+                ModuleItem::Statement(Statement::ExpressionStatement { expression, .. }),
+                ..,
+            ] => expression,
+            _ => panic!("expect an expression statement"),
+        }
+    }
+
+    #[test]
+    fn test_await() -> Result<(), String> {
+        let module = ParseOptions {
+            source_type: SourceType::Module,
+            ..ParseOptions::default()
+        };
+        let allowed = ParseOptions {
+            allow_await_outside_function: true,
+            ..ParseOptions::default()
+        };
+        for options in [module.clone(), allowed.clone()] {
+            let body = parse_with("await a.b", options)?;
+            let Expression::AwaitExpression { argument, .. } = first_expression(&body) else {
+                panic!("expect an await expression");
+            };
+            assert!(matches!(**argument, Expression::MemberExpression(_)));
+        }
+        // In a script `await` is an identifier, and so it is in a function.
+        let body = parse_with("await", ParseOptions::default())?;
+        assert!(matches!(first_expression(&body), Expression::Identifier(_)));
+        assert!(parse_with("await a", ParseOptions::default()).is_err());
+        parse_with("function f() { await } g = () => await", allowed.clone())?;
+        assert_eq!(
+            parse_with("function f() { await a }", module.clone()),
+            Err(
+                "'await' is only allowed within async functions and at the top levels of modules"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse_with(
+                "await a",
+                ParseOptions {
+                    ecma_version: EcmaVersion::Es2021,
+                    ..module
+                }
+            ),
+            Err("Top-level 'await' requires ES2022".to_string())
+        );
+        assert_eq!(
+            parse_with(
+                "await a",
+                ParseOptions {
+                    ecma_version: EcmaVersion::Es2016,
+                    ..allowed
+                }
+            ),
+            Err("'await' requires ES2017".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_proposals() -> Result<(), String> {
+        let source = "f = () => throw a";
+        assert!(parse_with(source, ParseOptions::default()).is_err());
+        let mut options = ParseOptions::default();
+        options.proposals.throw_expressions = true;
+        let body = parse_with(source, options)?;
+        let Expression::AssignmentExpression { right, .. } = first_expression(&body) else {
+            panic!("expect an assignment");
+        };
+        let Expression::ArrowFunctionExpression { body, .. } = right.as_ref() else {
+            panic!("expect an arrow function");
+        };
+        assert!(matches!(
+            body,
+            ArrowFunctionBody::Expression(expression)
+                if matches!(&**expression, Expression::UnaryExpression { operator, .. } if operator == "throw")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_export() -> Result<(), String> {
+        let module = ParseOptions {
+            source_type: SourceType::Module,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_with("import a from 'a'", ParseOptions::default()),
+            Err("'import' and 'export' may appear only with 'sourceType: module'".to_string())
+        );
+        assert_eq!(
+            parse_with("export default 1", module.clone()),
+            Err("'export' declarations are not supported".to_string())
+        );
+        parse_with("import('a'); import.meta", module.clone())?;
+        parse_with("a.import = {export: 1, enum() {}}", ParseOptions::default())?;
+        let es2019 = ParseOptions {
+            ecma_version: EcmaVersion::Es2019,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_with("import('a')", es2019),
+            Err("Dynamic 'import' requires ES2020".to_string())
+        );
+        assert_eq!(
+            parse_with("a = import.meta", ParseOptions::default()),
+            Err("'import.meta' may appear only with 'sourceType: module'".to_string())
+        );
+        assert_eq!(
+            parse_with("import.url", module),
+            Err("The only valid meta property for import is 'import.meta'".to_string())
+        );
+        for (source, name) in [
+            ("var import", "import"),
+            ("a = import", "import"),
+            ("f = ({export}) => 1", "export"),
+            ("function enum() {}", "enum"),
+            ("function f(enum) {}", "enum"),
+            ("try {} catch (export) {}", "export"),
+            ("a = {enum}", "enum"),
+        ] {
+            assert_eq!(
+                parse_with(source, ParseOptions::default()),
+                Err(format!("Unexpected reserved word '{name}'")),
+                "{source}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tolerant() -> Result<(), String> {
+        let source = "let a = ;\nf(a b);\nif (x) { g(; h() }\n}\nlet c = 1; d @ e";
+        let (program, diagnostics) = Parser::new(source.to_string())?.parse_tolerant();
+        let errors: Vec<(usize, usize)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.loc.start.index, diagnostic.loc.end.index))
+            .collect();
+        assert_eq!(errors, [(8, 9), (14, 15), (29, 30), (37, 38), (52, 52)]);
+        assert_eq!(diagnostics[3].message, "Unexpected token }");
+        let statements: Vec<String> = program
+            .body
+            .iter()
+            .map(|ModuleItem::Statement(statement)| match statement {
+                Statement::Invalid { loc } => {
+                    format!("Invalid {}..{}", loc.start.index, loc.end.index)
+                }
+                Statement::IfStatement { .. } => "If".to_string(),
+                Statement::Declaration(_) => "Declaration".to_string(),
+                Statement::ExpressionStatement { .. } => "Expression".to_string(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            statements,
+            [
+                "Invalid 0..9",
+                "Invalid 10..17",
+                "If",
+                "Invalid 37..38",
+                "Declaration",
+                "Expression"
+            ]
+        );
+        let ModuleItem::Statement(Statement::IfStatement { consequent, .. }) = &program.body[2]
+        else {
+            unreachable!()
+        };
+        let Statement::BlockStatement(block) = consequent.as_ref() else {
+            unreachable!()
+        };
+        assert!(matches!(
+            block.body[..],
+            [
+                Statement::Invalid { .. },
+                Statement::ExpressionStatement { .. }
+            ]
+        ));
+        assert!(Parser::new(source.to_string())?.parse().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tolerant_line_break() -> Result<(), String> {
+        let kinds = |source: &str| -> Result<Vec<&str>, String> {
+            let (program, _) = Parser::new(source.to_string())?.parse_tolerant();
+            Ok(program
+                .body
+                .iter()
+                .map(|ModuleItem::Statement(statement)| match statement {
+                    Statement::Invalid { .. } => "Invalid",
+                    _ => "Statement",
+                })
+                .collect())
+        };
+        assert_eq!(
+            kinds("x = )\ny()\nz()")?,
+            ["Invalid", "Statement", "Statement"]
+        );
+        assert_eq!(kinds("}\ny()\nz()")?, ["Invalid", "Statement", "Statement"]);
+        // A lexer error on the first token is reported, not returned.
+        let (program, diagnostics) = Parser::new("'a".to_string())?.parse_tolerant();
+        assert!(program.body.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].loc.start.index, 0);
+        let diagnostic = Parser::new("'a".to_string())?.parse().unwrap_err();
+        assert_eq!(diagnostic.message, diagnostics[0].message);
+        Ok(())
+    }
+
+    #[test]
+    fn test_directives() -> Result<(), String> {
+        let program = Parser::new(
+            "'use strict'; \"a\";\n(\"b\"); \"c\"; function f() { \"use strict\"; g }".to_string(),
+        )?
+        .parse()?;
+        let values: Vec<&str> = program
+            .directives
+            .iter()
+            .map(Directive::raw_value)
+            .collect();
+        assert_eq!(values, ["use strict", "a"]);
+        assert_eq!(program.body.len(), 3);
+        let ModuleItem::Statement(Statement::Declaration(Declaration::FunctionDeclaration(f))) =
+            &program.body[2]
+        else {
+            panic!("expect a function");
+        };
+        assert_eq!(f.body.directives.len(), 1);
+        assert_eq!(f.body.body.len(), 1);
+        let program = Parser::new("{ 'use strict' } with (a) {}".to_string())?.parse()?;
+        assert!(program.directives.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict() {
+        let sloppy = "with (a) b; a = 010 + 08 + '\\07'; delete a; eval = 1; arguments++;\n\
+                      var static; function f(a, a) {} g = (eval) => 1; implements: for (;;) break implements;\n\
+                      for (eval in a);";
+        assert!(parse_with(sloppy, ParseOptions::default()).is_ok());
+        let strict =
+            "'use strict'; a = '\\0'; a.static = {static: 1}; function f() { return } a = 0.5";
+        assert!(parse_with(strict, ParseOptions::default()).is_ok());
+        // Strictness ends with the function that asked for it.
+        assert!(
+            parse_with(
+                "function f() { 'use strict' } with (a) {}",
+                ParseOptions::default()
+            )
+            .is_ok()
+        );
+        for (source, message) in [
+            ("'use strict'; with (a) {}", "'with' in strict mode"),
+            (
+                "'use strict'; function f() { with (a) {} }",
+                "'with' in strict mode",
+            ),
+            (
+                "'use strict'; a = 010",
+                "Legacy octal literals are not allowed in strict mode",
+            ),
+            (
+                "'use strict'; a = 08",
+                "Legacy octal literals are not allowed in strict mode",
+            ),
+            (
+                "'use strict'; a = {'\\1': 1}",
+                "Octal escape sequences are not allowed in strict mode",
+            ),
+            (
+                "'\\08'; 'use strict'",
+                "Octal escape sequences are not allowed in strict mode",
+            ),
+            (
+                "'use strict'; delete (a)",
+                "Deleting local variable in strict mode",
+            ),
+            (
+                "'use strict'; eval = 1",
+                "Assigning to 'eval' in strict mode",
+            ),
+            (
+                "'use strict'; [a, arguments] = b",
+                "Assigning to 'arguments' in strict mode",
+            ),
+            ("'use strict'; --eval", "Assigning to 'eval' in strict mode"),
+            (
+                "'use strict'; var arguments",
+                "Binding 'arguments' in strict mode",
+            ),
+            (
+                "'use strict'; try {} catch (eval) {}",
+                "Binding 'eval' in strict mode",
+            ),
+            (
+                "function eval() { 'use strict' }",
+                "Binding 'eval' in strict mode",
+            ),
+            (
+                "function f(a, [a]) { 'use strict' }",
+                "Illegal 'use strict' directive in function with non-simple parameter list",
+            ),
+            (
+                "function f(a, a) { 'use strict' }",
+                "Duplicate parameter name 'a' in strict mode",
+            ),
+            (
+                "'use strict'; g = function (a, {b: [a]}) {}",
+                "Duplicate parameter name 'a' in strict mode",
+            ),
+            // Arrow functions reject it in sloppy mode too.
+            (
+                "g = (a, {b: [a]}) => 1",
+                "Duplicate parameter name 'a' in arrow function",
+            ),
+            (
+                "'use strict'; g = (a, a) => {}",
+                "Duplicate parameter name 'a' in arrow function",
+            ),
+            (
+                "'use strict'; for (eval in a);",
+                "Assigning to 'eval' in strict mode",
+            ),
+            (
+                "function f() { 'use strict'; for (arguments in a); }",
+                "Assigning to 'arguments' in strict mode",
+            ),
+            (
+                "'use strict'; var static",
+                "Unexpected strict mode reserved word 'static'",
+            ),
+            (
+                "function f() { 'use strict'; implements }",
+                "Unexpected strict mode reserved word 'implements'",
+            ),
+            (
+                "a = {m(public) { 'use strict' }}",
+                "Unexpected strict mode reserved word 'public'",
+            ),
+        ] {
+            assert_eq!(
+                parse_with(source, ParseOptions::default()),
+                Err(message.to_string()),
+                "{source}"
+            );
+        }
+        let module = ParseOptions {
+            source_type: SourceType::Module,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_with("with (a) {}", module),
+            Err("'with' in strict mode".to_string())
+        );
+    }
+
+    #[test]
+    fn test_escaped_keywords() {
+        // An escaped keyword is fine wherever a keyword would be a name.
+        for source in [
+            r"a.\u0069f",
+            r"({\u0069f: 1})",
+            r"({ n\u0065w() {} })",
+            r"var l\u0065t, y\u0069eld",
+        ] {
+            assert!(
+                parse_with(source, ParseOptions::default()).is_ok(),
+                "{source}"
+            );
+        }
+        for (source, name) in [
+            (r"var \u0069f", "if"),
+            (r"\u0069f (a) b", "if"),
+            (r"({\u0069f})", "if"),
+            (r"function \u0069f() {}", "if"),
+            (r"function f(n\u0065w) {}", "new"),
+            (r"'use strict'; var l\u0065t", "let"),
+            (r"'use strict'; y\u0069eld = 1", "yield"),
+        ] {
+            assert_eq!(
+                parse_with(source, ParseOptions::default()),
+                Err(format!(
+                    "Keyword must not contain escaped characters: {name}"
+                )),
+                "{source}"
+            );
+        }
+    }
+
     #[test]
     fn test_return() {
         let mut parser = Parser::new("return 1+2;".to_string()).unwrap();
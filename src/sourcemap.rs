@@ -0,0 +1,657 @@
+//! Source Map v3 (https://tc39.es/source-map/). Lines and columns are
+//! 0-based here, as in the format; columns count UTF-16 code units.
+
+use crate::json::{Json, string};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub generated_line: u32,
+    pub generated_column: u32,
+    /// Index into `sources`.
+    pub source: u32,
+    pub original_line: u32,
+    pub original_column: u32,
+    /// Index into `names`.
+    pub name: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub file: Option<String>,
+    pub sources: Vec<String>,
+    /// Parallel to `sources`; only written when some entry is present.
+    pub sources_content: Vec<Option<String>>,
+    pub names: Vec<String>,
+    /// Ordered by generated position.
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    pub fn add_source(&mut self, source: &str, content: Option<&str>) -> u32 {
+        self.sources.push(source.to_string());
+        self.sources_content.push(content.map(str::to_string));
+        (self.sources.len() - 1) as u32
+    }
+
+    pub fn add_name(&mut self, name: &str) -> u32 {
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => index as u32,
+            None => {
+                self.names.push(name.to_string());
+                (self.names.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Adds a mapping after the last one. A mapping at the same generated
+    /// position as the last replaces it, so the innermost node wins.
+    pub fn add_mapping(&mut self, mapping: Mapping) {
+        if let Some(last) = self.mappings.last_mut()
+            && last.generated_line == mapping.generated_line
+            && last.generated_column == mapping.generated_column
+        {
+            *last = mapping;
+            return;
+        }
+        self.mappings.push(mapping);
+    }
+
+    /// The `mappings` field: lines separated by `;`, segments by `,`, each
+    /// segment a run of VLQ deltas.
+    pub fn encode_mappings(&self) -> String {
+        let mut out = String::new();
+        let mut line = 0;
+        let mut column = 0;
+        let mut source = 0;
+        let mut original_line = 0;
+        let mut original_column = 0;
+        let mut name = 0;
+        for (i, mapping) in self.mappings.iter().enumerate() {
+            if mapping.generated_line != line {
+                while line < mapping.generated_line {
+                    out.push(';');
+                    line += 1;
+                }
+                column = 0;
+            } else if i > 0 {
+                out.push(',');
+            }
+            encode_vlq(mapping.generated_column as i64 - column, &mut out);
+            encode_vlq(mapping.source as i64 - source, &mut out);
+            encode_vlq(mapping.original_line as i64 - original_line, &mut out);
+            encode_vlq(mapping.original_column as i64 - original_column, &mut out);
+            column = mapping.generated_column as i64;
+            source = mapping.source as i64;
+            original_line = mapping.original_line as i64;
+            original_column = mapping.original_column as i64;
+            if let Some(index) = mapping.name {
+                encode_vlq(index as i64 - name, &mut out);
+                name = index as i64;
+            }
+        }
+        out
+    }
+
+    /// Reads a map from its JSON text. Index maps (`sections`) are not
+    /// supported.
+    pub fn parse(input: &str) -> Result<SourceMap, String> {
+        let json = Json::parse(input)?;
+        let Json::Object(members) = &json else {
+            return Err("source map: expect an object".to_string());
+        };
+        let get = |key: &str| members.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        if get("version") != Some(&Json::Number(3.0)) {
+            return Err("source map: expect version 3".to_string());
+        }
+        if get("sections").is_some() {
+            return Err("source map: index maps are not supported".to_string());
+        }
+        let strings = |key: &str| -> Result<Vec<Option<String>>, String> {
+            match get(key) {
+                None | Some(Json::Null) => Ok(vec![]),
+                Some(Json::Array(items)) => items
+                    .iter()
+                    .map(|item| match item {
+                        Json::String(s) => Ok(Some(s.clone())),
+                        Json::Null => Ok(None),
+                        _ => Err(format!("source map: expect strings in {key}")),
+                    })
+                    .collect(),
+                Some(_) => Err(format!("source map: expect an array for {key}")),
+            }
+        };
+        let root = match get("sourceRoot") {
+            Some(Json::String(root)) if !root.is_empty() && !root.ends_with('/') => {
+                format!("{root}/")
+            }
+            Some(Json::String(root)) => root.clone(),
+            _ => String::new(),
+        };
+        let sources: Vec<String> = strings("sources")?
+            .into_iter()
+            .map(|source| format!("{root}{}", source.unwrap_or_default()))
+            .collect();
+        let mut sources_content = strings("sourcesContent")?;
+        sources_content.resize(sources.len(), None);
+        let names: Vec<String> = strings("names")?
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect();
+        let mappings = match get("mappings") {
+            Some(Json::String(mappings)) => decode_mappings(mappings)?,
+            _ => return Err("source map: expect a string for mappings".to_string()),
+        };
+        let out_of_range = mappings.iter().any(|m| {
+            m.source as usize >= sources.len()
+                || m.name.is_some_and(|name| name as usize >= names.len())
+        });
+        if out_of_range {
+            return Err("source map: mapping refers to a missing source or name".to_string());
+        }
+        let file = match get("file") {
+            Some(Json::String(file)) => Some(file.clone()),
+            _ => None,
+        };
+        Ok(SourceMap {
+            file,
+            sources,
+            sources_content,
+            names,
+            mappings,
+        })
+    }
+
+    /// Traces every mapping through `input`, the map of the file this map's
+    /// original positions point into, so the result points at `input`'s
+    /// sources. Mappings that `input` does not cover are dropped.
+    pub fn compose(&self, input: &SourceMap) -> SourceMap {
+        let mut map = SourceMap {
+            file: self.file.clone(),
+            ..SourceMap::default()
+        };
+        let mut sources = vec![None; input.sources.len()];
+        for mapping in &self.mappings {
+            let Some(traced) = input.lookup(mapping.original_line, mapping.original_column) else {
+                continue;
+            };
+            let source = *sources[traced.source as usize].get_or_insert_with(|| {
+                map.add_source(
+                    &input.sources[traced.source as usize],
+                    input.sources_content[traced.source as usize].as_deref(),
+                )
+            });
+            // the name in `input` is the one from the true original source
+            let name = match (traced.name, mapping.name) {
+                (Some(name), _) => Some(map.add_name(&input.names[name as usize])),
+                (None, Some(name)) => Some(map.add_name(&self.names[name as usize])),
+                (None, None) => None,
+            };
+            map.add_mapping(Mapping {
+                generated_line: mapping.generated_line,
+                generated_column: mapping.generated_column,
+                source,
+                original_line: traced.original_line,
+                original_column: traced.original_column,
+                name,
+            });
+        }
+        map
+    }
+
+    /// The mapping covering a generated position: the last one on its line
+    /// that starts at or before `column`.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<&Mapping> {
+        let end = self
+            .mappings
+            .partition_point(|m| (m.generated_line, m.generated_column) <= (line, column));
+        self.mappings[..end]
+            .last()
+            .filter(|mapping| mapping.generated_line == line)
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut members = vec![("version".to_string(), Json::Number(3.0))];
+        if let Some(file) = &self.file {
+            members.push(("file".to_string(), string(file)));
+        }
+        members.push((
+            "sources".to_string(),
+            Json::Array(self.sources.iter().map(|s| string(s)).collect()),
+        ));
+        if self.sources_content.iter().any(Option::is_some) {
+            members.push((
+                "sourcesContent".to_string(),
+                Json::Array(
+                    self.sources_content
+                        .iter()
+                        .map(|content| content.as_deref().map_or(Json::Null, string))
+                        .collect(),
+                ),
+            ));
+        }
+        members.push((
+            "names".to_string(),
+            Json::Array(self.names.iter().map(|s| string(s)).collect()),
+        ));
+        members.push(("mappings".to_string(), string(&self.encode_mappings())));
+        Json::Object(members)
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_digit(c: char) -> Option<u32> {
+    BASE64
+        .iter()
+        .position(|&b| b as char == c)
+        .map(|i| i as u32)
+}
+
+/// Base64 VLQ: the sign goes in the lowest bit, then 5 bits per digit with
+/// bit 6 set on every digit but the last.
+pub fn encode_vlq(value: i64, out: &mut String) {
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+/// Reads one VLQ value from `chars`.
+pub fn decode_vlq(chars: &mut impl Iterator<Item = char>) -> Result<i64, String> {
+    let mut value: i64 = 0;
+    let mut shift = 0;
+    loop {
+        let c = chars.next().ok_or("source map: unterminated VLQ")?;
+        let digit = base64_digit(c).ok_or(format!("source map: invalid VLQ character '{c}'"))?;
+        if shift > 60 {
+            return Err("source map: VLQ too large".to_string());
+        }
+        value |= ((digit & 0b11111) as i64) << shift;
+        shift += 5;
+        if digit & 0b100000 == 0 {
+            break;
+        }
+    }
+    Ok(if value & 1 == 1 {
+        -(value >> 1)
+    } else {
+        value >> 1
+    })
+}
+
+/// Decodes a `mappings` field. Segments with a single field map a generated
+/// column to nothing and are skipped.
+pub fn decode_mappings(mappings: &str) -> Result<Vec<Mapping>, String> {
+    let mut result = vec![];
+    let (mut source, mut original_line, mut original_column, mut name) = (0, 0, 0, 0);
+    for (line, text) in mappings.split(';').enumerate() {
+        let mut column = 0;
+        for segment in text.split(',').filter(|segment| !segment.is_empty()) {
+            let mut chars = segment.chars().peekable();
+            let mut fields = vec![];
+            while chars.peek().is_some() {
+                fields.push(decode_vlq(&mut chars)?);
+            }
+            column += fields[0];
+            match fields.len() {
+                1 => continue,
+                4 | 5 => {}
+                n => return Err(format!("source map: segment with {n} fields")),
+            }
+            source += fields[1];
+            original_line += fields[2];
+            original_column += fields[3];
+            let name = match fields.get(4) {
+                Some(delta) => {
+                    name += delta;
+                    Some(name)
+                }
+                None => None,
+            };
+            if [
+                column,
+                source,
+                original_line,
+                original_column,
+                name.unwrap_or(0),
+            ]
+            .iter()
+            .any(|n| *n < 0 || *n > u32::MAX as i64)
+            {
+                return Err("source map: mapping out of range".to_string());
+            }
+            result.push(Mapping {
+                generated_line: line as u32,
+                generated_column: column as u32,
+                source: source as u32,
+                original_line: original_line as u32,
+                original_column: original_column as u32,
+                name: name.map(|name| name as u32),
+            });
+        }
+    }
+    result.sort_by_key(|m| (m.generated_line, m.generated_column));
+    Ok(result)
+}
+
+/// The URL of a `# sourceMappingURL=` comment (or the older `@` form),
+/// given the comment text without its `//` or `/* */`.
+pub fn source_mapping_url(comment: &str) -> Option<String> {
+    let rest = comment
+        .strip_prefix("# sourceMappingURL=")
+        .or_else(|| comment.strip_prefix("@ sourceMappingURL="))?;
+    let url = rest.split_whitespace().next()?;
+    Some(url.to_string())
+}
+
+/// Loads the map a `sourceMappingURL` points at: an inline `data:` URL, or
+/// a relative file path resolved against `base_dir`, the directory of the
+/// file that carried the comment. A path may not leave `base_dir`. The
+/// map's `sources` are made relative to `base_dir` too.
+pub fn load(url: &str, base_dir: &Path) -> Result<SourceMap, String> {
+    if let Some(data) = url.strip_prefix("data:") {
+        let (header, payload) = data
+            .split_once(',')
+            .ok_or("source map: malformed data URL")?;
+        let text = if header.split(';').any(|part| part == "base64") {
+            String::from_utf8(decode_base64(payload)?)
+        } else {
+            String::from_utf8(percent_decode(payload)?)
+        }
+        .map_err(|_| "source map: data URL is not UTF-8".to_string())?;
+        return SourceMap::parse(&text);
+    }
+    if url.starts_with("file://") {
+        return Err(format!("source map: cannot load file URL {url}"));
+    }
+    if url.contains("://") {
+        return Err(format!("source map: cannot load remote map {url}"));
+    }
+    let path = percent_decode(url)
+        .ok()
+        .and_then(|path| String::from_utf8(path).ok())
+        .ok_or(format!("source map: malformed URL {url}"))?;
+    let parts = relative_path(&path).ok_or(format!(
+        "source map: {path} is outside the directory of the file"
+    ))?;
+    let text = fs::read_to_string(base_dir.join(parts.join("/")))
+        .map_err(|err| format!("source map: cannot read {path}: {err}"))?;
+    let mut map = SourceMap::parse(&text)?;
+    // `sources` are relative to the map itself.
+    if let Some((_, dir)) = parts.split_last()
+        && !dir.is_empty()
+    {
+        let dir = dir.join("/");
+        for source in &mut map.sources {
+            if !source.contains("://") && !source.starts_with('/') {
+                *source = format!("{dir}/{source}");
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// The parts of `path` with `.` and `..` resolved, unless it is absolute or
+/// a `..` climbs out of the directory it is relative to.
+fn relative_path(path: &str) -> Option<Vec<&str>> {
+    let drive = path.as_bytes().get(1) == Some(&b':');
+    if path.starts_with(['/', '\\']) || drive || Path::new(path).is_absolute() {
+        return None;
+    }
+    let mut parts = vec![];
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts)
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    let mut out = vec![];
+    let mut buffer = 0;
+    let mut bits = 0;
+    for c in input.chars().take_while(|c| *c != '=') {
+        let digit = base64_digit(c).ok_or(format!("source map: invalid base64 character '{c}'"))?;
+        buffer = (buffer << 6) | digit;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+fn percent_decode(input: &str) -> Result<Vec<u8>, String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input
+                .get(i + 1..i + 3)
+                .ok_or("source map: malformed URL escape")?;
+            let byte =
+                u8::from_str_radix(hex, 16).map_err(|_| "source map: malformed URL escape")?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test_sourcemap {
+    use super::*;
+    use crate::codegen::{CodegenOptions, SourceMapOptions, print_with_source_map};
+    use crate::parser::Parser;
+
+    fn vlq(value: i64) -> String {
+        let mut out = String::new();
+        encode_vlq(value, &mut out);
+        out
+    }
+
+    fn mapping(line: u32, column: u32, original: (u32, u32), name: Option<u32>) -> Mapping {
+        Mapping {
+            generated_line: line,
+            generated_column: column,
+            source: 0,
+            original_line: original.0,
+            original_column: original.1,
+            name,
+        }
+    }
+
+    #[test]
+    fn test_vlq() {
+        assert_eq!(vlq(0), "A");
+        assert_eq!(vlq(1), "C");
+        assert_eq!(vlq(-1), "D");
+        assert_eq!(vlq(15), "e");
+        assert_eq!(vlq(16), "gB");
+        assert_eq!(vlq(-17), "jB");
+        assert_eq!(vlq(1000), "w+B");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut map = SourceMap {
+            file: Some("out.js".to_string()),
+            ..SourceMap::default()
+        };
+        map.add_source("in.js", None);
+        let name = map.add_name("foo");
+        assert_eq!(map.add_name("foo"), name);
+        map.add_mapping(mapping(0, 0, (0, 0), None));
+        map.add_mapping(mapping(0, 0, (0, 4), Some(name)));
+        map.add_mapping(mapping(0, 6, (1, 2), None));
+        map.add_mapping(mapping(2, 2, (1, 0), None));
+        assert_eq!(
+            map.to_json().to_string_compact(),
+            r#"{"version":3,"file":"out.js","sources":["in.js"],"names":["foo"],"mappings":"AAAIA,MACF;;EAAF"}"#
+        );
+    }
+
+    #[test]
+    fn test_decode() -> Result<(), String> {
+        for value in [0, 1, -1, 15, 16, -17, 1000, -123456789] {
+            assert_eq!(decode_vlq(&mut vlq(value).chars())?, value);
+        }
+        let mappings = "AAAIA,MACF;;EAAF,C;A";
+        let decoded = decode_mappings(mappings)?;
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[1], mapping(0, 6, (1, 2), None));
+        let map = SourceMap {
+            sources: vec!["in.js".to_string()],
+            names: vec!["foo".to_string()],
+            mappings: decoded,
+            ..SourceMap::default()
+        };
+        assert_eq!(map.encode_mappings(), "AAAIA,MACF;;EAAF");
+        assert!(decode_mappings("g").is_err());
+        assert!(decode_mappings("A!").is_err());
+        assert!(decode_mappings("AA").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let map = SourceMap::parse(
+            r#"{"version":3,"sourceRoot":"src","sources":["a.ts","b.ts"],"sourcesContent":["x"],"names":[],"mappings":"AAAA,CCCC"}"#,
+        )?;
+        assert_eq!(map.sources, vec!["src/a.ts", "src/b.ts"]);
+        assert_eq!(map.sources_content, vec![Some("x".to_string()), None]);
+        assert_eq!(map.mappings[1].source, 1);
+        assert_eq!(map.lookup(0, 0), Some(&map.mappings[0]));
+        assert_eq!(map.lookup(0, 5), Some(&map.mappings[1]));
+        assert_eq!(map.lookup(1, 0), None);
+        assert!(SourceMap::parse(r#"{"version":2,"sources":[],"mappings":""}"#).is_err());
+        assert!(SourceMap::parse(r#"{"version":3,"sources":[],"mappings":"AAAA"}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_mapping_url() -> Result<(), String> {
+        assert_eq!(
+            source_mapping_url("# sourceMappingURL=a.js.map "),
+            Some("a.js.map".to_string())
+        );
+        assert_eq!(
+            source_mapping_url("@ sourceMappingURL=b.map"),
+            Some("b.map".to_string())
+        );
+        assert_eq!(source_mapping_url(" sourceMappingURL=a.js.map"), None);
+        let mut parser = Parser::new("a;\n/*# sourceMappingURL=x.map */\nb\n".to_string())?;
+        parser.parse()?;
+        assert_eq!(parser.source_mapping_url.as_deref(), Some("x.map"));
+        let parser = Parser::new("//# sourceMappingURL=y.map".to_string())?;
+        assert_eq!(parser.source_mapping_url.as_deref(), Some("y.map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_inline() -> Result<(), String> {
+        let base64 = "data:application/json;charset=utf-8;base64,eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbImEudHMiXSwibmFtZXMiOlsieCJdLCJtYXBwaW5ncyI6IkFBQUFBIn0=";
+        let map = load(base64, Path::new("."))?;
+        assert_eq!(map.sources, vec!["a.ts"]);
+        assert_eq!(map.mappings, vec![mapping(0, 0, (0, 0), Some(0))]);
+        let plain = r#"data:application/json,{"version":3,"sources":["b%2Ets"],"mappings":""}"#;
+        assert_eq!(load(plain, Path::new("."))?.sources, vec!["b.ts"]);
+        assert!(load("https://example.com/a.map", Path::new(".")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_path() -> Result<(), String> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let map = load("sourcemap/./greet.js.map", &root)?;
+        assert_eq!(map.sources, vec!["sourcemap/src/greet.ts"]);
+        let dir = root.join("sourcemap");
+        assert_eq!(
+            load("x/../greet.js.map", &dir)?.sources,
+            vec!["src/greet.ts"]
+        );
+        for url in [
+            "/etc/passwd",
+            "file:///etc/passwd",
+            "../sourcemap/greet.js.map",
+            "x/../../greet.js.map",
+            "%2E%2E/greet.js.map",
+            "C:/greet.js.map",
+        ] {
+            let error = load(url, &dir).unwrap_err();
+            assert!(!error.contains("cannot read"), "{url}: {error}");
+        }
+        Ok(())
+    }
+
+    /// Prints compiled output minified and traces the new map through the
+    /// compiler's map back to the TypeScript source.
+    #[test]
+    fn test_compose() -> Result<(), String> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/sourcemap");
+        let source = fs::read_to_string(dir.join("greet.js")).unwrap();
+        let mut parser = Parser::new(source.clone())?;
+        let program = parser.parse()?;
+        let url = parser
+            .source_mapping_url
+            .ok_or("expect a sourceMappingURL")?;
+        let input = load(&url, &dir)?;
+
+        let options = SourceMapOptions {
+            source: "greet.js".to_string(),
+            file: Some("greet.min.js".to_string()),
+            sources_content: false,
+        };
+        let (printed, map) =
+            print_with_source_map(&program, &CodegenOptions::minified(), &source, &options);
+        assert_eq!(
+            printed,
+            "function greet(name){return\"Hello, \"+name}greet(\"world\")"
+        );
+        let composed = map.compose(&input);
+        assert_eq!(composed.file.as_deref(), Some("greet.min.js"));
+        assert_eq!(composed.sources, vec!["src/greet.ts"]);
+        assert_eq!(composed.sources_content, input.sources_content);
+        // `name` in `+name` and the second `greet` call, in TypeScript terms
+        let plus_name = printed.find("+name").unwrap() as u32 + 1;
+        let call = printed.rfind("greet").unwrap() as u32;
+        assert_eq!(
+            composed
+                .lookup(0, plus_name)
+                .map(|m| (m.original_line, m.original_column)),
+            Some((1, 21))
+        );
+        assert_eq!(
+            composed
+                .lookup(0, call)
+                .map(|m| (m.original_line, m.original_column)),
+            Some((3, 0))
+        );
+        Ok(())
+    }
+}
@@ -44,14 +44,34 @@ pub enum Token {
 
     Variable(String),
     Digit(String),
-    String(String),
+    BigInt(String),
+    String(String, String),
     Control(String),
     Comment(String),
+    BlockComment(String),
+    Hashbang(String),
     TemplateStr(String),
     Regex(String, String),
     EOF,
 }
 
+/// Text between tokens, kept only by the lossless syntax tree.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TriviaKind {
+    Whitespace,
+    /// One line terminator; `\r\n` counts as one.
+    Newline,
+    LineComment,
+    BlockComment,
+    Hashbang,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -61,11 +81,11 @@ impl Display for Token {
             Token::Variable(s) => {
                 write!(f, "{}", s)
             }
-            Token::Digit(s) => {
+            Token::Digit(s) | Token::BigInt(s) => {
                 write!(f, "{}", s)
             }
-            Token::Comment(_) => write!(f, "Comment"),
-            Token::String(_) => write!(f, "String"),
+            Token::Comment(_) | Token::BlockComment(_) => write!(f, "Comment"),
+            Token::String(..) => write!(f, "String"),
             Token::EOF => write!(f, "EOF"),
 
             Token::Var => write!(f, "var"),
@@ -0,0 +1,449 @@
+//! Runs [`Lex`] on its own, for highlighting and quick scans. Without a
+//! parser to say whether a `/` starts a regex, a stack of open brackets and
+//! the previous token decide: after `)` of `if (...)`, the `}` of a block or
+//! a function declaration, an operator or most keywords it is a regex,
+//! otherwise a divide. Templates are split at `${` and `}` the way esprima
+//! does.
+
+use crate::lex::{Lex, Loc};
+use crate::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenType {
+    Boolean,
+    Identifier,
+    Keyword,
+    Null,
+    Numeric,
+    Punctuator,
+    String,
+    RegularExpression,
+    Template,
+    LineComment,
+    BlockComment,
+}
+
+impl TokenType {
+    /// The name esprima uses for the type.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenType::Boolean => "Boolean",
+            TokenType::Identifier => "Identifier",
+            TokenType::Keyword => "Keyword",
+            TokenType::Null => "Null",
+            TokenType::Numeric => "Numeric",
+            TokenType::Punctuator => "Punctuator",
+            TokenType::String => "String",
+            TokenType::RegularExpression => "RegularExpression",
+            TokenType::Template => "Template",
+            TokenType::LineComment => "LineComment",
+            TokenType::BlockComment => "BlockComment",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceToken {
+    pub kind: TokenType,
+    /// As esprima reports it: the name of an identifier, the text of a
+    /// comment without its delimiters and the raw text of everything else.
+    pub value: String,
+    pub raw: String,
+    pub token: Token,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    /// Yield comments too, including a hashbang as a line comment.
+    pub comments: bool,
+}
+
+pub fn tokenize(source: &str, options: TokenizeOptions) -> Tokens {
+    Tokens {
+        lex: Lex::new(source.to_string()),
+        chars: source.chars().collect(),
+        options,
+        brackets: vec![],
+        regex_allowed: true,
+        block_allowed: true,
+        previous_keyword: None,
+        property_next: false,
+        function: None,
+        previous_line: 1,
+        done: false,
+    }
+}
+
+enum Bracket {
+    /// `regex_after` is set for the condition of `if`, `while`, `for` and
+    /// `with`; `function` for the parameters of a function.
+    Paren {
+        regex_after: bool,
+        function: Option<Function>,
+    },
+    Square,
+    /// `regex_after` is set for a block or the body of a function
+    /// declaration, and clear for an object or the body of a function
+    /// expression, which a `/` divides.
+    Brace {
+        regex_after: bool,
+    },
+    /// The `${` of a template substitution.
+    Template,
+}
+
+pub struct Tokens {
+    lex: Lex,
+    chars: Vec<char>,
+    options: TokenizeOptions,
+    brackets: Vec<Bracket>,
+    /// Whether a `/` here would start a regex.
+    regex_allowed: bool,
+    /// Whether a `{` here would open a block rather than an object.
+    block_allowed: bool,
+    previous_keyword: Option<Token>,
+    /// Whether the previous token was `.` or `?.`, after which a keyword
+    /// names a property.
+    property_next: bool,
+    /// A `function` keyword whose parameters have not started yet, or
+    /// whose body is next after its `)`.
+    function: Option<Function>,
+    /// The line the previous token ends on.
+    previous_line: usize,
+    done: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Declaration,
+    Expression,
+}
+
+impl Tokens {
+    fn read(&mut self) -> Result<Option<SourceToken>, String> {
+        loop {
+            let (mut token, mut loc) = self.lex.next()?;
+            match &token {
+                Token::EOF if self.brackets.iter().any(|b| matches!(b, Bracket::Template)) => {
+                    return Err("Unterminated template".to_string());
+                }
+                Token::EOF => return Ok(None),
+                Token::Comment(_) | Token::BlockComment(_) | Token::Hashbang(_) => {
+                    if self.options.comments {
+                        return Ok(Some(self.source_token(token, loc)));
+                    }
+                    continue;
+                }
+                Token::Control(s) if (s == "/" || s == "/=") && self.regex_allowed => {
+                    (token, loc) = self.lex.reread_as_regex()?;
+                }
+                Token::Control(s) if s == "}" => {
+                    if let Some(Bracket::Template) = self.brackets.last() {
+                        self.brackets.pop();
+                        (token, loc) = self.lex.reread_as_template_part()?;
+                    }
+                }
+                Token::TemplateStr(_) => (token, loc) = self.lex.reread_as_template_part()?,
+                _ if self.property_next
+                    && matches!(
+                        token_type(&token),
+                        TokenType::Keyword | TokenType::Boolean | TokenType::Null
+                    ) =>
+                {
+                    let name = self.chars[loc.start.index..loc.end.index].iter().collect();
+                    token = Token::Variable(name);
+                }
+                _ => {}
+            }
+            let source_token = self.source_token(token, loc);
+            self.advance(&source_token);
+            return Ok(Some(source_token));
+        }
+    }
+
+    /// Updates the bracket stack and what may follow `token`.
+    fn advance(&mut self, token: &SourceToken) {
+        let mut block_allowed = false;
+        let mut function = None;
+        self.regex_allowed = match token.kind {
+            TokenType::Punctuator => match token.raw.as_str() {
+                "(" => {
+                    let regex_after = matches!(
+                        self.previous_keyword,
+                        Some(Token::If | Token::While | Token::For | Token::With)
+                    );
+                    self.brackets.push(Bracket::Paren {
+                        regex_after,
+                        function: self.function,
+                    });
+                    true
+                }
+                ")" => {
+                    let (regex_after, params_of) = match self.brackets.pop() {
+                        Some(Bracket::Paren {
+                            regex_after,
+                            function,
+                        }) => (regex_after, function),
+                        _ => (false, None),
+                    };
+                    function = params_of;
+                    // Only a block or a function body can follow `)`.
+                    block_allowed = true;
+                    regex_after
+                }
+                "[" => {
+                    self.brackets.push(Bracket::Square);
+                    true
+                }
+                "]" => {
+                    self.brackets.pop();
+                    false
+                }
+                "{" => {
+                    // A `{` that cannot continue the previous expression
+                    // starts a statement after an inserted semicolon.
+                    let after_semicolon =
+                        !self.regex_allowed && token.loc.start.line > self.previous_line;
+                    let regex_after = match self.function {
+                        Some(Function::Declaration) => true,
+                        Some(Function::Expression) => false,
+                        None => self.block_allowed || after_semicolon,
+                    };
+                    self.brackets.push(Bracket::Brace { regex_after });
+                    block_allowed = true;
+                    true
+                }
+                "}" => {
+                    let regex_after = matches!(
+                        self.brackets.pop(),
+                        Some(Bracket::Brace { regex_after: true })
+                    );
+                    block_allowed = regex_after;
+                    regex_after
+                }
+                ";" => {
+                    block_allowed = true;
+                    true
+                }
+                "=>" => {
+                    block_allowed = true;
+                    true
+                }
+                "++" | "--" => false,
+                // So does the `*` of a generator.
+                "*" if self.function.is_some() => {
+                    function = self.function;
+                    true
+                }
+                _ => true,
+            },
+            TokenType::Keyword => {
+                if token.token == Token::Function {
+                    // Where a block could start, so could a declaration.
+                    function = Some(if self.block_allowed {
+                        Function::Declaration
+                    } else {
+                        Function::Expression
+                    });
+                }
+                block_allowed = matches!(
+                    token.token,
+                    Token::Else | Token::Do | Token::Try | Token::Finally
+                );
+                !matches!(token.token, Token::This)
+            }
+            // The name of a function keeps it pending.
+            TokenType::Identifier if self.function.is_some() => {
+                function = self.function;
+                false
+            }
+            TokenType::Template if token.raw.ends_with("${") => {
+                self.brackets.push(Bracket::Template);
+                true
+            }
+            _ => false,
+        };
+        self.block_allowed = block_allowed;
+        self.function = function;
+        self.previous_keyword = (token.kind == TokenType::Keyword).then(|| token.token.clone());
+        self.property_next =
+            token.kind == TokenType::Punctuator && matches!(token.raw.as_str(), "." | "?.");
+        self.previous_line = token.loc.end.line;
+    }
+
+    fn source_token(&self, token: Token, loc: Loc) -> SourceToken {
+        let raw: String = self.chars[loc.start.index..loc.end.index].iter().collect();
+        let kind = token_type(&token);
+        let value = match &token {
+            Token::Variable(name) => name.clone(),
+            Token::Comment(value) | Token::BlockComment(value) | Token::Hashbang(value) => {
+                value.clone()
+            }
+            _ => raw.clone(),
+        };
+        SourceToken {
+            kind,
+            value,
+            raw,
+            token,
+            loc,
+        }
+    }
+}
+
+impl Iterator for Tokens {
+    type Item = Result<SourceToken, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// `async`, `await` and `undefined` are identifiers to esprima, `let` and
+/// `yield` keywords.
+fn token_type(token: &Token) -> TokenType {
+    match token {
+        Token::Variable(_) | Token::Undefined | Token::Async | Token::Await => {
+            TokenType::Identifier
+        }
+        Token::True | Token::False => TokenType::Boolean,
+        Token::Null => TokenType::Null,
+        Token::Digit(_) | Token::BigInt(_) => TokenType::Numeric,
+        Token::String(..) => TokenType::String,
+        Token::TemplateStr(_) => TokenType::Template,
+        Token::Regex(..) => TokenType::RegularExpression,
+        Token::Control(_) => TokenType::Punctuator,
+        Token::Comment(_) | Token::Hashbang(_) => TokenType::LineComment,
+        Token::BlockComment(_) => TokenType::BlockComment,
+        _ => TokenType::Keyword,
+    }
+}
+
+#[cfg(test)]
+mod test_tokenize {
+    use super::*;
+
+    fn scan(source: &str, comments: bool) -> Result<Vec<(&'static str, String)>, String> {
+        tokenize(source, TokenizeOptions { comments })
+            .map(|token| token.map(|token| (token.kind.name(), token.value)))
+            .collect()
+    }
+
+    fn raws(source: &str) -> Result<Vec<String>, String> {
+        tokenize(source, TokenizeOptions::default())
+            .map(|token| token.map(|token| token.raw))
+            .collect()
+    }
+
+    #[test]
+    fn test_types() -> Result<(), String> {
+        let tokens = scan("let \\u0061 = async /* c */ + 1n ?? 'x' // d\n", true)?;
+        let expected = [
+            ("Keyword", "let"),
+            ("Identifier", "a"),
+            ("Punctuator", "="),
+            ("Identifier", "async"),
+            ("BlockComment", " c "),
+            ("Punctuator", "+"),
+            ("Numeric", "1n"),
+            ("Punctuator", "??"),
+            ("String", "'x'"),
+            ("LineComment", " d"),
+        ];
+        assert_eq!(
+            tokens,
+            expected.map(|(kind, value)| (kind, value.to_string()))
+        );
+        assert_eq!(scan("a /* c */", false)?.len(), 1);
+        assert_eq!(
+            scan("a.if.null", false)?[2..],
+            [
+                ("Identifier", "if".to_string()),
+                ("Punctuator", ".".to_string()),
+                ("Identifier", "null".to_string())
+            ]
+        );
+        let token = tokenize("\n  null", TokenizeOptions::default())
+            .next()
+            .unwrap()?;
+        assert_eq!(token.kind, TokenType::Null);
+        assert_eq!((token.loc.start.line, token.loc.start.column), (2, 3));
+        assert_eq!((token.loc.start.index, token.loc.end.index), (3, 7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_heuristic() -> Result<(), String> {
+        for (source, expected) in [
+            ("a / b / c", vec!["a", "/", "b", "/", "c"]),
+            ("x = /=/g", vec!["x", "=", "/=/g"]),
+            (
+                "if (a) /re/.test(b)",
+                vec!["if", "(", "a", ")", "/re/", ".", "test", "(", "b", ")"],
+            ),
+            ("f(a) / 2", vec!["f", "(", "a", ")", "/", "2"]),
+            ("{} /re/", vec!["{", "}", "/re/"]),
+            (
+                "function f() {} /re/",
+                vec!["function", "f", "(", ")", "{", "}", "/re/"],
+            ),
+            ("x = {} / 2", vec!["x", "=", "{", "}", "/", "2"]),
+            ("return /re/", vec!["return", "/re/"]),
+            ("this / 2", vec!["this", "/", "2"]),
+            ("a[0] / 2", vec!["a", "[", "0", "]", "/", "2"]),
+            ("a++ / 2", vec!["a", "++", "/", "2"]),
+            (
+                "x = a.return / 2",
+                vec!["x", "=", "a", ".", "return", "/", "2"],
+            ),
+            ("y.if / 2 / 3", vec!["y", ".", "if", "/", "2", "/", "3"]),
+            ("a?.this / 2", vec!["a", "?.", "this", "/", "2"]),
+            (
+                "x = function(){} / 2",
+                vec!["x", "=", "function", "(", ")", "{", "}", "/", "2"],
+            ),
+            (
+                "x = function* g(a) { if (a) {} } / 2",
+                vec![
+                    "x", "=", "function", "*", "g", "(", "a", ")", "{", "if", "(", "a", ")", "{",
+                    "}", "}", "/", "2",
+                ],
+            ),
+            (
+                "function f() {}\n/re/.test(s)",
+                vec![
+                    "function", "f", "(", ")", "{", "}", "/re/", ".", "test", "(", "s", ")",
+                ],
+            ),
+            ("a\n{}\n/re/", vec!["a", "{", "}", "/re/"]),
+            (
+                "while (a) { x = {} }\n/re/",
+                vec!["while", "(", "a", ")", "{", "x", "=", "{", "}", "}", "/re/"],
+            ),
+        ] {
+            assert_eq!(raws(source)?, expected, "{source}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_template() -> Result<(), String> {
+        assert_eq!(
+            raws("`a${ {b: `c${d}`}.b / 2 }e` / 2")?,
+            [
+                "`a${", "{", "b", ":", "`c${", "d", "}`", "}", ".", "b", "/", "2", "}e`", "/", "2"
+            ]
+        );
+        assert_eq!(raws("`\\`${x}`")?, ["`\\`${", "x", "}`"]);
+        let mut tokens = tokenize("`a${b", TokenizeOptions::default());
+        assert!(tokens.next().unwrap().is_err());
+        assert!(tokens.next().is_none());
+        Ok(())
+    }
+}